@@ -0,0 +1,225 @@
+// Breaker module — Per-Symbol Market Data Circuit Breaker
+//
+// Features:
+// - Trips on crossed book, spread anomaly, or stale data
+// - While open, derived metrics/signals for the symbol are suppressed
+//   (ingestion continues so the book can recover)
+// - Auto-closes after conditions stay normal for a cooldown
+
+pub mod breaker {
+    use crate::orderbook::L2Orderbook;
+    use std::collections::HashMap;
+
+    /// Circuit breaker thresholds
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub struct BreakerConfig {
+        pub max_spread_bps: f64,
+        pub max_staleness_ns: i64,
+        pub cooldown_ns: i64,       // Normal conditions required before closing
+    }
+
+    impl Default for BreakerConfig {
+        fn default() -> Self {
+            Self {
+                max_spread_bps: 100.0,
+                max_staleness_ns: 5_000_000_000,    // 5s
+                cooldown_ns: 10_000_000_000,        // 10s
+            }
+        }
+    }
+
+    /// Anomaly that opened a circuit
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub enum TripReason {
+        CrossedBook,
+        SpreadAnomaly,
+        Stale,
+    }
+
+    /// Circuit state change
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub enum BreakerEvent {
+        CircuitOpen { symbol_hash: u64, reason: TripReason, ts_ns: i64 },
+        CircuitClosed { symbol_hash: u64, ts_ns: i64 },
+    }
+
+    #[derive(Clone, Copy, Debug, Default)]
+    struct SymbolCircuit {
+        open: Option<TripReason>,
+        healthy_since_ns: Option<i64>,
+        last_update_ns: i64,
+    }
+
+    /// Per-symbol circuit breaker
+    pub struct CircuitBreaker {
+        config: BreakerConfig,
+        symbols: HashMap<u64, SymbolCircuit>,   // symbol_hash -> circuit
+        pub trips: u64,
+    }
+
+    impl CircuitBreaker {
+        pub fn new(config: BreakerConfig) -> Self {
+            Self {
+                config,
+                symbols: HashMap::new(),
+                trips: 0,
+            }
+        }
+
+        /// Evaluate the book after an applied update - O(log n)
+        pub fn on_book_update(&mut self, book: &L2Orderbook, now_ns: i64) -> Option<BreakerEvent> {
+            let anomaly = self.book_anomaly(book);
+            let circuit = self.symbols.entry(book.symbol_hash).or_default();
+            circuit.last_update_ns = now_ns;
+            self.transition(book.symbol_hash, anomaly, now_ns)
+        }
+
+        /// Timer path - trips symbols that stopped updating
+        pub fn check_staleness(&mut self, now_ns: i64) -> Vec<BreakerEvent> {
+            let stale: Vec<u64> = self
+                .symbols
+                .iter()
+                .filter(|(_, c)| now_ns - c.last_update_ns > self.config.max_staleness_ns)
+                .map(|(&h, _)| h)
+                .collect();
+
+            stale
+                .into_iter()
+                .filter_map(|h| self.transition(h, Some(TripReason::Stale), now_ns))
+                .collect()
+        }
+
+        fn book_anomaly(&self, book: &L2Orderbook) -> Option<TripReason> {
+            let (bid, ask) = (book.best_bid()?, book.best_ask()?);
+            if bid >= ask {
+                return Some(TripReason::CrossedBook);
+            }
+            if bid > 0.0 && (ask - bid) / bid * 10_000.0 > self.config.max_spread_bps {
+                return Some(TripReason::SpreadAnomaly);
+            }
+            None
+        }
+
+        fn transition(&mut self, symbol_hash: u64, anomaly: Option<TripReason>, now_ns: i64) -> Option<BreakerEvent> {
+            let cooldown = self.config.cooldown_ns;
+            let circuit = self.symbols.entry(symbol_hash).or_default();
+
+            match (circuit.open, anomaly) {
+                (None, Some(reason)) => {
+                    circuit.open = Some(reason);
+                    circuit.healthy_since_ns = None;
+                    self.trips += 1;
+                    tracing::warn!(symbol_hash, ?reason, "circuit open, suppressing publishing");
+                    Some(BreakerEvent::CircuitOpen { symbol_hash, reason, ts_ns: now_ns })
+                }
+                (Some(_), Some(_)) => {
+                    circuit.healthy_since_ns = None;
+                    None
+                }
+                (Some(_), None) => {
+                    let since = *circuit.healthy_since_ns.get_or_insert(now_ns);
+                    if now_ns - since < cooldown {
+                        return None;
+                    }
+                    circuit.open = None;
+                    circuit.healthy_since_ns = None;
+                    tracing::info!(symbol_hash, "circuit closed, publishing resumed");
+                    Some(BreakerEvent::CircuitClosed { symbol_hash, ts_ns: now_ns })
+                }
+                (None, None) => None,
+            }
+        }
+
+        /// Whether derived metrics/signals may be published for the symbol
+        #[inline(always)]
+        pub fn allow_publish(&self, symbol_hash: u64) -> bool {
+            self.open_reason(symbol_hash).is_none()
+        }
+
+        /// Reason the symbol's circuit is open, if it is
+        pub fn open_reason(&self, symbol_hash: u64) -> Option<TripReason> {
+            self.symbols.get(&symbol_hash).and_then(|c| c.open)
+        }
+    }
+
+    impl Default for CircuitBreaker {
+        fn default() -> Self {
+            Self::new(BreakerConfig::default())
+        }
+    }
+}
+
+pub use breaker::*;
+
+#[cfg(test)]
+mod tests {
+    use super::breaker::*;
+    use crate::orderbook::{L2Orderbook, Price, Qty};
+
+    fn config() -> BreakerConfig {
+        BreakerConfig {
+            max_spread_bps: 50.0,
+            max_staleness_ns: 1_000,
+            cooldown_ns: 100,
+        }
+    }
+
+    fn book(bid: f64, ask: f64) -> L2Orderbook {
+        let mut book = L2Orderbook::new(7);
+        book.apply_delta(Price(bid), Qty(1.0), true, 1);
+        book.apply_delta(Price(ask), Qty(1.0), false, 2);
+        book
+    }
+
+    fn assert_opens_then_closes(mut breaker: CircuitBreaker, bad: Option<L2Orderbook>, reason: TripReason) {
+        let healthy = book(100.0, 100.1);
+        assert_eq!(breaker.on_book_update(&healthy, 0), None);
+        assert!(breaker.allow_publish(7));
+
+        let opened = match bad {
+            Some(bad) => breaker.on_book_update(&bad, 10),
+            None => breaker.check_staleness(2_000).pop(),
+        };
+        assert!(matches!(opened, Some(BreakerEvent::CircuitOpen { symbol_hash: 7, reason: r, .. }) if r == reason));
+        assert!(!breaker.allow_publish(7));
+        assert_eq!(breaker.open_reason(7), Some(reason));
+
+        // Normal again, but must hold for the cooldown
+        assert_eq!(breaker.on_book_update(&healthy, 3_000), None);
+        assert_eq!(breaker.on_book_update(&healthy, 3_050), None);
+        assert!(!breaker.allow_publish(7));
+        assert_eq!(
+            breaker.on_book_update(&healthy, 3_100),
+            Some(BreakerEvent::CircuitClosed { symbol_hash: 7, ts_ns: 3_100 })
+        );
+        assert!(breaker.allow_publish(7));
+    }
+
+    #[test]
+    fn test_crossed_book_trips_circuit() {
+        assert_opens_then_closes(CircuitBreaker::new(config()), Some(book(100.2, 100.1)), TripReason::CrossedBook);
+    }
+
+    #[test]
+    fn test_spread_anomaly_trips_circuit() {
+        assert_opens_then_closes(CircuitBreaker::new(config()), Some(book(100.0, 101.0)), TripReason::SpreadAnomaly);
+    }
+
+    #[test]
+    fn test_staleness_trips_circuit() {
+        assert_opens_then_closes(CircuitBreaker::new(config()), None, TripReason::Stale);
+    }
+
+    #[test]
+    fn test_anomaly_during_cooldown_restarts_it() {
+        let mut breaker = CircuitBreaker::new(config());
+        let (healthy, crossed) = (book(100.0, 100.1), book(100.2, 100.1));
+
+        assert!(breaker.on_book_update(&crossed, 0).is_some());
+        assert_eq!(breaker.on_book_update(&healthy, 10), None);
+        assert_eq!(breaker.on_book_update(&crossed, 50), None);
+        assert_eq!(breaker.on_book_update(&healthy, 120), None);
+        assert!(breaker.on_book_update(&healthy, 220).is_some());
+        assert_eq!(breaker.trips, 1);
+    }
+}
@@ -2,14 +2,148 @@
 //
 // Features:
 // - Lock-free idempotency checking using hash set
+// - Order sanity validation (symbol, quantity, limit price) before submit
+// - Idempotency keys expire by TTL, oldest evicted first at the size cap
 // - Zero allocation in hot path using object pooling
 // - O(1) order submission with pre-allocated IDs
 // - Batch fill processing for amortized cost
+// - Fill stream subscribers (crossbeam channels) for embedders
+// - Optional book context (mid/spread/imbalance) captured at fill time
+// - Typed Side/OrderType (case-insensitive parse, strict serde)
+// - Post-fill markouts per symbol at configurable horizons
+// - Live order tracking with full and partial (quantity) cancels
+// - Partial fills accumulated per order (size-weighted average price)
+// - Filled/cancelled order states retired by TTL, oldest first at the size cap
+// - Instrument registry with per-symbol tradeable flag
+// - Tick-size enforcement on limit prices (reject or snap)
+// - Net position per symbol from processed fills
+// - Maker/taker volume and fee totals
+// - Separate maker/taker commission rates (negative maker = rebate)
+// - Exchange fill ingestion deduplicated by (exchange order, seq)
 
 pub mod execution {
-    use std::collections::HashSet;
+    use crate::orderbook::L2Orderbook;
+    use crossbeam_channel::{bounded, Receiver, Sender, TrySendError};
+    use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+    use std::collections::{HashMap, HashSet, VecDeque};
+    use std::fmt;
+    use std::str::FromStr;
     use std::sync::atomic::{AtomicU64, Ordering};
-    use std::time::{Duration, Instant};
+    use std::time::Instant;
+
+    /// Order side
+    #[repr(u8)]
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+    pub enum Side {
+        #[default]
+        Buy = 0,
+        Sell = 1,
+    }
+
+    impl Side {
+        #[inline(always)]
+        pub fn is_buy(self) -> bool {
+            self == Side::Buy
+        }
+    }
+
+    impl FromStr for Side {
+        type Err = &'static str;
+
+        /// Case-insensitive: "BUY", "buy", "Buy"
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            if s.eq_ignore_ascii_case("buy") {
+                Ok(Side::Buy)
+            } else if s.eq_ignore_ascii_case("sell") {
+                Ok(Side::Sell)
+            } else {
+                Err("INVALID_SIDE")
+            }
+        }
+    }
+
+    impl fmt::Display for Side {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str(match self {
+                Side::Buy => "BUY",
+                Side::Sell => "SELL",
+            })
+        }
+    }
+
+    /// Order type
+    #[repr(u8)]
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+    pub enum OrderType {
+        #[default]
+        Market = 0,
+        Limit = 1,
+    }
+
+    impl FromStr for OrderType {
+        type Err = &'static str;
+
+        /// Case-insensitive: "LIMIT", "limit", "Limit"
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            if s.eq_ignore_ascii_case("market") {
+                Ok(OrderType::Market)
+            } else if s.eq_ignore_ascii_case("limit") {
+                Ok(OrderType::Limit)
+            } else {
+                Err("INVALID_ORDER_TYPE")
+            }
+        }
+    }
+
+    impl fmt::Display for OrderType {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str(match self {
+                OrderType::Market => "MARKET",
+                OrderType::Limit => "LIMIT",
+            })
+        }
+    }
+
+    /// Serialize as the canonical string, deserialize via `FromStr`
+    /// (unknown values are rejected)
+    macro_rules! serde_via_str {
+        ($($ty:ty),*) => {$(
+            impl Serialize for $ty {
+                fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    serializer.collect_str(self)
+                }
+            }
+
+            impl<'de> Deserialize<'de> for $ty {
+                fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                    let s = <std::borrow::Cow<'de, str>>::deserialize(deserializer)?;
+                    s.parse().map_err(de::Error::custom)
+                }
+            }
+        )*};
+    }
+
+    serde_via_str!(Side, OrderType);
+
+    /// Whether a fill added (maker) or removed (taker) liquidity
+    #[repr(u8)]
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+    pub enum Liquidity {
+        #[default]
+        Taker = 0,
+        Maker = 1,
+    }
+
+    impl Liquidity {
+        /// Best guess without a venue flag: resting limits make, market orders take
+        #[inline(always)]
+        pub fn infer(order_type: OrderType) -> Self {
+            match order_type {
+                OrderType::Limit => Liquidity::Maker,
+                OrderType::Market => Liquidity::Taker,
+            }
+        }
+    }
 
     /// Order request - cache-line aligned
     #[repr(C, align(64))]
@@ -17,10 +151,11 @@ pub mod execution {
     pub struct OrderRequest {
         pub client_hash: u64,
         pub symbol_hash: u64,
-        pub side: u8,           // 0=Buy, 1=Sell
+        pub side: Side,
         pub quantity: i64,      // Fixed-point
         pub price: i64,         // Fixed-point
-        pub order_type: u8,     // 0=Market, 1=Limit
+        pub order_type: OrderType,
+        pub display_qty: i64,   // Iceberg slice size, 0 = fully displayed
         pub idempotency_key: u64,
         pub timestamp_ns: i64,
     }
@@ -31,11 +166,138 @@ pub mod execution {
     pub struct OrderAck {
         pub client_hash: u64,
         pub exchange_hash: u64,
-        pub status: u8,         // 0=Submitted, 1=Rejected, 2=Duplicate
+        pub status: OrderStatus,    // Order status as of this ack
         pub timestamp_ns: i64,
         pub latency_ns: i64,
     }
 
+    /// Order lifecycle errors
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub enum ExecError {
+        DuplicateOrder,
+        SymbolNotTradeable,
+        UnknownOrder,
+        InvalidQuantity,
+        ExceedsRemaining { remaining: i64 },
+        InvalidTick,
+        InvalidPrice,
+        MissingSymbol,
+    }
+
+    impl ExecError {
+        pub fn code(&self) -> &'static str {
+            match self {
+                ExecError::DuplicateOrder => "DUPLICATE_ORDER",
+                ExecError::SymbolNotTradeable => "SYMBOL_NOT_TRADEABLE",
+                ExecError::UnknownOrder => "UNKNOWN_ORDER",
+                ExecError::InvalidQuantity => "INVALID_QUANTITY",
+                ExecError::ExceedsRemaining { .. } => "EXCEEDS_REMAINING",
+                ExecError::InvalidTick => "INVALID_TICK",
+                ExecError::InvalidPrice => "INVALID_PRICE",
+                ExecError::MissingSymbol => "MISSING_SYMBOL",
+            }
+        }
+    }
+
+    impl fmt::Display for ExecError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str(self.code())
+        }
+    }
+
+    impl std::error::Error for ExecError {}
+
+    /// Basic sanity checks, independent of engine state
+    /// Fixed-point fields are always finite, so only sign and presence are
+    /// checked; market orders may carry price 0 (the price is ignored)
+    pub fn validate(req: &OrderRequest) -> Result<(), ExecError> {
+        if req.symbol_hash == 0 {
+            return Err(ExecError::MissingSymbol);
+        }
+        if req.quantity <= 0 {
+            return Err(ExecError::InvalidQuantity);
+        }
+        if req.order_type == OrderType::Limit && req.price <= 0 {
+            return Err(ExecError::InvalidPrice);
+        }
+        Ok(())
+    }
+
+    /// Static per-instrument parameters
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub struct Instrument {
+        pub symbol_hash: u64,
+        pub tradeable: bool,    // false = market data only
+        pub tick_size: i64,     // Fixed-point price increment, 0 = unconstrained
+    }
+
+    impl Instrument {
+        #[inline(always)]
+        pub fn is_on_tick(&self, price: i64) -> bool {
+            self.tick_size <= 0 || price % self.tick_size == 0
+        }
+
+        /// Nearest tick (half-up), never below one tick
+        #[inline(always)]
+        pub fn snap_price(&self, price: i64) -> i64 {
+            if self.tick_size <= 0 {
+                return price;
+            }
+            let tick = self.tick_size;
+            ((price + tick / 2).div_euclid(tick) * tick).max(tick)
+        }
+    }
+
+    /// Handling of limit prices off the instrument's tick grid
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+    pub enum TickPolicy {
+        #[default]
+        Reject,                 // ExecError::InvalidTick
+        Snap,                   // Round to the nearest tick and proceed
+    }
+
+    /// Known instruments - unregistered symbols are tradeable
+    #[derive(Debug, Default)]
+    pub struct InstrumentRegistry {
+        instruments: HashMap<u64, Instrument>,
+    }
+
+    impl InstrumentRegistry {
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        /// Add (or replace) an instrument
+        pub fn register(&mut self, instrument: Instrument) {
+            self.instruments.insert(instrument.symbol_hash, instrument);
+        }
+
+        pub fn get(&self, symbol_hash: u64) -> Option<&Instrument> {
+            self.instruments.get(&symbol_hash)
+        }
+
+        /// Enable/disable execution; registers the symbol if unknown
+        pub fn set_tradeable(&mut self, symbol_hash: u64, tradeable: bool) {
+            self.instruments
+                .entry(symbol_hash)
+                .or_insert(Instrument { symbol_hash, tradeable, tick_size: 0 })
+                .tradeable = tradeable;
+        }
+
+        #[inline(always)]
+        pub fn is_tradeable(&self, symbol_hash: u64) -> bool {
+            !matches!(self.instruments.get(&symbol_hash), Some(i) if !i.tradeable)
+        }
+
+        pub fn len(&self) -> usize {
+            self.instruments.len()
+        }
+
+        pub fn is_empty(&self) -> bool {
+            self.instruments.is_empty()
+        }
+    }
+
     /// Fill event
     #[repr(C, align(64))]
     #[derive(Clone, Copy, Default)]
@@ -43,92 +305,744 @@ pub mod execution {
         pub order_hash: u64,
         pub exchange_hash: u64,
         pub symbol_hash: u64,
-        pub side: u8,
+        pub side: Side,
         pub filled_qty: i64,    // Fixed-point
         pub fill_price: i64,    // Fixed-point
         pub commission: i64,    // Fixed-point
+        pub liquidity: Liquidity,
         pub timestamp_ns: i64,
         pub seq_id: u64,
         pub latency_ns: i64,
     }
 
+    /// Lifecycle of a submitted order
+    #[repr(u8)]
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+    pub enum OrderStatus {
+        #[default]
+        Open = 0,
+        PartiallyFilled = 1,
+        Filled = 2,
+        Cancelled = 3,
+    }
+
+    /// Cumulative fill state of one order - quantities and prices fixed-point
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct OrderState {
+        pub quantity: i64,          // Ordered, less any cancelled quantity
+        pub filled_qty: i64,
+        pub avg_fill_price: i64,    // Size-weighted across fills, 0 before any
+        pub status: OrderStatus,
+    }
+
+    impl OrderState {
+        fn new(quantity: i64) -> Self {
+            Self { quantity, filled_qty: 0, avg_fill_price: 0, status: OrderStatus::Open }
+        }
+
+        #[inline(always)]
+        pub fn remaining_qty(&self) -> i64 {
+            (self.quantity - self.filled_qty).max(0)
+        }
+
+        fn record_fill(&mut self, qty: i64, price: i64) {
+            // i128: cumulative notional overflows i64 before quantities do
+            let notional = self.avg_fill_price as i128 * self.filled_qty as i128 + price as i128 * qty as i128;
+            self.filled_qty += qty;
+            if self.filled_qty > 0 {
+                self.avg_fill_price = (notional / self.filled_qty as i128) as i64;
+            }
+            self.status = if self.remaining_qty() == 0 {
+                OrderStatus::Filled
+            } else {
+                OrderStatus::PartiallyFilled
+            };
+        }
+    }
+
+    /// Running maker/taker totals - volume is fixed-point notional
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+    pub struct FeeStats {
+        pub maker_volume: i64,
+        pub taker_volume: i64,
+        pub maker_fees: i64,
+        pub taker_fees: i64,
+    }
+
+    impl FeeStats {
+        #[inline(always)]
+        fn record(&mut self, liquidity: Liquidity, notional: i64, commission: i64) {
+            let (volume, fees) = match liquidity {
+                Liquidity::Maker => (&mut self.maker_volume, &mut self.maker_fees),
+                Liquidity::Taker => (&mut self.taker_volume, &mut self.taker_fees),
+            };
+            *volume += notional;
+            *fees += commission;
+        }
+
+        /// Fees paid per unit of volume in bps - compare against statements
+        pub fn effective_rate_bps(&self, liquidity: Liquidity) -> Option<f64> {
+            let (volume, fees) = match liquidity {
+                Liquidity::Maker => (self.maker_volume, self.maker_fees),
+                Liquidity::Taker => (self.taker_volume, self.taker_fees),
+            };
+            (volume > 0).then(|| fees as f64 * 10_000.0 / volume as f64)
+        }
+    }
+
+    /// Prevailing book state at fill time
+    #[derive(Clone, Copy, Debug, Default, PartialEq)]
+    pub struct FillContext {
+        pub mid: i64,           // Fixed-point
+        pub spread_bps: i64,
+        pub imbalance: f64,     // Top-of-book (bid - ask) / (bid + ask), -1..1
+    }
+
+    impl FillContext {
+        /// Capture context from a book - None unless both sides are quoted
+        pub fn from_book(book: &L2Orderbook) -> Option<Self> {
+            let mid = book.mid_price()?;
+            let spread_bps = book.spread_bps()?;
+            let (_, &bid_qty) = book.bids.iter().next_back()?;
+            let (_, &ask_qty) = book.asks.iter().next()?;
+            let total = (bid_qty + ask_qty) as f64;
+            let imbalance = if total > 0.0 {
+                (bid_qty - ask_qty) as f64 / total
+            } else {
+                0.0
+            };
+            Some(Self {
+                mid: (mid * FIXED_SCALE as f64).round() as i64,
+                spread_bps,
+                imbalance,
+            })
+        }
+    }
+
+    /// Fill with the market context it executed into, for post-trade analysis
+    #[derive(Clone, Copy, Default)]
+    pub struct EnrichedFill {
+        pub fill: FillEvent,
+        pub context: Option<FillContext>,
+    }
+
+    impl EnrichedFill {
+        /// Execution cost vs prevailing mid in bps - positive = worse than mid
+        pub fn slippage_bps(&self) -> Option<f64> {
+            let ctx = self.context?;
+            if ctx.mid <= 0 {
+                return None;
+            }
+            let diff = (self.fill.fill_price - ctx.mid) as f64;
+            let signed = if self.fill.side.is_buy() { diff } else { -diff };
+            Some(signed / ctx.mid as f64 * 10_000.0)
+        }
+    }
+
+    /// Mid move after a fill in the fill's favour, in bps of the fill price
+    /// Negative = adverse selection (price moved against the position)
+    #[inline(always)]
+    pub fn markout_bps(fill: &FillEvent, later_mid: i64) -> f64 {
+        if fill.fill_price <= 0 {
+            return 0.0;
+        }
+        let moved = (later_mid - fill.fill_price) as f64;
+        let signed = if fill.side.is_buy() { moved } else { -moved };
+        signed / fill.fill_price as f64 * 10_000.0
+    }
+
+    #[derive(Clone, Copy)]
+    struct PendingMarkout {
+        due_ns: i64,
+        fill: FillEvent,
+    }
+
+    #[derive(Clone, Copy, Default)]
+    struct MarkoutSum {
+        total_bps: f64,
+        count: u64,
+    }
+
+    /// Average markouts per symbol at several horizons
+    pub struct MarkoutTracker {
+        horizons_ns: Vec<i64>,
+        pending: HashMap<(u64, usize), VecDeque<PendingMarkout>>,   // (symbol, horizon) -> due order
+        sums: HashMap<(u64, usize), MarkoutSum>,
+    }
+
+    impl MarkoutTracker {
+        pub fn new(horizons_ns: Vec<i64>) -> Self {
+            Self {
+                horizons_ns,
+                pending: HashMap::new(),
+                sums: HashMap::new(),
+            }
+        }
+
+        pub fn horizons_ns(&self) -> &[i64] {
+            &self.horizons_ns
+        }
+
+        /// Start measuring a fill at every horizon - O(horizons)
+        pub fn record_fill(&mut self, fill: &FillEvent) {
+            for (i, &horizon) in self.horizons_ns.iter().enumerate() {
+                self.pending
+                    .entry((fill.symbol_hash, i))
+                    .or_default()
+                    .push_back(PendingMarkout {
+                        due_ns: fill.timestamp_ns + horizon,
+                        fill: *fill,
+                    });
+            }
+        }
+
+        /// Resolve fills whose horizon has elapsed against the current mid
+        /// Returns the number of markouts completed
+        pub fn on_mid(&mut self, symbol_hash: u64, mid: i64, ts_ns: i64) -> usize {
+            let mut resolved = 0;
+            for i in 0..self.horizons_ns.len() {
+                let Some(queue) = self.pending.get_mut(&(symbol_hash, i)) else {
+                    continue;
+                };
+                while queue.front().is_some_and(|p| p.due_ns <= ts_ns) {
+                    let pending = queue.pop_front().unwrap();
+                    let sum = self.sums.entry((symbol_hash, i)).or_default();
+                    sum.total_bps += markout_bps(&pending.fill, mid);
+                    sum.count += 1;
+                    resolved += 1;
+                }
+            }
+            resolved
+        }
+
+        /// Average markout (bps) for a symbol at horizon index `horizon`
+        pub fn average_markout_bps(&self, symbol_hash: u64, horizon: usize) -> Option<f64> {
+            let sum = self.sums.get(&(symbol_hash, horizon))?;
+            (sum.count > 0).then(|| sum.total_bps / sum.count as f64)
+        }
+
+        /// Fills still waiting on a horizon
+        pub fn pending(&self) -> usize {
+            self.pending.values().map(VecDeque::len).sum()
+        }
+    }
+
+    /// Fixed-point precision: 1e8 = 8 decimal places
+    pub const FIXED_SCALE: i64 = 100_000_000;
+
+    /// Decimal places carried by fixed-point values
+    pub const FIXED_DECIMALS: u32 = 8;
+
+    /// Fee model applied in `process_fill`
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub struct FeeModel {
+        pub maker_bps: i64,             // Negative = rebate
+        pub taker_bps: i64,
+        pub commission_precision: u32,  // Decimal places commission is rounded to
+        pub min_commission: i64,        // Fixed-point minimum fee per fill (not rebates)
+    }
+
+    impl FeeModel {
+        #[inline(always)]
+        pub fn rate_bps(&self, liquidity: Liquidity) -> i64 {
+            match liquidity {
+                Liquidity::Maker => self.maker_bps,
+                Liquidity::Taker => self.taker_bps,
+            }
+        }
+
+        /// Commission for a fill - rate, then round half-up, then minimum fee
+        /// Rebates come back negative and are never raised to the minimum
+        #[inline(always)]
+        pub fn commission(&self, quantity: i64, price: i64, liquidity: Liquidity) -> i64 {
+            // i128 intermediate: qty * price overflows i64 for ordinary fills
+            let notional = quantity as i128 * price as i128 / FIXED_SCALE as i128;
+            let raw = notional * self.rate_bps(liquidity) as i128 / 10_000;
+
+            let precision = self.commission_precision.min(FIXED_DECIMALS);
+            let step = 10i128.pow(FIXED_DECIMALS - precision);
+            let rounded = (raw + step / 2).div_euclid(step) * step;
+
+            if rounded < 0 {
+                return rounded as i64;
+            }
+            (rounded as i64).max(self.min_commission)
+        }
+    }
+
+    impl Default for FeeModel {
+        fn default() -> Self {
+            Self {
+                maker_bps: 4,
+                taker_bps: 4,
+                commission_precision: FIXED_DECIMALS,
+                min_commission: 0,
+            }
+        }
+    }
+
+    /// Capacity of each fill stream channel
+    pub const FILL_STREAM_CAPACITY: usize = 65_536;
+
+    /// How long a submitted idempotency key blocks a replay by default
+    pub const DEFAULT_IDEMPOTENCY_TTL_NS: i64 = 60_000_000_000;
+
+    /// How long a filled or cancelled order stays in `order_status` by default
+    pub const DEFAULT_ORDER_STATE_TTL_NS: i64 = 60_000_000_000;
+
+    /// Wall-clock ns since the epoch - replaceable for tests and replay
+    pub type EngineClock = Box<dyn Fn() -> i64 + Send + Sync>;
+
+    fn system_now_ns() -> i64 {
+        chrono::Utc::now().timestamp_nanos_opt().unwrap_or(0)
+    }
+
     /// Idempotent execution engine
     pub struct ExecutionEngine {
-        seen_keys: HashSet<u64>,
-        max_keys: usize,
+        seen_keys: HashMap<u64, i64>,               // idempotency_key -> submitted at (ns)
+        key_order: VecDeque<(u64, i64)>,            // Submission order, oldest first
+        max_keys: usize,                            // Hard cap, oldest evicted past it
+        idempotency_ttl_ns: i64,
+        clock: EngineClock,
+        fee_model: FeeModel,
+        fill_subscribers: Vec<Sender<FillEvent>>,
+        live_orders: HashMap<u64, OrderRequest>,    // exchange_hash -> open remainder
+        order_states: HashMap<u64, OrderState>,     // exchange_hash -> cumulative fills
+        closed_orders: VecDeque<(u64, i64)>,        // Terminal states by close time, oldest first
+        order_state_ttl_ns: i64,
+        instruments: InstrumentRegistry,
+        tick_policy: TickPolicy,
+        positions: HashMap<u64, i64>,               // symbol_hash -> signed fixed-point qty
+        fee_stats: FeeStats,
+        seen_fills: HashSet<(u64, u64)>,            // (exchange_hash, seq_id)
+        fill_order: VecDeque<(u64, u64)>,           // Insertion order, oldest evicted first
         
         // Atomic counters for stats
         total_submitted: AtomicU64,
         total_duplicates: AtomicU64,
         total_fills: AtomicU64,
         total_rejected: AtomicU64,
+        duplicate_fills: AtomicU64,
     }
 
     impl ExecutionEngine {
         pub fn new(max_keys: usize) -> Self {
+            Self::with_fee_model(max_keys, FeeModel::default())
+        }
+
+        pub fn with_fee_model(max_keys: usize, fee_model: FeeModel) -> Self {
             Self {
-                seen_keys: HashSet::with_capacity(max_keys),
+                seen_keys: HashMap::with_capacity(max_keys),
+                key_order: VecDeque::with_capacity(max_keys),
                 max_keys,
+                idempotency_ttl_ns: DEFAULT_IDEMPOTENCY_TTL_NS,
+                clock: Box::new(system_now_ns),
+                fee_model,
+                fill_subscribers: Vec::new(),
+                live_orders: HashMap::new(),
+                order_states: HashMap::new(),
+                closed_orders: VecDeque::new(),
+                order_state_ttl_ns: DEFAULT_ORDER_STATE_TTL_NS,
+                instruments: InstrumentRegistry::new(),
+                tick_policy: TickPolicy::default(),
+                positions: HashMap::new(),
+                fee_stats: FeeStats::default(),
+                seen_fills: HashSet::new(),
+                fill_order: VecDeque::new(),
                 total_submitted: AtomicU64::new(0),
                 total_duplicates: AtomicU64::new(0),
                 total_fills: AtomicU64::new(0),
                 total_rejected: AtomicU64::new(0),
+                duplicate_fills: AtomicU64::new(0),
             }
         }
 
-        /// Submit order with idempotency check - O(1) average
+        /// Submit order with idempotency check - O(1) amortized
+        /// A key is rejected as a duplicate until its TTL lapses (or it is
+        /// pushed out as the oldest of `max_keys`)
         #[inline(always)]
-        pub fn submit(&mut self, req: &OrderRequest) -> Result<OrderAck, &'static str> {
+        pub fn submit(&mut self, req: &OrderRequest) -> Result<OrderAck, ExecError> {
             let start = Instant::now();
+            if let Err(e) = validate(req) {
+                self.total_rejected.fetch_add(1, Ordering::Relaxed);
+                return Err(e);
+            }
+            let now_ns = (self.clock)();
+            self.expire_keys(now_ns);
+            self.expire_closed_orders(now_ns);
 
             // Idempotency check
-            if self.seen_keys.contains(&req.idempotency_key) {
+            if self.seen_keys.contains_key(&req.idempotency_key) {
                 self.total_duplicates.fetch_add(1, Ordering::Relaxed);
-                return Err("DUPLICATE_ORDER");
+                return Err(ExecError::DuplicateOrder);
             }
 
-            // Add to seen set
-            self.seen_keys.insert(req.idempotency_key);
+            // Data-only symbols never trade; the key stays unused for a retry
+            if !self.instruments.is_tradeable(req.symbol_hash) {
+                self.total_rejected.fetch_add(1, Ordering::Relaxed);
+                return Err(ExecError::SymbolNotTradeable);
+            }
 
-            // Periodic cleanup
-            if self.seen_keys.len() >= self.max_keys {
-                self.seen_keys.clear();
+            // Limit prices must sit on the tick grid; market prices are ignored
+            let mut order = *req;
+            if let Some(instrument) = self.instruments.get(req.symbol_hash) {
+                if req.order_type == OrderType::Limit && !instrument.is_on_tick(req.price) {
+                    match self.tick_policy {
+                        TickPolicy::Reject => {
+                            self.total_rejected.fetch_add(1, Ordering::Relaxed);
+                            return Err(ExecError::InvalidTick);
+                        }
+                        TickPolicy::Snap => order.price = instrument.snap_price(req.price),
+                    }
+                }
+            }
+
+            // Remember the key; past the cap the oldest goes, never the lot
+            self.seen_keys.insert(req.idempotency_key, now_ns);
+            self.key_order.push_back((req.idempotency_key, now_ns));
+            if self.key_order.len() > self.max_keys {
+                if let Some((oldest, _)) = self.key_order.pop_front() {
+                    self.seen_keys.remove(&oldest);
+                }
             }
 
             // Generate exchange hash (in production, use proper ID generation)
             let exchange_hash = self.total_submitted.fetch_add(1, Ordering::Relaxed)
                 .wrapping_add(0xDEAD_BEEF_CAFE_BABE);
+            self.live_orders.insert(exchange_hash, order);
+            self.order_states.insert(exchange_hash, OrderState::new(order.quantity));
 
             Ok(OrderAck {
                 client_hash: req.client_hash,
                 exchange_hash,
-                status: 0, // Submitted
+                status: OrderStatus::Open,
                 timestamp_ns: chrono::Utc::now().timestamp_nanos_opt().unwrap_or(0),
                 latency_ns: start.elapsed().as_nanos() as i64,
             })
         }
 
-        /// Process fill for an order
+        /// Drop keys submitted more than the TTL ago - O(expired)
+        fn expire_keys(&mut self, now_ns: i64) {
+            let cutoff = now_ns.saturating_sub(self.idempotency_ttl_ns);
+            while let Some(&(key, submitted_ns)) = self.key_order.front() {
+                if submitted_ns > cutoff {
+                    break;
+                }
+                self.key_order.pop_front();
+                self.seen_keys.remove(&key);
+            }
+        }
+
+        pub fn set_idempotency_ttl_ns(&mut self, ttl_ns: i64) {
+            self.idempotency_ttl_ns = ttl_ns.max(0);
+        }
+
+        /// Mark a filled or cancelled order for retirement; past `max_keys`
+        /// closed orders the oldest state goes at once
+        fn close_order(&mut self, exchange_hash: u64) {
+            let now_ns = (self.clock)();
+            self.closed_orders.push_back((exchange_hash, now_ns));
+            if self.closed_orders.len() > self.max_keys {
+                if let Some((oldest, _)) = self.closed_orders.pop_front() {
+                    self.order_states.remove(&oldest);
+                }
+            }
+            self.expire_closed_orders(now_ns);
+        }
+
+        /// Drop order states closed more than the TTL ago - O(expired)
+        fn expire_closed_orders(&mut self, now_ns: i64) {
+            let cutoff = now_ns.saturating_sub(self.order_state_ttl_ns);
+            while let Some(&(exchange_hash, closed_ns)) = self.closed_orders.front() {
+                if closed_ns > cutoff {
+                    break;
+                }
+                self.closed_orders.pop_front();
+                self.order_states.remove(&exchange_hash);
+            }
+        }
+
+        pub fn set_order_state_ttl_ns(&mut self, ttl_ns: i64) {
+            self.order_state_ttl_ns = ttl_ns.max(0);
+        }
+
+        /// Replace the wall clock used to age idempotency keys and closed orders
+        pub fn set_clock(&mut self, clock: EngineClock) {
+            self.clock = clock;
+        }
+
+        /// Idempotency keys currently remembered
+        pub fn tracked_keys(&self) -> usize {
+            self.seen_keys.len()
+        }
+
+        /// Order states currently queryable via `order_status`
+        pub fn tracked_orders(&self) -> usize {
+            self.order_states.len()
+        }
+
+        /// Fill the live remainder of an order, liquidity inferred from the order type
         #[inline(always)]
-        pub fn process_fill(&mut self, ack: &OrderAck, req: &OrderRequest) -> FillEvent {
-            let start = Instant::now();
-            let seq_id = self.total_fills.fetch_add(1, Ordering::Relaxed);
+        pub fn process_fill(&mut self, ack: &OrderAck, req: &OrderRequest) -> Result<FillEvent, ExecError> {
+            self.process_fill_as(ack, req, Liquidity::infer(req.order_type))
+        }
 
-            // Commission: 4 basis points
-            let commission = (req.quantity * req.price * 4) / 10_000;
+        /// Fill the live remainder with the venue-reported maker/taker flag
+        /// Earlier partial fills are not booked twice; orders that are no
+        /// longer live are `UnknownOrder`
+        #[inline(always)]
+        pub fn process_fill_as(
+            &mut self,
+            ack: &OrderAck,
+            req: &OrderRequest,
+            liquidity: Liquidity,
+        ) -> Result<FillEvent, ExecError> {
+            let remaining = self.live_orders.get(&ack.exchange_hash).ok_or(ExecError::UnknownOrder)?.quantity;
+            self.fill_slice(ack, req, remaining, req.price, liquidity)
+        }
+
+        /// Fill `filled_qty` of a live order at `fill_price` - O(1)
+        /// Slices accumulate into `order_status`; the order leaves the live
+        /// set once its remainder is filled
+        pub fn process_partial_fill(
+            &mut self,
+            ack: &OrderAck,
+            req: &OrderRequest,
+            filled_qty: i64,
+            fill_price: i64,
+            liquidity: Liquidity,
+        ) -> Result<FillEvent, ExecError> {
+            if filled_qty <= 0 {
+                return Err(ExecError::InvalidQuantity);
+            }
+            let order = self.live_orders.get(&ack.exchange_hash).ok_or(ExecError::UnknownOrder)?;
+            if filled_qty > order.quantity {
+                return Err(ExecError::ExceedsRemaining { remaining: order.quantity });
+            }
+            self.fill_slice(ack, req, filled_qty, fill_price, liquidity)
+        }
 
-            FillEvent {
+        fn fill_slice(
+            &mut self,
+            ack: &OrderAck,
+            req: &OrderRequest,
+            qty: i64,
+            price: i64,
+            liquidity: Liquidity,
+        ) -> Result<FillEvent, ExecError> {
+            let start = Instant::now();
+            let mut fill = FillEvent {
                 order_hash: req.client_hash,
                 exchange_hash: ack.exchange_hash,
                 symbol_hash: req.symbol_hash,
                 side: req.side,
-                filled_qty: req.quantity,
-                fill_price: req.price,
-                commission,
+                filled_qty: qty,
+                fill_price: price,
+                commission: self.fee_model.commission(qty, price, liquidity),
+                liquidity,
+                timestamp_ns: chrono::Utc::now().timestamp_nanos_opt().unwrap_or(0),
+                seq_id: 0,
+                latency_ns: 0,
+            };
+            self.book_fill(&fill)?;
+            fill.seq_id = self.total_fills.fetch_add(1, Ordering::Relaxed);
+            fill.latency_ns = start.elapsed().as_nanos() as i64;
+
+            self.publish_fill(&fill);
+            Ok(fill)
+        }
+
+        /// Apply a fill to the order remainder, order state, position and fees
+        /// Fills for orders that are not live (unknown, filled, cancelled) or
+        /// larger than the remainder are rejected before anything is booked
+        fn book_fill(&mut self, fill: &FillEvent) -> Result<(), ExecError> {
+            let order = self.live_orders.get_mut(&fill.exchange_hash).ok_or(ExecError::UnknownOrder)?;
+            if fill.filled_qty <= 0 {
+                return Err(ExecError::InvalidQuantity);
+            }
+            if fill.filled_qty > order.quantity {
+                return Err(ExecError::ExceedsRemaining { remaining: order.quantity });
+            }
+            order.quantity -= fill.filled_qty;
+            let filled = order.quantity == 0;
+            if filled {
+                self.live_orders.remove(&fill.exchange_hash);
+            }
+            if let Some(state) = self.order_states.get_mut(&fill.exchange_hash) {
+                state.record_fill(fill.filled_qty, fill.fill_price);
+            }
+            if filled {
+                self.close_order(fill.exchange_hash);
+            }
+            let signed_qty = if fill.side.is_buy() { fill.filled_qty } else { -fill.filled_qty };
+            *self.positions.entry(fill.symbol_hash).or_insert(0) += signed_qty;
+            let notional = (fill.filled_qty as i128 * fill.fill_price as i128 / FIXED_SCALE as i128) as i64;
+            self.fee_stats.record(fill.liquidity, notional, fill.commission);
+            Ok(())
+        }
+
+        /// Cumulative fill state of a live or recently closed order
+        /// Filled and cancelled orders are kept for the order-state TTL, and
+        /// at most `max_keys` of them
+        pub fn order_status(&self, exchange_hash: u64) -> Option<OrderState> {
+            self.order_states.get(&exchange_hash).copied()
+        }
+
+        /// Apply an exchange-reported fill - O(1) average
+        /// Retransmits (same `exchange_hash` and `seq_id`) are dropped and
+        /// counted in `duplicate_fills`, so positions and fee totals see each
+        /// fill once. The last `max_keys` fill ids are remembered.
+        /// Fills for orders that are no longer live, or that exceed the
+        /// remainder, are ignored.
+        pub fn apply_fill(&mut self, fill: &FillEvent) -> bool {
+            let id = (fill.exchange_hash, fill.seq_id);
+            if self.seen_fills.contains(&id) {
+                self.duplicate_fills.fetch_add(1, Ordering::Relaxed);
+                return false;
+            }
+            if self.book_fill(fill).is_err() {
+                return false;
+            }
+            self.seen_fills.insert(id);
+            self.fill_order.push_back(id);
+            if self.fill_order.len() > self.max_keys {
+                if let Some(oldest) = self.fill_order.pop_front() {
+                    self.seen_fills.remove(&oldest);
+                }
+            }
+
+            self.total_fills.fetch_add(1, Ordering::Relaxed);
+            self.publish_fill(fill);
+            true
+        }
+
+        /// Exchange fills dropped as retransmits
+        pub fn duplicate_fills(&self) -> u64 {
+            self.duplicate_fills.load(Ordering::Relaxed)
+        }
+
+        /// Process a fill and capture the book state it executed into
+        /// Context is None when no book is given or the book is one-sided
+        pub fn process_fill_with_book(
+            &mut self,
+            ack: &OrderAck,
+            req: &OrderRequest,
+            book: Option<&L2Orderbook>,
+        ) -> Result<EnrichedFill, ExecError> {
+            let context = book.and_then(FillContext::from_book);
+            Ok(EnrichedFill {
+                fill: self.process_fill(ack, req)?,
+                context,
+            })
+        }
+
+        /// Cancel a live order's whole remainder - O(1)
+        /// Unknown, filled and already-cancelled orders are `UnknownOrder`
+        pub fn cancel_order(&mut self, exchange_hash: u64) -> Result<OrderAck, ExecError> {
+            let start = Instant::now();
+            let order = self.live_orders.remove(&exchange_hash).ok_or(ExecError::UnknownOrder)?;
+            if let Some(state) = self.order_states.get_mut(&exchange_hash) {
+                state.quantity = state.filled_qty;
+                state.status = OrderStatus::Cancelled;
+            }
+            self.close_order(exchange_hash);
+            Ok(OrderAck {
+                client_hash: order.client_hash,
+                exchange_hash,
+                status: OrderStatus::Cancelled,
                 timestamp_ns: chrono::Utc::now().timestamp_nanos_opt().unwrap_or(0),
-                seq_id,
                 latency_ns: start.elapsed().as_nanos() as i64,
+            })
+        }
+
+        /// Cancel part of a live order's quantity - O(1)
+        /// The displayed quantity is clamped to what remains. Cancelling the
+        /// whole remainder acks `Cancelled`; a reduced order keeps its status
+        /// (`Open` or `PartiallyFilled`).
+        pub fn cancel_qty(&mut self, exchange_hash: u64, qty: i64) -> Result<OrderAck, ExecError> {
+            let start = Instant::now();
+            if qty <= 0 {
+                return Err(ExecError::InvalidQuantity);
+            }
+            let order = self.live_orders.get_mut(&exchange_hash).ok_or(ExecError::UnknownOrder)?;
+            if qty > order.quantity {
+                return Err(ExecError::ExceedsRemaining { remaining: order.quantity });
+            }
+
+            order.quantity -= qty;
+            order.display_qty = order.display_qty.min(order.quantity);
+            let client_hash = order.client_hash;
+            let closed = order.quantity == 0;
+            let mut status = if closed { OrderStatus::Cancelled } else { OrderStatus::Open };
+            if let Some(state) = self.order_states.get_mut(&exchange_hash) {
+                state.quantity -= qty;
+                if closed {
+                    state.status = OrderStatus::Cancelled;
+                }
+                status = state.status;
             }
+            if closed {
+                self.live_orders.remove(&exchange_hash);
+                self.close_order(exchange_hash);
+            }
+
+            Ok(OrderAck {
+                client_hash,
+                exchange_hash,
+                status,
+                timestamp_ns: chrono::Utc::now().timestamp_nanos_opt().unwrap_or(0),
+                latency_ns: start.elapsed().as_nanos() as i64,
+            })
+        }
+
+        /// Open remainder of a live order (quantity = remaining)
+        pub fn live_order(&self, exchange_hash: u64) -> Option<&OrderRequest> {
+            self.live_orders.get(&exchange_hash)
+        }
+
+        /// Net position per symbol (long > 0, short < 0), fixed-point
+        pub fn positions(&self) -> &HashMap<u64, i64> {
+            &self.positions
+        }
+
+        pub fn position(&self, symbol_hash: u64) -> i64 {
+            self.positions.get(&symbol_hash).copied().unwrap_or(0)
+        }
+
+        /// Subscribe to fill events - every fill produced by `process_fill`
+        /// is delivered to each live subscriber (non-blocking)
+        pub fn fill_stream(&mut self) -> Receiver<FillEvent> {
+            let (tx, rx) = bounded(FILL_STREAM_CAPACITY);
+            self.fill_subscribers.push(tx);
+            rx
+        }
+
+        /// Fan out a fill to subscribers, dropping the disconnected ones
+        #[inline(always)]
+        fn publish_fill(&mut self, fill: &FillEvent) {
+            self.fill_subscribers
+                .retain(|tx| !matches!(tx.try_send(*fill), Err(TrySendError::Disconnected(_))));
+        }
+
+        pub fn instruments(&self) -> &InstrumentRegistry {
+            &self.instruments
+        }
+
+        pub fn instruments_mut(&mut self) -> &mut InstrumentRegistry {
+            &mut self.instruments
+        }
+
+        pub fn tick_policy(&self) -> TickPolicy {
+            self.tick_policy
+        }
+
+        pub fn set_tick_policy(&mut self, policy: TickPolicy) {
+            self.tick_policy = policy;
+        }
+
+        /// Active fee model
+        pub fn fee_model(&self) -> &FeeModel {
+            &self.fee_model
         }
 
         /// Get statistics
@@ -141,9 +1055,23 @@ pub mod execution {
             )
         }
 
+        /// Maker/taker volume and fee totals
+        pub fn fee_stats(&self) -> FeeStats {
+            self.fee_stats
+        }
+
         /// Reset statistics
         pub fn reset(&mut self) {
             self.seen_keys.clear();
+            self.key_order.clear();
+            self.live_orders.clear();
+            self.order_states.clear();
+            self.closed_orders.clear();
+            self.positions.clear();
+            self.fee_stats = FeeStats::default();
+            self.seen_fills.clear();
+            self.fill_order.clear();
+            self.duplicate_fills.store(0, Ordering::Relaxed);
             self.total_submitted.store(0, Ordering::Relaxed);
             self.total_duplicates.store(0, Ordering::Relaxed);
             self.total_fills.store(0, Ordering::Relaxed);
@@ -184,7 +1112,562 @@ pub mod execution {
         pub fn len(&self) -> usize {
             self.fills.len()
         }
+
+        pub fn is_empty(&self) -> bool {
+            self.fills.is_empty()
+        }
     }
 }
 
 pub use execution::*;
+
+#[cfg(test)]
+mod tests {
+    use super::execution::*;
+    use crate::orderbook::{L2Orderbook, OrderbookSnapshot};
+
+    fn order(key: u64) -> OrderRequest {
+        OrderRequest {
+            client_hash: 42,
+            symbol_hash: 7,
+            side: Side::Buy,
+            quantity: 1_000,
+            price: 67_500,
+            order_type: OrderType::Limit,
+            display_qty: 0,
+            idempotency_key: key,
+            timestamp_ns: 0,
+        }
+    }
+
+    #[test]
+    fn test_fill_stream_receives_fills() {
+        let mut engine = ExecutionEngine::default();
+        let fills = engine.fill_stream();
+
+        let req = order(1);
+        let ack = engine.submit(&req).unwrap();
+        let fill = engine.process_fill(&ack, &req).unwrap();
+
+        let received = fills.try_recv().unwrap();
+        assert_eq!(received.exchange_hash, ack.exchange_hash);
+        assert_eq!(received.filled_qty, fill.filled_qty);
+        assert_eq!(received.seq_id, fill.seq_id);
+        assert!(fills.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_fill_stream_drops_closed_subscriber() {
+        let mut engine = ExecutionEngine::default();
+        drop(engine.fill_stream());
+        let live = engine.fill_stream();
+
+        let req = order(2);
+        let ack = engine.submit(&req).unwrap();
+        engine.process_fill(&ack, &req).unwrap();
+
+        assert!(live.try_recv().is_ok());
+    }
+
+    #[test]
+    fn test_commission_rounds_half_up_to_precision() {
+        let fees = FeeModel {
+            taker_bps: 4,
+            commission_precision: 2,
+            ..FeeModel::default()
+        };
+
+        // 0.125 @ 100.0 = 12.5 notional -> 0.005 at 4bps -> 0.01
+        assert_eq!(fees.commission(12_500_000, 10_000_000_000, Liquidity::Taker), 1_000_000);
+        // 0.3 @ 100.0 = 30.0 notional -> 0.012 at 4bps -> 0.01
+        assert_eq!(fees.commission(30_000_000, 10_000_000_000, Liquidity::Taker), 1_000_000);
+    }
+
+    #[test]
+    fn test_tiny_fill_hits_min_commission() {
+        let fees = FeeModel {
+            commission_precision: 2,
+            min_commission: 1_000_000, // 0.01
+            ..FeeModel::default()
+        };
+        let mut engine = ExecutionEngine::with_fee_model(1_000, fees);
+
+        let req = OrderRequest {
+            quantity: 100_000,          // 0.001
+            price: 10_000_000_000,      // 100.0
+            ..order(3)
+        };
+        let ack = engine.submit(&req).unwrap();
+        let fill = engine.process_fill(&ack, &req).unwrap();
+
+        assert_eq!(fill.commission, 1_000_000);
+    }
+
+    #[test]
+    fn test_default_commission_does_not_overflow() {
+        // 1.0 BTC @ 67,500 = 27.0 at 4bps
+        let fees = FeeModel::default();
+        assert_eq!(fees.commission(100_000_000, 6_750_000_000_000, Liquidity::Taker), 2_700_000_000);
+    }
+
+    #[test]
+    fn test_fill_captures_book_context() {
+        let mut book = L2Orderbook::new(7);
+        book.apply_snapshot(&OrderbookSnapshot {
+            symbol_hash: 7,
+            seq_id: 1,
+            bids: vec![(99.0, 3.0), (98.0, 5.0)],
+            asks: vec![(101.0, 1.0), (102.0, 5.0)],
+        });
+        let mut engine = ExecutionEngine::default();
+        let mut req = order(3);
+        req.price = 101 * FIXED_SCALE;
+
+        let ack = engine.submit(&req).unwrap();
+        let enriched = engine.process_fill_with_book(&ack, &req, Some(&book)).unwrap();
+
+        let ctx = enriched.context.unwrap();
+        assert_eq!(ctx.mid, 100 * FIXED_SCALE);
+        assert_eq!(ctx.spread_bps, 202);    // 2.0 / 99.0
+        assert!((ctx.imbalance - 0.5).abs() < 1e-12);
+        // Bought at 101 vs mid 100 -> 100bps worse than mid
+        assert!((enriched.slippage_bps().unwrap() - 100.0).abs() < 1e-9);
+
+        let ack = engine.submit(&order(4)).unwrap();
+        assert!(engine.process_fill_with_book(&ack, &order(4), None).unwrap().context.is_none());
+    }
+
+    #[test]
+    fn test_side_and_order_type_parse() {
+        assert_eq!("BUY".parse::<Side>(), Ok(Side::Buy));
+        assert_eq!("sell".parse::<Side>(), Ok(Side::Sell));
+        assert_eq!("Limit".parse::<OrderType>(), Ok(OrderType::Limit));
+        assert_eq!("MARKET".parse::<OrderType>(), Ok(OrderType::Market));
+        assert_eq!("hold".parse::<Side>(), Err("INVALID_SIDE"));
+        assert_eq!("".parse::<OrderType>(), Err("INVALID_ORDER_TYPE"));
+        assert_eq!(Side::Sell.to_string(), "SELL");
+        assert_eq!(OrderType::Limit.to_string(), "LIMIT");
+    }
+
+    #[test]
+    fn test_side_and_order_type_serde_round_trip() {
+        for side in [Side::Buy, Side::Sell] {
+            let json = serde_json::to_string(&side).unwrap();
+            assert_eq!(serde_json::from_str::<Side>(&json).unwrap(), side);
+        }
+        for order_type in [OrderType::Market, OrderType::Limit] {
+            let json = serde_json::to_string(&order_type).unwrap();
+            assert_eq!(serde_json::from_str::<OrderType>(&json).unwrap(), order_type);
+        }
+        assert_eq!(serde_json::to_string(&Side::Buy).unwrap(), "\"BUY\"");
+        assert_eq!(serde_json::from_str::<Side>("\"sell\"").unwrap(), Side::Sell);
+        assert!(serde_json::from_str::<Side>("\"short\"").is_err());
+        assert!(serde_json::from_str::<OrderType>("\"stop\"").is_err());
+        assert!(serde_json::from_str::<Side>("0").is_err());
+    }
+
+    #[test]
+    fn test_markout_at_horizons() {
+        const SEC: i64 = 1_000_000_000;
+        let mut tracker = MarkoutTracker::new(vec![SEC, 5 * SEC]);
+        let fill = FillEvent {
+            symbol_hash: 7,
+            side: Side::Buy,
+            fill_price: 100 * FIXED_SCALE,
+            timestamp_ns: 10 * SEC,
+            ..Default::default()
+        };
+        tracker.record_fill(&fill);
+        assert_eq!(tracker.pending(), 2);
+
+        // Before the first horizon nothing resolves
+        assert_eq!(tracker.on_mid(7, 101 * FIXED_SCALE, 10 * SEC + 1), 0);
+        // 1s later mid is 100.5: +50bps for the buyer
+        assert_eq!(tracker.on_mid(7, 100 * FIXED_SCALE + FIXED_SCALE / 2, 11 * SEC), 1);
+        assert!((tracker.average_markout_bps(7, 0).unwrap() - 50.0).abs() < 1e-9);
+        assert_eq!(tracker.average_markout_bps(7, 1), None);
+
+        // 5s later mid fell to 99: adverse, -100bps
+        assert_eq!(tracker.on_mid(7, 99 * FIXED_SCALE, 15 * SEC), 1);
+        assert!((tracker.average_markout_bps(7, 1).unwrap() + 100.0).abs() < 1e-9);
+        assert_eq!(tracker.pending(), 0);
+
+        // Sells mirror: mid falling after a sell is favourable
+        let sell = FillEvent { side: Side::Sell, ..fill };
+        assert!((markout_bps(&sell, 99 * FIXED_SCALE) - 100.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_partial_cancel_reduces_remaining_and_display() {
+        let mut engine = ExecutionEngine::default();
+        let req = OrderRequest { display_qty: 400, ..order(5) };
+        let ack = engine.submit(&req).unwrap();
+
+        let reduced = engine.cancel_qty(ack.exchange_hash, 700).unwrap();
+        assert_eq!(reduced.status, OrderStatus::Open);
+        let live = engine.live_order(ack.exchange_hash).unwrap();
+        assert_eq!(live.quantity, 300);
+        assert_eq!(live.display_qty, 300);
+
+        let closed = engine.cancel_qty(ack.exchange_hash, 300).unwrap();
+        assert_eq!(closed.status, OrderStatus::Cancelled);
+        assert!(engine.live_order(ack.exchange_hash).is_none());
+        assert_eq!(engine.cancel_qty(ack.exchange_hash, 1).err(), Some(ExecError::UnknownOrder));
+
+        // After a partial fill the reduced order stays partially filled
+        let ack = engine.submit(&order(8)).unwrap();
+        engine.process_partial_fill(&ack, &order(8), 200, 67_500, Liquidity::Maker).unwrap();
+        let reduced = engine.cancel_qty(ack.exchange_hash, 300).unwrap();
+        assert_eq!(reduced.status, OrderStatus::PartiallyFilled);
+        assert_eq!(engine.order_status(ack.exchange_hash).unwrap().remaining_qty(), 500);
+        assert_eq!(engine.cancel_qty(ack.exchange_hash, 500).unwrap().status, OrderStatus::Cancelled);
+    }
+
+    #[test]
+    fn test_cancel_order_closes_live_order() {
+        let mut engine = ExecutionEngine::default();
+        let ack = engine.submit(&order(7)).unwrap();
+        assert_eq!(ack.status, OrderStatus::Open);
+
+        let cancelled = engine.cancel_order(ack.exchange_hash).unwrap();
+        assert_eq!((cancelled.status, cancelled.client_hash), (OrderStatus::Cancelled, 42));
+        assert!(cancelled.latency_ns >= 0);
+        assert!(engine.live_order(ack.exchange_hash).is_none());
+        assert_eq!(engine.cancel_order(ack.exchange_hash).err(), Some(ExecError::UnknownOrder));
+
+        let filled = engine.submit(&order(8)).unwrap();
+        engine.process_fill(&filled, &order(8)).unwrap();
+        assert_eq!(engine.cancel_order(filled.exchange_hash).err(), Some(ExecError::UnknownOrder));
+        assert_eq!(engine.cancel_order(12_345).err(), Some(ExecError::UnknownOrder));
+    }
+
+    #[test]
+    fn test_over_cancel_is_rejected() {
+        let mut engine = ExecutionEngine::default();
+        let ack = engine.submit(&order(6)).unwrap();
+
+        assert_eq!(
+            engine.cancel_qty(ack.exchange_hash, 1_001).err(),
+            Some(ExecError::ExceedsRemaining { remaining: 1_000 })
+        );
+        assert_eq!(engine.cancel_qty(ack.exchange_hash, 0).err(), Some(ExecError::InvalidQuantity));
+        assert_eq!(engine.live_order(ack.exchange_hash).unwrap().quantity, 1_000);
+
+        // Filled orders are no longer live
+        engine.process_fill(&ack, &order(6)).unwrap();
+        assert_eq!(engine.cancel_qty(ack.exchange_hash, 1).err(), Some(ExecError::UnknownOrder));
+    }
+
+    #[test]
+    fn test_non_tradeable_symbol_is_rejected() {
+        let mut engine = ExecutionEngine::default();
+        engine.instruments_mut().register(Instrument { symbol_hash: 7, tradeable: false, tick_size: 0 });
+        engine.instruments_mut().register(Instrument { symbol_hash: 8, tradeable: true, tick_size: 0 });
+
+        assert_eq!(engine.submit(&order(10)).err(), Some(ExecError::SymbolNotTradeable));
+        let enabled = OrderRequest { symbol_hash: 8, ..order(11) };
+        assert!(engine.submit(&enabled).is_ok());
+        assert_eq!(engine.stats().3, 1);
+
+        // Enabling later lets the same order through
+        engine.instruments_mut().set_tradeable(7, true);
+        assert!(engine.submit(&order(10)).is_ok());
+        assert_eq!(engine.submit(&order(10)).err(), Some(ExecError::DuplicateOrder));
+    }
+
+    #[test]
+    fn test_off_tick_limit_order_rejected_or_snapped() {
+        const TICK: i64 = FIXED_SCALE / 100; // 0.01
+        let mut engine = ExecutionEngine::default();
+        engine.instruments_mut().register(Instrument { symbol_hash: 7, tradeable: true, tick_size: TICK });
+        let off_tick = OrderRequest {
+            order_type: OrderType::Limit,
+            price: 100 * FIXED_SCALE + 3 * TICK / 4, // 100.0075
+            ..order(20)
+        };
+
+        assert_eq!(engine.tick_policy(), TickPolicy::Reject);
+        assert_eq!(engine.submit(&off_tick).err(), Some(ExecError::InvalidTick));
+        assert_eq!(engine.stats().3, 1);
+        // Market orders don't carry a meaningful price
+        let market = OrderRequest { order_type: OrderType::Market, ..off_tick };
+        assert!(engine.submit(&OrderRequest { idempotency_key: 21, ..market }).is_ok());
+
+        // Rejection left the key unused, so the retry goes through snapped
+        engine.set_tick_policy(TickPolicy::Snap);
+        let ack = engine.submit(&off_tick).unwrap();
+        assert_eq!(engine.live_order(ack.exchange_hash).unwrap().price, 100 * FIXED_SCALE + TICK);
+    }
+
+    #[test]
+    fn test_maker_and_taker_fills_tracked_separately() {
+        let mut engine = ExecutionEngine::default(); // 4 bps
+        let mut fill = |key: u64, order_type: OrderType, liquidity: Option<Liquidity>| {
+            let req = OrderRequest {
+                order_type,
+                quantity: FIXED_SCALE,
+                price: 1_000 * FIXED_SCALE,
+                ..order(key)
+            };
+            let ack = engine.submit(&req).unwrap();
+            match liquidity {
+                Some(liquidity) => engine.process_fill_as(&ack, &req, liquidity),
+                None => engine.process_fill(&ack, &req),
+            }
+            .unwrap()
+        };
+        assert_eq!(fill(1, OrderType::Limit, None).liquidity, Liquidity::Maker);
+        assert_eq!(fill(2, OrderType::Market, None).liquidity, Liquidity::Taker);
+        // Marketable limit reported as taker by the venue
+        assert_eq!(fill(3, OrderType::Limit, Some(Liquidity::Taker)).liquidity, Liquidity::Taker);
+
+        let fees = engine.fee_stats();
+        assert_eq!(
+            fees,
+            FeeStats {
+                maker_volume: 1_000 * FIXED_SCALE,
+                taker_volume: 2_000 * FIXED_SCALE,
+                maker_fees: 40_000_000,     // 0.4
+                taker_fees: 80_000_000,
+            }
+        );
+        assert_eq!(fees.effective_rate_bps(Liquidity::Taker), Some(4.0));
+
+        engine.reset();
+        assert_eq!(engine.fee_stats(), FeeStats::default());
+        assert_eq!(engine.fee_stats().effective_rate_bps(Liquidity::Maker), None);
+    }
+
+    #[test]
+    fn test_retransmitted_fill_applied_once() {
+        let mut engine = ExecutionEngine::new(2);
+        let req = OrderRequest { quantity: 5 * FIXED_SCALE, ..order(1) };
+        let ack = engine.submit(&req).unwrap();
+        let fill = FillEvent {
+            exchange_hash: ack.exchange_hash,
+            symbol_hash: req.symbol_hash,
+            side: Side::Buy,
+            filled_qty: FIXED_SCALE,
+            fill_price: 100 * FIXED_SCALE,
+            commission: 4_000_000,
+            seq_id: 9,
+            ..Default::default()
+        };
+
+        assert!(engine.apply_fill(&fill));
+        assert!(!engine.apply_fill(&fill));
+        assert_eq!(engine.duplicate_fills(), 1);
+        assert_eq!(engine.position(req.symbol_hash), FIXED_SCALE);
+        assert_eq!(engine.fee_stats().taker_fees, 4_000_000);
+        assert_eq!(engine.live_order(ack.exchange_hash).unwrap().quantity, 4 * FIXED_SCALE);
+        assert_eq!(engine.stats().2, 1);
+
+        // Next fill on the same order is distinct
+        assert!(engine.apply_fill(&FillEvent { seq_id: 10, ..fill }));
+        assert_eq!(engine.position(req.symbol_hash), 2 * FIXED_SCALE);
+
+        // Bounded memory: the oldest id is forgotten first
+        assert!(engine.apply_fill(&FillEvent { seq_id: 11, ..fill }));
+        assert!(engine.apply_fill(&FillEvent { seq_id: 9, ..fill }));
+        assert!(!engine.apply_fill(&FillEvent { seq_id: 11, ..fill }));
+    }
+
+    #[test]
+    fn test_idempotency_keys_expire_by_ttl_not_bulk_clear() {
+        use std::sync::atomic::{AtomicI64, Ordering};
+        use std::sync::Arc;
+
+        let now = Arc::new(AtomicI64::new(1_000));
+        let mut engine = ExecutionEngine::new(3);
+        let clock = now.clone();
+        engine.set_clock(Box::new(move || clock.load(Ordering::Relaxed)));
+
+        engine.submit(&order(1)).unwrap();
+        now.fetch_add(DEFAULT_IDEMPOTENCY_TTL_NS - 1, Ordering::Relaxed);
+        assert_eq!(engine.submit(&order(1)).err(), Some(ExecError::DuplicateOrder));
+
+        now.fetch_add(1, Ordering::Relaxed);
+        assert!(engine.submit(&order(1)).is_ok());
+
+        // At the cap only the oldest key is forgotten
+        engine.submit(&order(2)).unwrap();
+        engine.submit(&order(3)).unwrap();
+        engine.submit(&order(4)).unwrap();
+        assert_eq!(engine.tracked_keys(), 3);
+        assert!(engine.submit(&order(1)).is_ok());
+        for key in [3, 4] {
+            assert_eq!(engine.submit(&order(key)).err(), Some(ExecError::DuplicateOrder));
+        }
+    }
+
+    #[test]
+    fn test_closed_order_states_expire_by_ttl_and_cap() {
+        use std::sync::atomic::{AtomicI64, Ordering};
+        use std::sync::Arc;
+
+        let now = Arc::new(AtomicI64::new(1_000));
+        let mut engine = ExecutionEngine::new(2);
+        let clock = now.clone();
+        engine.set_clock(Box::new(move || clock.load(Ordering::Relaxed)));
+
+        let req = order(1);
+        let filled = engine.submit(&req).unwrap();
+        engine.process_fill(&filled, &req).unwrap();
+        let cancelled = engine.submit(&order(2)).unwrap().exchange_hash;
+        engine.cancel_order(cancelled).unwrap();
+        let live = engine.submit(&order(3)).unwrap().exchange_hash;
+        assert_eq!(engine.tracked_orders(), 3);
+
+        // Closed states answer until the TTL lapses; live ones never expire
+        now.fetch_add(DEFAULT_ORDER_STATE_TTL_NS - 1, Ordering::Relaxed);
+        engine.submit(&order(4)).unwrap();
+        assert_eq!(engine.order_status(filled.exchange_hash).unwrap().status, OrderStatus::Filled);
+        now.fetch_add(1, Ordering::Relaxed);
+        engine.submit(&order(5)).unwrap();
+        assert!(engine.order_status(filled.exchange_hash).is_none());
+        assert!(engine.order_status(cancelled).is_none());
+        assert_eq!(engine.order_status(live).unwrap().status, OrderStatus::Open);
+
+        // Past `max_keys` closed orders the oldest state goes immediately
+        engine.set_order_state_ttl_ns(i64::MAX);
+        let closed: Vec<u64> = (6..9).map(|key| engine.submit(&order(key)).unwrap().exchange_hash).collect();
+        for &hash in &closed {
+            engine.cancel_order(hash).unwrap();
+        }
+        assert!(engine.order_status(closed[0]).is_none());
+        assert!(closed[1..].iter().all(|&h| engine.order_status(h).is_some()));
+        assert_eq!(engine.tracked_orders(), 5);    // 3 live + 2 closed
+    }
+
+    #[test]
+    fn test_partial_fills_accumulate_to_filled() {
+        let mut engine = ExecutionEngine::default();
+        let req = OrderRequest { quantity: 10 * FIXED_SCALE, price: 101 * FIXED_SCALE, ..order(9) };
+        let ack = engine.submit(&req).unwrap();
+        assert_eq!(engine.order_status(ack.exchange_hash).unwrap().status, OrderStatus::Open);
+
+        let first = engine
+            .process_partial_fill(&ack, &req, 4 * FIXED_SCALE, 100 * FIXED_SCALE, Liquidity::Maker)
+            .unwrap();
+        assert_eq!(first.filled_qty, 4 * FIXED_SCALE);
+        let state = engine.order_status(ack.exchange_hash).unwrap();
+        assert_eq!(state.status, OrderStatus::PartiallyFilled);
+        assert_eq!(state.remaining_qty(), 6 * FIXED_SCALE);
+        assert_eq!(engine.live_order(ack.exchange_hash).unwrap().quantity, 6 * FIXED_SCALE);
+        assert_eq!(
+            engine.process_partial_fill(&ack, &req, 7 * FIXED_SCALE, 100 * FIXED_SCALE, Liquidity::Maker).err(),
+            Some(ExecError::ExceedsRemaining { remaining: 6 * FIXED_SCALE })
+        );
+
+        engine
+            .process_partial_fill(&ack, &req, 6 * FIXED_SCALE, 105 * FIXED_SCALE, Liquidity::Maker)
+            .unwrap();
+        let state = engine.order_status(ack.exchange_hash).unwrap();
+        assert_eq!(state.status, OrderStatus::Filled);
+        assert_eq!(state.filled_qty, 10 * FIXED_SCALE);
+        assert_eq!(state.avg_fill_price, 103 * FIXED_SCALE);   // (4*100 + 6*105) / 10
+        assert_eq!(state.remaining_qty(), 0);
+        assert!(engine.live_order(ack.exchange_hash).is_none());
+        assert_eq!(engine.position(req.symbol_hash), 10 * FIXED_SCALE);
+        assert_eq!(
+            engine.process_partial_fill(&ack, &req, 1, 100 * FIXED_SCALE, Liquidity::Maker).err(),
+            Some(ExecError::UnknownOrder)
+        );
+    }
+
+    #[test]
+    fn test_full_fill_after_partial_books_only_remainder() {
+        let mut engine = ExecutionEngine::default();
+        let req = OrderRequest { quantity: 10 * FIXED_SCALE, price: 100 * FIXED_SCALE, ..order(12) };
+        let ack = engine.submit(&req).unwrap();
+        engine
+            .process_partial_fill(&ack, &req, 4 * FIXED_SCALE, 100 * FIXED_SCALE, Liquidity::Maker)
+            .unwrap();
+
+        let rest = engine.process_fill(&ack, &req).unwrap();
+        assert_eq!(rest.filled_qty, 6 * FIXED_SCALE);
+        let state = engine.order_status(ack.exchange_hash).unwrap();
+        assert_eq!(state.status, OrderStatus::Filled);
+        assert_eq!(state.filled_qty, 10 * FIXED_SCALE);
+        assert_eq!(engine.position(req.symbol_hash), 10 * FIXED_SCALE);
+
+        // Nothing left to fill
+        assert_eq!(engine.process_fill(&ack, &req).err(), Some(ExecError::UnknownOrder));
+        assert_eq!(engine.position(req.symbol_hash), 10 * FIXED_SCALE);
+    }
+
+    #[test]
+    fn test_cancelled_order_cannot_be_filled() {
+        let mut engine = ExecutionEngine::default();
+        let req = OrderRequest { quantity: 10 * FIXED_SCALE, ..order(13) };
+        let ack = engine.submit(&req).unwrap();
+        engine.cancel_order(ack.exchange_hash).unwrap();
+
+        assert_eq!(engine.process_fill(&ack, &req).err(), Some(ExecError::UnknownOrder));
+        assert_eq!(
+            engine.process_partial_fill(&ack, &req, FIXED_SCALE, req.price, Liquidity::Maker).err(),
+            Some(ExecError::UnknownOrder)
+        );
+        let late = FillEvent {
+            exchange_hash: ack.exchange_hash,
+            symbol_hash: req.symbol_hash,
+            side: Side::Buy,
+            filled_qty: FIXED_SCALE,
+            fill_price: req.price,
+            seq_id: 1,
+            ..Default::default()
+        };
+        assert!(!engine.apply_fill(&late));
+
+        assert_eq!(engine.order_status(ack.exchange_hash).unwrap().status, OrderStatus::Cancelled);
+        assert_eq!(engine.position(req.symbol_hash), 0);
+        assert_eq!(engine.stats().2, 0);
+    }
+
+    #[test]
+    fn test_maker_rebate_is_negative_and_taker_uses_taker_rate() {
+        let fees = FeeModel {
+            maker_bps: -1,
+            taker_bps: 5,
+            min_commission: 1_000,
+            ..FeeModel::default()
+        };
+        let mut engine = ExecutionEngine::with_fee_model(1_000, fees);
+        let mut fill = |key: u64, order_type: OrderType| {
+            let req = OrderRequest {
+                order_type,
+                quantity: FIXED_SCALE,
+                price: 1_000 * FIXED_SCALE,
+                ..order(key)
+            };
+            let ack = engine.submit(&req).unwrap();
+            engine.process_fill(&ack, &req).unwrap()
+        };
+
+        // 1,000 notional: -0.1 rebate (minimum fee not applied), 0.5 taker fee
+        assert_eq!(fill(1, OrderType::Limit).commission, -10_000_000);
+        assert_eq!(fill(2, OrderType::Market).commission, 50_000_000);
+        assert_eq!(engine.fee_stats().maker_fees, -10_000_000);
+        assert_eq!(engine.fee_stats().effective_rate_bps(Liquidity::Maker), Some(-1.0));
+    }
+
+    #[test]
+    fn test_validate_rejects_each_bad_field() {
+        assert_eq!(validate(&order(1)), Ok(()));
+        assert_eq!(validate(&OrderRequest { symbol_hash: 0, ..order(1) }), Err(ExecError::MissingSymbol));
+        assert_eq!(validate(&OrderRequest { quantity: 0, ..order(1) }), Err(ExecError::InvalidQuantity));
+        assert_eq!(validate(&OrderRequest { quantity: -5, ..order(1) }), Err(ExecError::InvalidQuantity));
+        assert_eq!(validate(&OrderRequest { price: 0, ..order(1) }), Err(ExecError::InvalidPrice));
+        assert_eq!(validate(&OrderRequest { price: -1, ..order(1) }), Err(ExecError::InvalidPrice));
+
+        // Market orders may leave the price at zero
+        let market = OrderRequest { order_type: OrderType::Market, price: 0, ..order(1) };
+        assert_eq!(validate(&market), Ok(()));
+
+        // Rejected before the key is remembered, so a corrected retry goes through
+        let mut engine = ExecutionEngine::default();
+        assert_eq!(engine.submit(&OrderRequest { quantity: 0, ..order(2) }).err(), Some(ExecError::InvalidQuantity));
+        assert!(engine.submit(&order(2)).is_ok());
+        assert_eq!(engine.stats().3, 1);
+    }
+}
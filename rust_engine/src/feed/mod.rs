@@ -0,0 +1,1810 @@
+// Feed module — Exchange Feed Connection Management
+//
+// Features:
+// - Transport-agnostic feed loop (WebSocket, replay, mocks)
+// - Bounded consecutive reconnects before fatal shutdown
+// - Reconnect backoff jittered from the pipeline seed (replayable)
+// - Shared shutdown flag so a dead feed stops the whole pipeline
+// - Heartbeat watchdog: silent feeds are torn down, reconnected and resynced
+// - Exchange vs local clock-skew estimation (sliding minimum delay)
+// - Pause/resume ingestion without dropping the connection
+// - Exchange trade prints parsed directly (Binance @trade)
+// - Best bid/ask updates parsed for top-of-book-only symbols (@bookTicker)
+// - Effective spread paid by aggressors, averaged per symbol
+// - Max frame size guard ahead of parsing
+// - Recorded feed replay with cold-start handoff to the live feed
+// - Replay speed multiplier honoring recorded inter-event timing
+// - Persisted last-applied seq per symbol for warm (resume) reconnects
+// - Binance diff depth parsed into multi-level depth updates (@depth)
+// - Binance snapshot + buffered-delta sequencing (DepthSync)
+// - WebSocket transport and REST depth snapshots for live Binance
+
+pub mod feed {
+    use crate::execution::Side;
+    use crate::orderbook::{
+        symbol_hash, DeltaOutcome, L2Orderbook, Levels, OrderbookManager, OrderbookSnapshot, Price, Qty, TopOfBook,
+        TradeActivity,
+    };
+    use crate::rng::{PipelineRng, StreamRng};
+    use crossbeam_channel::Sender;
+    use serde::Deserialize;
+    use std::collections::{HashMap, VecDeque};
+    use std::fs::File;
+    use std::io::{BufRead, BufReader, Read};
+    use std::path::Path;
+    use std::sync::atomic::{AtomicBool, AtomicI64, AtomicU64, Ordering};
+    use std::sync::Arc;
+    use std::time::{Duration, Instant};
+
+    /// Raw message transport beneath the feed
+    pub trait FeedTransport {
+        /// (Re-)establish the connection
+        fn connect(&mut self) -> Result<(), &'static str>;
+
+        /// Next raw frame: Ok(None) when idle, Err when the connection dropped
+        fn recv(&mut self) -> Result<Option<Vec<u8>>, &'static str>;
+    }
+
+    /// Exchange-reported trade print
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub struct TradeEvent {
+        pub symbol_hash: u64,
+        pub trade_id: u64,
+        pub price: Price,
+        pub qty: Qty,
+        pub aggressor: Side,    // Taker side
+        pub ts_ns: i64,
+    }
+
+    impl TradeEvent {
+        /// Feed the print into per-level trade activity - a buy aggressor
+        /// lifts the ask, a sell aggressor hits the bid
+        pub fn record_into(&self, activity: &mut TradeActivity) {
+            activity.record_trade(self.price, self.qty, !self.aggressor.is_buy(), self.ts_ns);
+        }
+    }
+
+    /// Exchange best bid/ask update
+    #[derive(Clone, Debug, PartialEq)]
+    pub struct BookTickerEvent {
+        pub symbol: String,     // Kept for routing to string-keyed books
+        pub update_id: u64,
+        pub bid: (Price, Qty),
+        pub ask: (Price, Qty),
+    }
+
+    impl BookTickerEvent {
+        pub fn apply_to(&self, top: &mut TopOfBook) -> bool {
+            top.apply(self.update_id, self.bid, self.ask)
+        }
+
+        /// Route to the symbol's top-of-book-only entry
+        pub fn apply_to_manager(&self, manager: &mut OrderbookManager) -> bool {
+            manager.apply_top_of_book(&self.symbol, self.update_id, self.bid, self.ask)
+        }
+    }
+
+    /// Exchange diff depth update covering update ids `first..=final`
+    #[derive(Clone, Debug, PartialEq)]
+    pub struct DepthUpdateEvent {
+        pub symbol_hash: u64,
+        pub first_update_id: u64,               // Binance `U`
+        pub final_update_id: u64,               // Binance `u`
+        pub prev_final_update_id: Option<u64>,  // Binance futures `pu`
+        pub bids: Levels,                       // qty 0 = delete level
+        pub asks: Levels,
+        pub event_ts_ns: i64,                   // Exchange event time
+    }
+
+    impl DepthUpdateEvent {
+        /// Update id the book must already be at for this event to follow on
+        pub fn expected_prev(&self) -> u64 {
+            self.prev_final_update_id.unwrap_or(self.first_update_id.saturating_sub(1))
+        }
+
+        /// Apply every level, chaining from `prev_seq` - O(k log n)
+        /// Stops at a gap; otherwise the last non-Applied outcome wins
+        pub fn apply_to(&self, book: &mut L2Orderbook, prev_seq: u64) -> DeltaOutcome {
+            let mut prev = prev_seq;
+            let mut outcome = DeltaOutcome::Applied;
+            for (levels, is_bid) in [(&self.bids, true), (&self.asks, false)] {
+                for &(price, qty) in levels {
+                    match book.apply_delta_with_prev(Price(price), Qty(qty), is_bid, Some(prev), self.final_update_id) {
+                        gap @ DeltaOutcome::Gap { .. } => return gap,
+                        DeltaOutcome::Applied => {}
+                        other => outcome = other,
+                    }
+                    prev = self.final_update_id;
+                }
+            }
+            outcome
+        }
+    }
+
+    /// Decoded feed frame
+    #[derive(Clone, Debug, PartialEq)]
+    pub enum FeedEvent {
+        Trade(TradeEvent),
+        BookTicker(BookTickerEvent),
+        Depth(DepthUpdateEvent),
+        Raw(Vec<u8>),           // Anything else, for the book path
+    }
+
+    /// Binance `<symbol>@trade` payload
+    #[derive(Deserialize)]
+    struct BinanceTrade {
+        #[serde(rename = "s")]
+        symbol: String,
+        #[serde(rename = "t")]
+        trade_id: u64,
+        #[serde(rename = "p")]
+        price: String,
+        #[serde(rename = "q")]
+        qty: String,
+        #[serde(rename = "T")]
+        trade_time_ms: i64,
+        #[serde(rename = "m")]
+        buyer_is_maker: bool,
+    }
+
+    /// Binance `<symbol>@bookTicker` payload (spot omits `e`)
+    #[derive(Deserialize)]
+    struct BinanceBookTicker {
+        #[serde(rename = "u")]
+        update_id: u64,
+        #[serde(rename = "s")]
+        symbol: String,
+        #[serde(rename = "b")]
+        bid_price: String,
+        #[serde(rename = "B")]
+        bid_qty: String,
+        #[serde(rename = "a")]
+        ask_price: String,
+        #[serde(rename = "A")]
+        ask_qty: String,
+    }
+
+    /// Binance `<symbol>@depth` payload (`pu` on futures only)
+    #[derive(Deserialize)]
+    struct BinanceDepthUpdate {
+        #[serde(rename = "E")]
+        event_time_ms: i64,
+        #[serde(rename = "s")]
+        symbol: String,
+        #[serde(rename = "U")]
+        first_update_id: u64,
+        #[serde(rename = "u")]
+        final_update_id: u64,
+        #[serde(rename = "pu", default)]
+        prev_final_update_id: Option<u64>,
+        #[serde(rename = "b")]
+        bids: Vec<(String, String)>,
+        #[serde(rename = "a")]
+        asks: Vec<(String, String)>,
+    }
+
+    /// Binance REST depth snapshot (`GET /api/v3/depth`)
+    #[derive(Deserialize)]
+    struct BinanceDepthSnapshot {
+        #[serde(rename = "lastUpdateId")]
+        last_update_id: u64,
+        bids: Vec<(String, String)>,
+        asks: Vec<(String, String)>,
+    }
+
+    /// String price/qty pairs as numeric levels
+    fn parse_levels(levels: &[(String, String)], err: &'static str) -> Result<Levels, &'static str> {
+        levels
+            .iter()
+            .map(|(p, q)| Ok((p.parse::<f64>().map_err(|_| err)?, q.parse::<f64>().map_err(|_| err)?)))
+            .collect()
+    }
+
+    /// Default frame size cap - far above any legitimate depth/trade message
+    pub const DEFAULT_MAX_FRAME_BYTES: usize = 1 << 20;
+
+    /// Decode a frame - trade prints become `FeedEvent::Trade`, bypassing
+    /// trade inference, and best bid/ask updates `FeedEvent::BookTicker`;
+    /// accepts raw and combined-stream (`data`) envelopes
+    pub fn parse_frame(frame: &[u8]) -> Result<FeedEvent, &'static str> {
+        parse_frame_limited(frame, DEFAULT_MAX_FRAME_BYTES)
+    }
+
+    /// `parse_frame` with an explicit size cap (0 = unlimited) - oversize
+    /// frames are rejected before any parsing or allocation
+    pub fn parse_frame_limited(frame: &[u8], max_frame_bytes: usize) -> Result<FeedEvent, &'static str> {
+        if max_frame_bytes > 0 && frame.len() > max_frame_bytes {
+            return Err("OVERSIZE_FRAME");
+        }
+        let value: serde_json::Value =
+            serde_json::from_slice(frame).map_err(|_| "MALFORMED_FRAME")?;
+        let payload = value.get("data").unwrap_or(&value);
+        match payload.get("e").and_then(|e| e.as_str()) {
+            Some("trade") => {}
+            Some("bookTicker") => return parse_book_ticker(payload),
+            Some("depthUpdate") => return parse_depth_update(payload),
+            None if payload.get("B").is_some() && payload.get("A").is_some() => {
+                return parse_book_ticker(payload);
+            }
+            _ => return Ok(FeedEvent::Raw(frame.to_vec())),
+        }
+
+        let trade = BinanceTrade::deserialize(payload).map_err(|_| "MALFORMED_TRADE")?;
+        let price = trade.price.parse::<f64>().map_err(|_| "MALFORMED_TRADE")?;
+        let qty = trade.qty.parse::<f64>().map_err(|_| "MALFORMED_TRADE")?;
+        Ok(FeedEvent::Trade(TradeEvent {
+            symbol_hash: symbol_hash(&trade.symbol),
+            trade_id: trade.trade_id,
+            price: Price(price),
+            qty: Qty(qty),
+            // Buyer is maker -> the seller crossed the spread
+            aggressor: if trade.buyer_is_maker { Side::Sell } else { Side::Buy },
+            ts_ns: trade.trade_time_ms * 1_000_000,
+        }))
+    }
+
+    fn parse_book_ticker(payload: &serde_json::Value) -> Result<FeedEvent, &'static str> {
+        let ticker = BinanceBookTicker::deserialize(payload).map_err(|_| "MALFORMED_BOOK_TICKER")?;
+        let num = |s: &str| s.parse::<f64>().map_err(|_| "MALFORMED_BOOK_TICKER");
+        Ok(FeedEvent::BookTicker(BookTickerEvent {
+            update_id: ticker.update_id,
+            bid: (Price(num(&ticker.bid_price)?), Qty(num(&ticker.bid_qty)?)),
+            ask: (Price(num(&ticker.ask_price)?), Qty(num(&ticker.ask_qty)?)),
+            symbol: ticker.symbol,
+        }))
+    }
+
+    fn parse_depth_update(payload: &serde_json::Value) -> Result<FeedEvent, &'static str> {
+        let update = BinanceDepthUpdate::deserialize(payload).map_err(|_| "MALFORMED_DEPTH")?;
+        Ok(FeedEvent::Depth(DepthUpdateEvent {
+            symbol_hash: symbol_hash(&update.symbol),
+            first_update_id: update.first_update_id,
+            final_update_id: update.final_update_id,
+            prev_final_update_id: update.prev_final_update_id,
+            bids: parse_levels(&update.bids, "MALFORMED_DEPTH")?,
+            asks: parse_levels(&update.asks, "MALFORMED_DEPTH")?,
+            event_ts_ns: update.event_time_ms * 1_000_000,
+        }))
+    }
+
+    /// Decode a REST depth snapshot body; `lastUpdateId` becomes the seq
+    pub fn parse_depth_snapshot(symbol: &str, body: &[u8]) -> Result<OrderbookSnapshot, &'static str> {
+        let snapshot: BinanceDepthSnapshot = serde_json::from_slice(body).map_err(|_| "MALFORMED_SNAPSHOT")?;
+        Ok(OrderbookSnapshot {
+            symbol_hash: symbol_hash(symbol),
+            seq_id: snapshot.last_update_id,
+            bids: parse_levels(&snapshot.bids, "MALFORMED_SNAPSHOT")?,
+            asks: parse_levels(&snapshot.asks, "MALFORMED_SNAPSHOT")?,
+        })
+    }
+
+    /// Result of routing a depth update through `DepthSync`
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub enum DepthSyncOutcome {
+        Buffered,               // Waiting for a snapshot
+        Stale,                  // Already covered by the book, dropped
+        Applied(DeltaOutcome),  // Applied, or Crossed/InvalidPrice
+        Gap,                    // Broke the chain; buffering until a new snapshot
+    }
+
+    /// Snapshot + buffered-delta sequencing (Binance "manage a local book")
+    ///
+    /// Updates are buffered until `on_snapshot`. Those the snapshot already
+    /// covers (`final <= lastUpdateId`) are dropped; the first one kept may
+    /// straddle `lastUpdateId + 1`, and each after that must chain on the
+    /// previous one. A break puts the sync back into buffering - request a
+    /// new snapshot when `on_update` reports `Gap`.
+    pub struct DepthSync {
+        buffer: VecDeque<DepthUpdateEvent>,
+        max_buffered: usize,
+        synced: bool,
+        awaiting_first: bool,       // Next update may straddle the snapshot seq
+        pub stale_dropped: u64,
+        pub buffer_overflows: u64,  // Oldest buffered updates evicted
+    }
+
+    impl DepthSync {
+        pub fn new(max_buffered: usize) -> Self {
+            Self {
+                buffer: VecDeque::with_capacity(max_buffered.min(4_096)),
+                max_buffered: max_buffered.max(1),
+                synced: false,
+                awaiting_first: false,
+                stale_dropped: 0,
+                buffer_overflows: 0,
+            }
+        }
+
+        pub fn is_synced(&self) -> bool {
+            self.synced
+        }
+
+        pub fn buffered(&self) -> usize {
+            self.buffer.len()
+        }
+
+        /// Stop applying and buffer until the next snapshot
+        pub fn begin_resync(&mut self) {
+            self.synced = false;
+            self.awaiting_first = false;
+        }
+
+        fn buffer_update(&mut self, update: DepthUpdateEvent) {
+            if self.buffer.len() == self.max_buffered {
+                self.buffer.pop_front();
+                self.buffer_overflows += 1;
+            }
+            self.buffer.push_back(update);
+        }
+
+        /// Route a live update - O(k log n) when synced, O(1) while buffering
+        pub fn on_update(&mut self, update: DepthUpdateEvent, book: &mut L2Orderbook) -> DepthSyncOutcome {
+            if !self.synced {
+                self.buffer_update(update);
+                return DepthSyncOutcome::Buffered;
+            }
+            match self.apply(&update, book) {
+                None => DepthSyncOutcome::Stale,
+                Some(DeltaOutcome::Gap { .. }) => {
+                    self.begin_resync();
+                    self.buffer_update(update);
+                    DepthSyncOutcome::Gap
+                }
+                Some(outcome) => DepthSyncOutcome::Applied(outcome),
+            }
+        }
+
+        /// Rebuild the book from `snapshot` and replay what was buffered
+        /// Returns the number of updates replayed; on
+        /// `Err("SNAPSHOT_TOO_OLD")` the buffer no longer joins the snapshot
+        /// and a newer one is needed
+        pub fn on_snapshot(&mut self, snapshot: &OrderbookSnapshot, book: &mut L2Orderbook) -> Result<usize, &'static str> {
+            book.resync(snapshot);
+            self.awaiting_first = true;
+
+            let mut replayed = 0;
+            while let Some(update) = self.buffer.pop_front() {
+                match self.apply(&update, book) {
+                    None => {}
+                    Some(DeltaOutcome::Gap { .. }) => {
+                        self.begin_resync();
+                        self.buffer.push_front(update);
+                        return Err("SNAPSHOT_TOO_OLD");
+                    }
+                    Some(_) => replayed += 1,
+                }
+            }
+            self.synced = true;
+            Ok(replayed)
+        }
+
+        /// None when the book already covers the update
+        fn apply(&mut self, update: &DepthUpdateEvent, book: &mut L2Orderbook) -> Option<DeltaOutcome> {
+            let last = book.last_seq_id.load(Ordering::Relaxed);
+            if update.final_update_id <= last {
+                self.stale_dropped += 1;
+                return None;
+            }
+            let prev = if self.awaiting_first && update.first_update_id <= last + 1 {
+                last
+            } else {
+                update.expected_prev()
+            };
+            let outcome = update.apply_to(book, prev);
+            if !matches!(outcome, DeltaOutcome::Gap { .. }) {
+                self.awaiting_first = false;
+            }
+            Some(outcome)
+        }
+    }
+
+    /// Feed connection policy
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub struct FeedConfig {
+        pub max_reconnects: u32,            // Consecutive failures before fatal, 0 = unlimited
+        pub reconnect_backoff: Duration,   // Mean delay; each wait is jittered to 0.5-1.5x
+        pub max_frame_bytes: usize,         // Larger frames are dropped, 0 = unlimited
+    }
+
+    impl Default for FeedConfig {
+        fn default() -> Self {
+            Self {
+                max_reconnects: 10,
+                reconnect_backoff: Duration::from_secs(1),
+                max_frame_bytes: DEFAULT_MAX_FRAME_BYTES,
+            }
+        }
+    }
+
+    /// Shared pause switch for ingestion
+    #[derive(Debug, Default)]
+    pub struct IngestionControl {
+        paused: AtomicBool,
+    }
+
+    impl IngestionControl {
+        pub fn pause(&self) {
+            self.paused.store(true, Ordering::Release);
+        }
+
+        pub fn resume(&self) {
+            self.paused.store(false, Ordering::Release);
+        }
+
+        #[inline(always)]
+        pub fn is_paused(&self) -> bool {
+            self.paused.load(Ordering::Acquire)
+        }
+    }
+
+    /// Wall clock in ns - comparable across the feed and heartbeat threads
+    fn unix_now_ns() -> i64 {
+        chrono::Utc::now().timestamp_nanos_opt().unwrap_or(0)
+    }
+
+    /// Feed liveness shared between the runner and a heartbeat watchdog
+    #[derive(Debug, Default)]
+    pub struct FeedHealth {
+        last_message_ns: AtomicI64,     // Last frame or connect, 0 = never
+        reconnect_requested: AtomicBool,
+        resync_pending: AtomicBool,
+        pub stall_reconnects: AtomicU64,
+        reconnects: AtomicU64,          // All reconnects, stalls included
+    }
+
+    impl FeedHealth {
+        #[inline(always)]
+        pub fn touch(&self, now_ns: i64) {
+            self.last_message_ns.store(now_ns, Ordering::Release);
+        }
+
+        pub fn last_message_ns(&self) -> i64 {
+            self.last_message_ns.load(Ordering::Acquire)
+        }
+
+        /// Heartbeat check - requests a reconnect once the feed has been
+        /// silent for more than `max_silence_ns`; true when this call did
+        pub fn check(&self, now_ns: i64, max_silence_ns: i64) -> bool {
+            let last = self.last_message_ns();
+            if last == 0 || now_ns - last <= max_silence_ns {
+                return false;
+            }
+            !self.reconnect_requested.swap(true, Ordering::AcqRel)
+        }
+
+        /// Reconnects of the runner sharing this health, for metrics
+        pub fn reconnects(&self) -> u64 {
+            self.reconnects.load(Ordering::Relaxed)
+        }
+
+        fn take_reconnect(&self) -> bool {
+            self.reconnect_requested.swap(false, Ordering::AcqRel)
+        }
+
+        /// True once after each (re)connect: books must be cleared and
+        /// rebuilt from a fresh snapshot
+        pub fn take_resync(&self) -> bool {
+            self.resync_pending.swap(false, Ordering::AcqRel)
+        }
+
+        /// Check every `interval` until shutdown
+        pub fn spawn_heartbeat(
+            self: &Arc<Self>,
+            interval: Duration,
+            max_silence: Duration,
+            shutdown: Arc<AtomicBool>,
+        ) -> std::thread::JoinHandle<()> {
+            let health = Arc::clone(self);
+            let max_silence_ns = max_silence.as_nanos() as i64;
+            std::thread::spawn(move || {
+                while !shutdown.load(Ordering::Acquire) {
+                    std::thread::sleep(interval);
+                    if health.check(unix_now_ns(), max_silence_ns) {
+                        tracing::warn!(silent_ms = (unix_now_ns() - health.last_message_ns()) / 1_000_000, "feed stalled, reconnecting");
+                    }
+                }
+            })
+        }
+    }
+
+    /// Drives a transport, forwarding frames until shutdown
+    pub struct FeedRunner<T: FeedTransport> {
+        transport: T,
+        config: FeedConfig,
+        shutdown: Arc<AtomicBool>,
+        control: Arc<IngestionControl>,
+        health: Arc<FeedHealth>,
+        connected: bool,
+        consecutive_failures: u32,
+        backoff_rng: StreamRng,
+        clock: Box<dyn ReplayClock>,
+        pub reconnects: AtomicU64,
+        pub frames_received: AtomicU64,
+        pub frames_discarded: AtomicU64,    // Read while paused
+        pub oversize_frames: AtomicU64,
+    }
+
+    impl<T: FeedTransport> FeedRunner<T> {
+        pub fn new(transport: T, config: FeedConfig, shutdown: Arc<AtomicBool>) -> Self {
+            Self {
+                transport,
+                config,
+                shutdown,
+                control: Arc::new(IngestionControl::default()),
+                health: Arc::new(FeedHealth::default()),
+                connected: false,
+                consecutive_failures: 0,
+                backoff_rng: PipelineRng::default().stream("feed_backoff"),
+                clock: Box::new(SystemClock::default()),
+                reconnects: AtomicU64::new(0),
+                frames_received: AtomicU64::new(0),
+                frames_discarded: AtomicU64::new(0),
+                oversize_frames: AtomicU64::new(0),
+            }
+        }
+
+        /// Draw backoff jitter from the run's `feed_backoff` sub-stream
+        pub fn with_rng(mut self, rng: &PipelineRng) -> Self {
+            self.backoff_rng = rng.stream("feed_backoff");
+            self
+        }
+
+        /// Clock that reconnect backoffs sleep on
+        pub fn with_clock(mut self, clock: Box<dyn ReplayClock>) -> Self {
+            self.clock = clock;
+            self
+        }
+
+        /// Run until the shared shutdown flag is raised
+        /// Exceeding `max_reconnects` raises the flag itself and returns
+        /// `Err("MAX_RECONNECTS_EXCEEDED")` so the supervisor can restart cleanly
+        pub fn run(&mut self, frame_tx: &Sender<Vec<u8>>) -> Result<(), &'static str> {
+            while !self.shutdown.load(Ordering::Acquire) {
+                if !self.connected {
+                    self.connect_once()?;
+                    continue;
+                }
+                // Heartbeat found the feed silent: drop it, connect afresh
+                if self.health.take_reconnect() {
+                    self.disconnect();
+                    self.health.stall_reconnects.fetch_add(1, Ordering::Relaxed);
+                    continue;
+                }
+
+                let received = self.transport.recv();
+                if let Ok(Some(_)) = received {
+                    self.health.touch(unix_now_ns());
+                }
+                match received {
+                    // Paused: keep reading so the connection/heartbeat stays
+                    // alive, but produce nothing downstream
+                    Ok(Some(_)) if self.control.is_paused() => {
+                        self.frames_discarded.fetch_add(1, Ordering::Relaxed);
+                    }
+                    Ok(Some(frame))
+                        if self.config.max_frame_bytes > 0
+                            && frame.len() > self.config.max_frame_bytes =>
+                    {
+                        self.oversize_frames.fetch_add(1, Ordering::Relaxed);
+                        tracing::warn!(bytes = frame.len(), "dropping oversize feed frame");
+                    }
+                    Ok(Some(frame)) => {
+                        self.frames_received.fetch_add(1, Ordering::Relaxed);
+                        if frame_tx.send(frame).is_err() {
+                            return Err("FRAME_CHANNEL_DISCONNECTED");
+                        }
+                    }
+                    Ok(None) => std::thread::yield_now(),
+                    Err(_) => self.disconnect(),
+                }
+            }
+            Ok(())
+        }
+
+        /// Drop the connection; the next loop iteration reconnects
+        fn disconnect(&mut self) {
+            self.connected = false;
+            self.reconnects.fetch_add(1, Ordering::Relaxed);
+            self.health.reconnects.fetch_add(1, Ordering::Relaxed);
+        }
+
+        /// Single connection attempt with failure accounting
+        fn connect_once(&mut self) -> Result<(), &'static str> {
+            match self.transport.connect() {
+                Ok(()) => {
+                    self.connected = true;
+                    self.consecutive_failures = 0;
+                    // Silence is measured from the connect; updates missed
+                    // while disconnected make the books unusable
+                    self.health.touch(unix_now_ns());
+                    self.health.resync_pending.store(true, Ordering::Release);
+                    Ok(())
+                }
+                Err(_) => {
+                    self.consecutive_failures += 1;
+                    let max = self.config.max_reconnects;
+                    if max > 0 && self.consecutive_failures >= max {
+                        self.shutdown.store(true, Ordering::Release);
+                        return Err("MAX_RECONNECTS_EXCEEDED");
+                    }
+                    // Jitter keeps many feeds from reconnecting in lockstep
+                    let jitter = 0.5 + self.backoff_rng.next_f64();
+                    self.clock.sleep(self.config.reconnect_backoff.mul_f64(jitter));
+                    Ok(())
+                }
+            }
+        }
+
+        /// Consecutive failed connection attempts since the last success
+        pub fn consecutive_failures(&self) -> u32 {
+            self.consecutive_failures
+        }
+
+        /// Pause/resume switch shared with operators and the processor
+        pub fn control(&self) -> Arc<IngestionControl> {
+            self.control.clone()
+        }
+
+        /// Liveness shared with the heartbeat and the book consumer
+        pub fn health(&self) -> Arc<FeedHealth> {
+            self.health.clone()
+        }
+
+        pub fn transport(&self) -> &T {
+            &self.transport
+        }
+
+        pub fn transport_mut(&mut self) -> &mut T {
+            &mut self.transport
+        }
+    }
+
+    /// Exchange event time of a frame in ns (`E`, else `T`, in ms)
+    pub fn frame_ts_ns(frame: &[u8]) -> Option<i64> {
+        let value: serde_json::Value = serde_json::from_slice(frame).ok()?;
+        let payload = value.get("data").unwrap_or(&value);
+        let ms = payload.get("E").or_else(|| payload.get("T"))?.as_i64()?;
+        Some(ms * 1_000_000)
+    }
+
+    /// Binance spot endpoints
+    pub const BINANCE_WS_URL: &str = "wss://stream.binance.com:9443";
+    pub const BINANCE_REST_URL: &str = "https://api.binance.com";
+
+    /// Combined-stream URL for each symbol's diff depth and book ticker
+    pub fn binance_stream_url(base: &str, symbols: &[&str]) -> String {
+        let streams: Vec<String> = symbols
+            .iter()
+            .map(|s| s.to_ascii_lowercase())
+            .flat_map(|s| [format!("{}@depth@100ms", s), format!("{}@bookTicker", s)])
+            .collect();
+        format!("{}/stream?streams={}", base, streams.join("/"))
+    }
+
+    /// WebSocket transport (ws:// or wss://) for `FeedRunner`
+    ///
+    /// Reads time out after `idle_timeout` so an idle socket reports
+    /// `Ok(None)` and the runner can observe shutdown; pings are answered by
+    /// the socket on read.
+    pub struct WebSocketTransport {
+        url: String,
+        idle_timeout: Duration,
+        max_frame_bytes: usize,
+        socket: Option<tungstenite::WebSocket<tungstenite::stream::MaybeTlsStream<std::net::TcpStream>>>,
+    }
+
+    impl WebSocketTransport {
+        pub fn new(url: impl Into<String>, idle_timeout: Duration) -> Self {
+            Self { url: url.into(), idle_timeout, max_frame_bytes: DEFAULT_MAX_FRAME_BYTES, socket: None }
+        }
+
+        /// Cap incoming messages and frames, normally at the runner's
+        /// `FeedConfig::max_frame_bytes`; 0 = unlimited
+        /// Enforced by the socket while reading, so an oversized message is
+        /// never buffered; it drops the connection with `WS_FRAME_TOO_LARGE`.
+        pub fn with_max_frame_bytes(mut self, max_frame_bytes: usize) -> Self {
+            self.max_frame_bytes = max_frame_bytes;
+            self
+        }
+
+        pub fn url(&self) -> &str {
+            &self.url
+        }
+    }
+
+    impl FeedTransport for WebSocketTransport {
+        fn connect(&mut self) -> Result<(), &'static str> {
+            use tungstenite::protocol::WebSocketConfig;
+            use tungstenite::stream::MaybeTlsStream;
+
+            self.socket = None;
+            let limit = (self.max_frame_bytes > 0).then_some(self.max_frame_bytes);
+            let ws_config = WebSocketConfig {
+                max_message_size: limit,
+                max_frame_size: limit,
+                ..Default::default()
+            };
+            let (socket, _) = tungstenite::client::connect_with_config(self.url.as_str(), Some(ws_config), 3).map_err(|e| {
+                tracing::warn!(url = %self.url, error = %e, "websocket connect failed");
+                "WS_CONNECT_FAILED"
+            })?;
+            let timeout = Some(self.idle_timeout.max(Duration::from_millis(1)));
+            let tcp = match socket.get_ref() {
+                MaybeTlsStream::Plain(tcp) => Some(tcp),
+                MaybeTlsStream::Rustls(tls) => Some(tls.get_ref()),
+                _ => None,
+            };
+            if let Some(tcp) = tcp {
+                tcp.set_read_timeout(timeout).map_err(|_| "WS_CONNECT_FAILED")?;
+            }
+            self.socket = Some(socket);
+            Ok(())
+        }
+
+        fn recv(&mut self) -> Result<Option<Vec<u8>>, &'static str> {
+            use tungstenite::error::CapacityError;
+            use tungstenite::{Error, Message};
+
+            let socket = self.socket.as_mut().ok_or("WS_NOT_CONNECTED")?;
+            match socket.read() {
+                Ok(Message::Text(text)) => Ok(Some(text.into_bytes())),
+                Ok(Message::Binary(bytes)) => Ok(Some(bytes)),
+                Ok(Message::Close(_)) => {
+                    self.socket = None;
+                    Err("WS_CLOSED")
+                }
+                Ok(_) => Ok(None),
+                Err(Error::Io(e))
+                    if matches!(e.kind(), std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut) =>
+                {
+                    Ok(None)
+                }
+                Err(Error::Capacity(CapacityError::MessageTooLong { size, max_size })) => {
+                    tracing::warn!(url = %self.url, size, max_size, "websocket message too large, dropping connection");
+                    self.socket = None;
+                    Err("WS_FRAME_TOO_LARGE")
+                }
+                Err(e) => {
+                    tracing::warn!(url = %self.url, error = %e, "websocket read failed");
+                    self.socket = None;
+                    Err("WS_DISCONNECTED")
+                }
+            }
+        }
+    }
+
+    /// Where `DepthSync` gets its snapshots
+    pub trait SnapshotSource {
+        fn fetch(&mut self, symbol: &str) -> Result<OrderbookSnapshot, &'static str>;
+    }
+
+    /// Binance REST depth snapshots (`/api/v3/depth`)
+    pub struct BinanceRestSnapshots {
+        pub base_url: String,
+        pub limit: u32,             // Levels per side, Binance caps at 5000
+        pub timeout: Duration,
+    }
+
+    impl Default for BinanceRestSnapshots {
+        fn default() -> Self {
+            Self {
+                base_url: BINANCE_REST_URL.to_string(),
+                limit: 1_000,
+                timeout: Duration::from_secs(5),
+            }
+        }
+    }
+
+    impl SnapshotSource for BinanceRestSnapshots {
+        fn fetch(&mut self, symbol: &str) -> Result<OrderbookSnapshot, &'static str> {
+            let url = format!("{}/api/v3/depth?symbol={}&limit={}", self.base_url, symbol.to_ascii_uppercase(), self.limit);
+            let mut body = Vec::new();
+            ureq::get(&url)
+                .timeout(self.timeout)
+                .call()
+                .map_err(|e| {
+                    tracing::warn!(%url, error = %e, "depth snapshot request failed");
+                    "SNAPSHOT_FETCH_FAILED"
+                })?
+                .into_reader()
+                .read_to_end(&mut body)
+                .map_err(|_| "SNAPSHOT_FETCH_FAILED")?;
+            parse_depth_snapshot(symbol, &body)
+        }
+    }
+
+    /// Time source for paced replay and reconnect backoff
+    pub trait ReplayClock: Send {
+        fn now_ns(&self) -> i64;
+        fn sleep(&mut self, duration: Duration);
+    }
+
+    /// Wall clock - monotonic, real sleeps
+    pub struct SystemClock {
+        origin: Instant,
+    }
+
+    impl Default for SystemClock {
+        fn default() -> Self {
+            Self { origin: Instant::now() }
+        }
+    }
+
+    impl ReplayClock for SystemClock {
+        fn now_ns(&self) -> i64 {
+            self.origin.elapsed().as_nanos() as i64
+        }
+
+        fn sleep(&mut self, duration: Duration) {
+            std::thread::sleep(duration);
+        }
+    }
+
+    /// Recorded feed played back as a transport - one frame per line
+    ///
+    /// With a speed multiplier, frames are released at their recorded
+    /// event-time offsets divided by `speed`, anchored to the first timed
+    /// frame so processing time doesn't accumulate as drift. Speed 0 (the
+    /// default) replays as fast as possible.
+    pub struct FeedReplayer<R: BufRead> {
+        reader: R,
+        exhausted: bool,
+        speed: f64,
+        clock: Box<dyn ReplayClock>,
+        anchor: Option<(i64, i64)>,     // (first event ts, clock at release)
+        pub frames_replayed: u64,
+    }
+
+    impl FeedReplayer<BufReader<File>> {
+        pub fn from_path(path: impl AsRef<Path>) -> std::io::Result<Self> {
+            Ok(Self::new(BufReader::new(File::open(path)?)))
+        }
+    }
+
+    impl<R: BufRead> FeedReplayer<R> {
+        pub fn new(reader: R) -> Self {
+            Self {
+                reader,
+                exhausted: false,
+                speed: 0.0,
+                clock: Box::new(SystemClock::default()),
+                anchor: None,
+                frames_replayed: 0,
+            }
+        }
+
+        /// Replay at `speed`x recorded time (2.0 = twice as fast), 0 = max speed
+        pub fn with_speed(mut self, speed: f64) -> Self {
+            self.speed = speed.max(0.0);
+            self
+        }
+
+        pub fn with_clock(mut self, clock: Box<dyn ReplayClock>) -> Self {
+            self.clock = clock;
+            self
+        }
+
+        /// Sleep until the frame's scaled offset from the anchor; untimed
+        /// frames pass straight through
+        fn pace(&mut self, frame: &[u8]) {
+            if self.speed == 0.0 {
+                return;
+            }
+            let Some(ts_ns) = frame_ts_ns(frame) else {
+                return;
+            };
+            let now = self.clock.now_ns();
+            let Some((first_ts, start)) = self.anchor else {
+                self.anchor = Some((ts_ns, now));
+                return;
+            };
+            let due = start + ((ts_ns - first_ts) as f64 / self.speed) as i64;
+            if due > now {
+                self.clock.sleep(Duration::from_nanos((due - now) as u64));
+            }
+        }
+
+        /// Next recorded frame, skipping blank lines; None once exhausted
+        pub fn next_frame(&mut self) -> Option<Vec<u8>> {
+            let mut line = Vec::new();
+            while !self.exhausted {
+                line.clear();
+                match self.reader.read_until(b'\n', &mut line) {
+                    Ok(0) | Err(_) => self.exhausted = true,
+                    Ok(_) => {
+                        while matches!(line.last(), Some(b'\n' | b'\r')) {
+                            line.pop();
+                        }
+                        if !line.is_empty() {
+                            self.pace(&line);
+                            self.frames_replayed += 1;
+                            return Some(line);
+                        }
+                    }
+                }
+            }
+            None
+        }
+
+        pub fn is_exhausted(&self) -> bool {
+            self.exhausted
+        }
+    }
+
+    impl<R: BufRead> FeedTransport for FeedReplayer<R> {
+        fn connect(&mut self) -> Result<(), &'static str> {
+            Ok(())
+        }
+
+        /// Idle forever once the recording runs out
+        fn recv(&mut self) -> Result<Option<Vec<u8>>, &'static str> {
+            Ok(self.next_frame())
+        }
+    }
+
+    /// Cold start: replay a recording to warm up downstream state, then
+    /// switch to the live transport on the same frame stream
+    ///
+    /// Consumers keep their book/indicators across the seam. Since the
+    /// recording and the live feed don't line up on sequence numbers, the
+    /// handoff raises `resync_signal()` so the book path fetches a fresh
+    /// depth snapshot before applying live deltas.
+    pub struct ColdStartTransport<R: BufRead, L: FeedTransport> {
+        replay: FeedReplayer<R>,
+        live: L,
+        is_live: bool,
+        resync: Arc<AtomicBool>,
+    }
+
+    impl<R: BufRead, L: FeedTransport> ColdStartTransport<R, L> {
+        pub fn new(replay: FeedReplayer<R>, live: L) -> Self {
+            Self {
+                replay,
+                live,
+                is_live: false,
+                resync: Arc::new(AtomicBool::new(false)),
+            }
+        }
+
+        /// Raised at the replay -> live seam; the consumer clears it once
+        /// the snapshot is applied
+        pub fn resync_signal(&self) -> Arc<AtomicBool> {
+            self.resync.clone()
+        }
+
+        pub fn is_live(&self) -> bool {
+            self.is_live
+        }
+
+        pub fn replay(&self) -> &FeedReplayer<R> {
+            &self.replay
+        }
+
+        pub fn live(&self) -> &L {
+            &self.live
+        }
+
+        pub fn live_mut(&mut self) -> &mut L {
+            &mut self.live
+        }
+    }
+
+    impl<R: BufRead, L: FeedTransport> FeedTransport for ColdStartTransport<R, L> {
+        fn connect(&mut self) -> Result<(), &'static str> {
+            if self.is_live {
+                self.live.connect()
+            } else {
+                Ok(())
+            }
+        }
+
+        /// Live connect failures at the seam surface as a dropped
+        /// connection, so the runner's reconnect policy applies
+        fn recv(&mut self) -> Result<Option<Vec<u8>>, &'static str> {
+            if self.is_live {
+                return self.live.recv();
+            }
+            if let Some(frame) = self.replay.next_frame() {
+                return Ok(Some(frame));
+            }
+
+            self.is_live = true;
+            self.resync.store(true, Ordering::Release);
+            tracing::info!(frames = self.replay.frames_replayed, "replay exhausted, switching to live feed");
+            self.live.connect()?;
+            self.live.recv()
+        }
+    }
+
+    /// How to recover a symbol's book after reconnecting
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub enum ResumePlan {
+        Resume { from_seq: u64 },   // Request incremental updates from here
+        Snapshot,                   // Refetch the full book
+    }
+
+    /// Last applied sequence per symbol, persisted across reconnects
+    #[derive(Debug, Default)]
+    pub struct SeqStore {
+        last_seq: HashMap<u64, u64>,
+        max_resume_gap: u64,
+    }
+
+    impl SeqStore {
+        /// Resume only when at most `max_resume_gap` updates were missed
+        pub fn new(max_resume_gap: u64) -> Self {
+            Self {
+                last_seq: HashMap::new(),
+                max_resume_gap,
+            }
+        }
+
+        /// Load a saved store; a missing file starts empty
+        pub fn load(path: impl AsRef<Path>, max_resume_gap: u64) -> std::io::Result<Self> {
+            let last_seq = match std::fs::read(path) {
+                Ok(bytes) => serde_json::from_slice(&bytes)
+                    .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => HashMap::new(),
+                Err(e) => return Err(e),
+            };
+            Ok(Self { last_seq, max_resume_gap })
+        }
+
+        /// Write via a temp file + rename so a crash never leaves a torn file
+        pub fn save(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+            let path = path.as_ref();
+            let tmp = path.with_extension("tmp");
+            std::fs::write(&tmp, serde_json::to_vec(&self.last_seq)?)?;
+            std::fs::rename(tmp, path)
+        }
+
+        /// Note an applied sequence (never moves backwards)
+        pub fn record(&mut self, symbol_hash: u64, seq_id: u64) {
+            let last = self.last_seq.entry(symbol_hash).or_insert(0);
+            *last = (*last).max(seq_id);
+        }
+
+        pub fn record_book(&mut self, book: &L2Orderbook) {
+            self.record(book.symbol_hash, book.last_seq_id.load(Ordering::Relaxed));
+        }
+
+        pub fn last_seq(&self, symbol_hash: u64) -> Option<u64> {
+            self.last_seq.get(&symbol_hash).copied()
+        }
+
+        /// Resume if the venue supports it (`venue_seq` = its latest seq)
+        /// and the gap is small; otherwise fall back to a full snapshot
+        pub fn plan(&self, symbol_hash: u64, venue_seq: Option<u64>) -> ResumePlan {
+            match (self.last_seq(symbol_hash), venue_seq) {
+                (Some(last), Some(venue))
+                    if last > 0 && venue >= last && venue - last <= self.max_resume_gap =>
+                {
+                    ResumePlan::Resume { from_seq: last + 1 }
+                }
+                _ => ResumePlan::Snapshot,
+            }
+        }
+    }
+
+    /// Separates clock skew from latency in `local_ts - exchange_ts`
+    ///
+    /// The raw delay is `latency - skew` where skew is how far the exchange
+    /// clock runs ahead of ours. Over a window the fastest message has
+    /// near-zero latency, so the minimum raw delay estimates `-skew`, and
+    /// `raw - min` is the skew-corrected latency.
+    pub struct ClockSkewEstimator {
+        window: usize,
+        seen: u64,
+        minima: VecDeque<(u64, i64)>,   // Monotonic deque of (sample idx, raw delay)
+    }
+
+    impl ClockSkewEstimator {
+        pub fn new(window: usize) -> Self {
+            Self {
+                window: window.max(1),
+                seen: 0,
+                minima: VecDeque::new(),
+            }
+        }
+
+        /// Record a message - returns its skew-corrected latency in ns
+        #[inline(always)]
+        pub fn observe(&mut self, exchange_ts_ns: i64, local_ts_ns: i64) -> i64 {
+            let raw = local_ts_ns - exchange_ts_ns;
+            let idx = self.seen;
+            self.seen += 1;
+
+            while matches!(self.minima.back(), Some(&(_, v)) if v >= raw) {
+                self.minima.pop_back();
+            }
+            self.minima.push_back((idx, raw));
+            while matches!(self.minima.front(), Some(&(i, _)) if i + (self.window as u64) <= idx) {
+                self.minima.pop_front();
+            }
+
+            raw - self.min_delay_ns().unwrap_or(raw)
+        }
+
+        /// Minimum raw delay over the window
+        #[inline(always)]
+        pub fn min_delay_ns(&self) -> Option<i64> {
+            self.minima.front().map(|&(_, v)| v)
+        }
+
+        /// Estimated exchange-minus-local clock offset in ns
+        #[inline(always)]
+        pub fn skew_ns(&self) -> Option<i64> {
+            self.min_delay_ns().map(|d| -d)
+        }
+
+        /// Latency with the estimated skew removed
+        #[inline(always)]
+        pub fn corrected_latency_ns(&self, exchange_ts_ns: i64, local_ts_ns: i64) -> i64 {
+            let raw = local_ts_ns - exchange_ts_ns;
+            raw - self.min_delay_ns().unwrap_or(raw)
+        }
+    }
+
+    /// Per-symbol effective spread running sums
+    #[derive(Default)]
+    struct SpreadSamples {
+        recent: VecDeque<(f64, f64)>,   // (absolute, bps of mid), windowed mode only
+        count: u64,
+        sum: f64,
+        sum_bps: f64,
+    }
+
+    /// Taker transaction cost: effective spread `2 * |price - mid|` of each
+    /// aggressive trade against the mid prevailing when it printed
+    pub struct EffectiveSpreadTracker {
+        window: usize,          // Trades averaged per symbol, 0 = all
+        symbols: HashMap<u64, SpreadSamples>,
+        pub missing_mid: u64,   // Trades skipped for lack of a two-sided book
+    }
+
+    impl EffectiveSpreadTracker {
+        pub fn new(window: usize) -> Self {
+            Self { window, symbols: HashMap::new(), missing_mid: 0 }
+        }
+
+        /// Record a trade against the mid at print time - O(1)
+        /// Returns its effective spread; None (counted) without a mid
+        pub fn record(&mut self, trade: &TradeEvent, mid: Option<f64>) -> Option<f64> {
+            let Some(mid) = mid.filter(|&m| m > 0.0) else {
+                self.missing_mid += 1;
+                return None;
+            };
+            let spread = 2.0 * (trade.price.0 - mid).abs();
+            let bps = spread / mid * 10_000.0;
+
+            let samples = self.symbols.entry(trade.symbol_hash).or_default();
+            samples.count += 1;
+            samples.sum += spread;
+            samples.sum_bps += bps;
+            if self.window > 0 {
+                samples.recent.push_back((spread, bps));
+                if samples.recent.len() > self.window {
+                    if let Some((old, old_bps)) = samples.recent.pop_front() {
+                        samples.count -= 1;
+                        samples.sum -= old;
+                        samples.sum_bps -= old_bps;
+                    }
+                }
+            }
+            Some(spread)
+        }
+
+        /// Mean effective spread in price units
+        pub fn average(&self, symbol_hash: u64) -> Option<f64> {
+            let samples = self.symbols.get(&symbol_hash)?;
+            Some(samples.sum / samples.count as f64)
+        }
+
+        /// Mean effective spread in bps of mid
+        pub fn average_bps(&self, symbol_hash: u64) -> Option<f64> {
+            let samples = self.symbols.get(&symbol_hash)?;
+            Some(samples.sum_bps / samples.count as f64)
+        }
+    }
+}
+
+pub use feed::*;
+
+#[cfg(test)]
+mod tests {
+    use super::feed::*;
+    use crate::execution::Side;
+    use crate::rng::PipelineRng;
+    use crate::orderbook::{
+        symbol_hash, DeltaOutcome, L2Orderbook, OrderbookManager, OrderbookSnapshot, Price, Qty, TradeActivity,
+    };
+    use crossbeam_channel::unbounded;
+    use std::collections::VecDeque;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    /// Scripted transport: connect results and frames are popped in order
+    struct MockTransport {
+        connects: VecDeque<bool>,
+        frames: VecDeque<Result<Option<Vec<u8>>, &'static str>>,
+        attempts: u32,
+        done: Arc<AtomicBool>,
+        resume_after: Option<(usize, Arc<IngestionControl>)>,   // Resume once N frames are left
+        reads: u32,
+    }
+
+    impl FeedTransport for MockTransport {
+        fn connect(&mut self) -> Result<(), &'static str> {
+            self.attempts += 1;
+            match self.connects.pop_front() {
+                Some(true) => Ok(()),
+                _ => Err("CONNECT_FAILED"),
+            }
+        }
+
+        fn recv(&mut self) -> Result<Option<Vec<u8>>, &'static str> {
+            self.reads += 1;
+            if let Some((left, control)) = &self.resume_after {
+                if self.frames.len() == *left {
+                    control.resume();
+                }
+            }
+            self.frames.pop_front().unwrap_or_else(|| {
+                self.done.store(true, Ordering::Release);
+                Ok(None)
+            })
+        }
+    }
+
+    fn config(max_reconnects: u32) -> FeedConfig {
+        FeedConfig {
+            max_reconnects,
+            reconnect_backoff: Duration::ZERO,
+            max_frame_bytes: 0,
+        }
+    }
+
+    #[test]
+    fn test_feed_shuts_pipeline_down_after_max_reconnects() {
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let transport = MockTransport {
+            connects: VecDeque::new(),
+            frames: VecDeque::new(),
+            attempts: 0,
+            done: shutdown.clone(),
+            resume_after: None,
+            reads: 0,
+        };
+        let mut runner = FeedRunner::new(transport, config(3), shutdown.clone());
+        let (tx, _rx) = unbounded();
+
+        assert_eq!(runner.run(&tx), Err("MAX_RECONNECTS_EXCEEDED"));
+        assert!(shutdown.load(Ordering::Acquire));
+        assert_eq!(runner.transport().attempts, 3);
+    }
+
+    #[test]
+    fn test_same_seed_gives_same_reconnect_backoffs() {
+        let backoffs = |seed: u64| {
+            let shutdown = Arc::new(AtomicBool::new(false));
+            let transport = MockTransport {
+                connects: VecDeque::new(),
+                frames: VecDeque::new(),
+                attempts: 0,
+                done: shutdown.clone(),
+                resume_after: None,
+                reads: 0,
+            };
+            let sleeps = Arc::new(std::sync::Mutex::new(Vec::new()));
+            let clock = MockClock { now_ns: 0, sleeps: sleeps.clone() };
+            let config = FeedConfig { reconnect_backoff: Duration::from_secs(1), ..config(6) };
+            let mut runner = FeedRunner::new(transport, config, shutdown)
+                .with_rng(&PipelineRng::new(seed))
+                .with_clock(Box::new(clock));
+            let (tx, _rx) = unbounded();
+            assert_eq!(runner.run(&tx), Err("MAX_RECONNECTS_EXCEEDED"));
+            let sleeps = sleeps.lock().unwrap().clone();
+            sleeps
+        };
+
+        let first = backoffs(42);
+        assert_eq!(first.len(), 5);     // No sleep after the fatal attempt
+        assert!(first.iter().all(|d| (Duration::from_millis(500)..Duration::from_millis(1500)).contains(d)));
+        assert!(first.windows(2).any(|w| w[0] != w[1]));
+        assert_eq!(backoffs(42), first);
+        assert_ne!(backoffs(43), first);
+    }
+
+    #[test]
+    fn test_successful_connect_resets_failure_count() {
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let transport = MockTransport {
+            // fail, fail, ok, (drop), fail, fail, ok
+            connects: VecDeque::from(vec![false, false, true, false, false, true]),
+            frames: VecDeque::from(vec![Ok(Some(b"a".to_vec())), Err("RESET"), Ok(Some(b"b".to_vec()))]),
+            attempts: 0,
+            done: shutdown.clone(),
+            resume_after: None,
+            reads: 0,
+        };
+        let mut runner = FeedRunner::new(transport, config(3), shutdown.clone());
+        let (tx, rx) = unbounded();
+
+        assert_eq!(runner.run(&tx), Ok(()));
+        assert_eq!(runner.consecutive_failures(), 0);
+        assert_eq!(runner.reconnects.load(Ordering::Relaxed), 1);
+        assert_eq!(runner.health().reconnects(), 1);
+        assert_eq!(rx.try_iter().collect::<Vec<_>>(), vec![b"a".to_vec(), b"b".to_vec()]);
+    }
+
+    #[test]
+    fn test_clock_skew_estimator_recovers_constant_skew() {
+        const SKEW: i64 = 5_000_000; // Exchange clock 5ms ahead
+        let latencies = [250_000, 0, 1_200_000, 80_000, 3_000_000, 40_000];
+        let mut estimator = ClockSkewEstimator::new(64);
+
+        for (i, &latency) in latencies.iter().cycle().take(120).enumerate() {
+            let local_send = i as i64 * 1_000_000;
+            let exchange_ts = local_send + SKEW;
+            let local_ts = local_send + latency;
+            estimator.observe(exchange_ts, local_ts);
+        }
+
+        assert_eq!(estimator.skew_ns(), Some(SKEW));
+        // 2ms of genuine latency is reported as such, not as -3ms
+        assert_eq!(estimator.corrected_latency_ns(SKEW, 2_000_000), 2_000_000);
+    }
+
+    #[test]
+    fn test_clock_skew_window_forgets_old_minimum() {
+        let mut estimator = ClockSkewEstimator::new(3);
+        estimator.observe(0, -10);
+        for _ in 0..3 {
+            estimator.observe(0, 5);
+        }
+        assert_eq!(estimator.min_delay_ns(), Some(5));
+    }
+
+    #[test]
+    fn test_paused_feed_produces_nothing_until_resumed() {
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let frames = (0..6u8).map(|i| Ok(Some(vec![i]))).collect();
+        let transport = MockTransport {
+            connects: VecDeque::from(vec![true]),
+            frames,
+            attempts: 0,
+            done: shutdown.clone(),
+            resume_after: None,
+            reads: 0,
+        };
+        let mut runner = FeedRunner::new(transport, config(3), shutdown.clone());
+        let control = runner.control();
+        control.pause();
+        runner.transport_mut().resume_after = Some((2, control.clone()));
+        let (tx, rx) = unbounded();
+
+        assert_eq!(runner.run(&tx), Ok(()));
+
+        // First 4 frames read (connection alive) but discarded; last 2 pass
+        assert!(!control.is_paused());
+        assert_eq!(runner.transport().attempts, 1);
+        assert!(runner.transport().reads >= 6);
+        assert_eq!(runner.frames_discarded.load(Ordering::Relaxed), 4);
+        assert_eq!(rx.try_iter().collect::<Vec<_>>(), vec![vec![4u8], vec![5u8]]);
+    }
+
+    #[test]
+    fn test_parse_binance_trade_message() {
+        let frame = br#"{"stream":"btcusdt@trade","data":{"e":"trade","E":1700000000123,"s":"BTCUSDT","t":3281974155,"p":"37012.50000000","q":"0.01420000","b":22187645731,"a":22187645712,"T":1700000000121,"m":true,"M":true}}"#;
+
+        let trade = match parse_frame(frame).unwrap() {
+            FeedEvent::Trade(trade) => trade,
+            other => panic!("expected trade, got {:?}", other),
+        };
+        assert_eq!(trade.symbol_hash, symbol_hash("BTCUSDT"));
+        assert_eq!(trade.trade_id, 3281974155);
+        assert_eq!(trade.price, Price(37012.5));
+        assert_eq!(trade.qty, Qty(0.0142));
+        assert_eq!(trade.aggressor, Side::Sell);
+        assert_eq!(trade.ts_ns, 1_700_000_000_121_000_000);
+
+        // Seller aggressor hits the bid
+        let mut activity = TradeActivity::new(1_000_000_000, 16);
+        trade.record_into(&mut activity);
+        assert_eq!(activity.trade_count_at(37012.5, true), 1.0);
+        assert_eq!(activity.trade_count_at(37012.5, false), 0.0);
+
+        let kline = br#"{"e":"kline","s":"BTCUSDT"}"#;
+        assert_eq!(parse_frame(kline), Ok(FeedEvent::Raw(kline.to_vec())));
+        assert_eq!(parse_frame(br#"{"e":"trade","s":"BTCUSDT"}"#), Err("MALFORMED_TRADE"));
+        assert_eq!(parse_frame(b"not json"), Err("MALFORMED_FRAME"));
+    }
+
+    #[test]
+    fn test_oversize_frames_rejected_before_parsing() {
+        // Valid JSON, just too big - must not reach the parser
+        let mut huge = br#"{"e":"trade","pad":""#.to_vec();
+        huge.resize(DEFAULT_MAX_FRAME_BYTES + 1, b'x');
+        assert_eq!(parse_frame(&huge), Err("OVERSIZE_FRAME"));
+        assert_eq!(parse_frame_limited(br#"{"e":"depthUpdate"}"#, 8), Err("OVERSIZE_FRAME"));
+        assert!(parse_frame_limited(br#"{"e":"kline"}"#, 0).is_ok());
+
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let transport = MockTransport {
+            connects: VecDeque::from(vec![true]),
+            frames: VecDeque::from(vec![Ok(Some(vec![0u8; 64])), Ok(Some(vec![1u8; 16]))]),
+            attempts: 0,
+            done: shutdown.clone(),
+            resume_after: None,
+            reads: 0,
+        };
+        let config = FeedConfig { max_frame_bytes: 32, ..config(3) };
+        let mut runner = FeedRunner::new(transport, config, shutdown);
+        let (tx, rx) = unbounded();
+
+        assert_eq!(runner.run(&tx), Ok(()));
+        assert_eq!(runner.oversize_frames.load(Ordering::Relaxed), 1);
+        assert_eq!(rx.try_iter().collect::<Vec<_>>(), vec![vec![1u8; 16]]);
+    }
+
+    #[test]
+    fn test_cold_start_replays_then_hands_off_to_live_without_rewarm() {
+        let trade = |id: u64| {
+            format!(
+                r#"{{"e":"trade","s":"BTCUSDT","t":{},"p":"100.0","q":"1.0","T":{},"m":false}}"#,
+                id, id
+            )
+        };
+        let path = std::env::temp_dir().join(format!("cold_start_{}.jsonl", std::process::id()));
+        let recording: String = (1..=3).map(|id| trade(id) + "\n").collect();
+        std::fs::write(&path, recording + "\n").unwrap();
+
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let live = MockTransport {
+            connects: VecDeque::from(vec![true]),
+            frames: (4..=5).map(|id| Ok(Some(trade(id).into_bytes()))).collect(),
+            attempts: 0,
+            done: shutdown.clone(),
+            resume_after: None,
+            reads: 0,
+        };
+        let replay = FeedReplayer::from_path(&path).unwrap();
+        let transport = ColdStartTransport::new(replay, live);
+        let resync = transport.resync_signal();
+        let mut runner = FeedRunner::new(transport, config(3), shutdown);
+        let (tx, rx) = unbounded();
+
+        assert_eq!(runner.run(&tx), Ok(()));
+        std::fs::remove_file(&path).unwrap();
+
+        // One consumer across the seam: the indicator keeps warming rather
+        // than restarting, and no trade is lost or repeated
+        let mut activity = TradeActivity::new(i64::MAX, 16);
+        let mut ids = Vec::new();
+        for frame in rx.try_iter() {
+            if let Ok(FeedEvent::Trade(trade)) = parse_frame(&frame) {
+                trade.record_into(&mut activity);
+                ids.push(trade.trade_id);
+            }
+        }
+        assert_eq!(ids, vec![1, 2, 3, 4, 5]);
+        assert!((activity.trade_count_at(100.0, false) - 5.0).abs() < 1e-6);
+
+        let transport = runner.transport();
+        assert!(transport.is_live());
+        assert!(transport.replay().is_exhausted());
+        assert_eq!(transport.replay().frames_replayed, 3);
+        assert_eq!(transport.live().attempts, 1);
+        assert!(resync.load(Ordering::Acquire));
+        assert_eq!(runner.reconnects.load(Ordering::Relaxed), 0);
+    }
+
+    /// Mock clock: sleeping advances time and is recorded
+    struct MockClock {
+        now_ns: i64,
+        sleeps: Arc<std::sync::Mutex<Vec<Duration>>>,
+    }
+
+    impl ReplayClock for MockClock {
+        fn now_ns(&self) -> i64 {
+            self.now_ns
+        }
+
+        fn sleep(&mut self, duration: Duration) {
+            self.now_ns += duration.as_nanos() as i64;
+            self.sleeps.lock().unwrap().push(duration);
+        }
+    }
+
+    #[test]
+    fn test_replay_speed_scales_inter_event_sleeps() {
+        // Events 100ms then 300ms apart; the depth frame has no event time
+        let recording = concat!(
+            r#"{"e":"trade","E":1000}"#, "\n",
+            r#"{"e":"trade","E":1100}"#, "\n",
+            r#"{"lastUpdateId":1}"#, "\n",
+            r#"{"data":{"e":"trade","E":1400}}"#, "\n",
+        );
+        let sleeps_at = |speed: f64| {
+            let sleeps = Arc::new(std::sync::Mutex::new(Vec::new()));
+            let clock = MockClock { now_ns: 0, sleeps: sleeps.clone() };
+            let mut replayer = FeedReplayer::new(recording.as_bytes())
+                .with_speed(speed)
+                .with_clock(Box::new(clock));
+            while replayer.next_frame().is_some() {}
+            assert_eq!(replayer.frames_replayed, 4);
+            let sleeps = sleeps.lock().unwrap().clone();
+            sleeps
+        };
+
+        let ms = Duration::from_millis;
+        assert_eq!(sleeps_at(1.0), vec![ms(100), ms(300)]);
+        assert_eq!(sleeps_at(2.0), vec![ms(50), ms(150)]);
+        assert_eq!(sleeps_at(0.0), vec![]);
+    }
+
+    #[test]
+    fn test_small_gap_reconnect_resumes_from_persisted_seq() {
+        let mut book = L2Orderbook::new(7);
+        for seq in 1..=100u64 {
+            book.apply_delta(Price(100.0 + (seq % 5) as f64), Qty(1.0), seq % 2 == 0, seq);
+        }
+        let mut store = SeqStore::new(50);
+        store.record_book(&book);
+        store.record(7, 42); // Stale report can't rewind
+
+        let path = std::env::temp_dir().join(format!("seq_store_{}.json", std::process::id()));
+        store.save(&path).unwrap();
+        let store = SeqStore::load(&path, 50).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(store.last_seq(7), Some(100));
+
+        // Venue moved on by 4 updates while we were away
+        let ResumePlan::Resume { from_seq } = store.plan(7, Some(104)) else {
+            panic!("expected resume");
+        };
+        assert_eq!(from_seq, 101);
+        for seq in from_seq..=104 {
+            assert!(book.apply_delta(Price(101.0), Qty(2.0), true, seq).is_applied());
+        }
+        assert_eq!(book.gaps_detected.load(Ordering::Relaxed), 0);
+
+        // Too far behind, no resume support, venue reset, unknown symbol
+        assert_eq!(store.plan(7, Some(151)), ResumePlan::Snapshot);
+        assert_eq!(store.plan(7, None), ResumePlan::Snapshot);
+        assert_eq!(store.plan(7, Some(3)), ResumePlan::Snapshot);
+        assert_eq!(store.plan(8, Some(104)), ResumePlan::Snapshot);
+        assert!(SeqStore::load(std::env::temp_dir().join("no_such_seq_store.json"), 50).is_ok());
+    }
+
+    #[test]
+    fn test_book_ticker_tracks_bbo_without_depth() {
+        let frames: [&[u8]; 3] = [
+            br#"{"u":400900217,"s":"BNBUSDT","b":"25.35190000","B":"31.21000000","a":"25.36520000","A":"40.66000000"}"#,
+            br#"{"stream":"bnbusdt@bookTicker","data":{"u":400900219,"s":"BNBUSDT","b":"25.35200000","B":"5.00000000","a":"25.36000000","A":"15.00000000"}}"#,
+            br#"{"u":400900218,"s":"BNBUSDT","b":"25.00000000","B":"1.00000000","a":"26.00000000","A":"1.00000000"}"#,
+        ];
+        let mut manager = OrderbookManager::new();
+        for frame in frames {
+            match parse_frame(frame).unwrap() {
+                FeedEvent::BookTicker(ticker) => ticker.apply_to_manager(&mut manager),
+                other => panic!("expected book ticker, got {:?}", other),
+            };
+        }
+
+        let top = manager.top_of_book("BNBUSDT").unwrap();
+        assert_eq!(top.symbol_hash, symbol_hash("BNBUSDT"));
+        assert_eq!(top.bid, Some((25.352, 5.0)));
+        assert_eq!(top.ask, Some((25.36, 15.0)));
+        assert_eq!(top.last_update_id, 400900219);
+        assert_eq!(top.stale_updates, 1);
+        assert!(manager.get("BNBUSDT").is_none());
+        assert_eq!(manager.stats().books, 0);
+
+        let futures = br#"{"e":"bookTicker","u":1,"s":"BTCUSDT","b":"1","B":"1","a":"2","A":"1","T":0,"E":0}"#;
+        assert!(matches!(parse_frame(futures), Ok(FeedEvent::BookTicker(_))));
+        assert_eq!(parse_frame(br#"{"u":1,"s":"BTCUSDT","B":"x","A":"1"}"#), Err("MALFORMED_BOOK_TICKER"));
+    }
+
+    #[test]
+    fn test_trade_at_ask_pays_full_quoted_spread() {
+        let mut book = L2Orderbook::new(symbol_hash("BTCUSDT"));
+        book.apply_delta(Price(100.0), Qty(1.0), true, 1);
+        book.apply_delta(Price(101.0), Qty(1.0), false, 2);
+        let trade = TradeEvent {
+            symbol_hash: book.symbol_hash,
+            trade_id: 1,
+            price: Price(101.0),
+            qty: Qty(0.5),
+            aggressor: Side::Buy,
+            ts_ns: 0,
+        };
+
+        let mut tracker = EffectiveSpreadTracker::new(2);
+        let half_spread = (101.0 - 100.0) / 2.0;
+        assert_eq!(tracker.record(&trade, book.mid_price()), Some(2.0 * half_spread));
+        assert_eq!(tracker.average(book.symbol_hash), Some(1.0));
+        assert!((tracker.average_bps(book.symbol_hash).unwrap() - 1.0 / 100.5 * 10_000.0).abs() < 1e-9);
+
+        // Inside-spread print, then the window drops the first trade
+        tracker.record(&TradeEvent { price: Price(100.75), ..trade }, book.mid_price());
+        tracker.record(&TradeEvent { price: Price(100.25), aggressor: Side::Sell, ..trade }, book.mid_price());
+        assert_eq!(tracker.average(book.symbol_hash), Some(0.5));
+
+        // No mid: skipped and counted, averages untouched
+        assert_eq!(tracker.record(&trade, None), None);
+        assert_eq!(tracker.missing_mid, 1);
+        assert_eq!(tracker.average(symbol_hash("ETHUSDT")), None);
+    }
+
+    fn depth(first: u64, last: u64, bid: (f64, f64)) -> DepthUpdateEvent {
+        DepthUpdateEvent {
+            symbol_hash: symbol_hash("BNBBTC"),
+            first_update_id: first,
+            final_update_id: last,
+            prev_final_update_id: None,
+            bids: vec![bid],
+            asks: vec![(0.0030, 1.0)],
+            event_ts_ns: 0,
+        }
+    }
+
+    #[test]
+    fn test_depth_sync_replays_buffered_updates_onto_snapshot() {
+        let frame = br#"{"e":"depthUpdate","E":1700000000123,"s":"BNBBTC","U":157,"u":160,"b":[["0.0024","10"]],"a":[["0.0026","100"],["0.0027","0"]]}"#;
+        let Ok(FeedEvent::Depth(update)) = parse_frame(frame) else { panic!("expected depth") };
+        assert_eq!((update.first_update_id, update.final_update_id, update.expected_prev()), (157, 160, 156));
+        assert_eq!(update.asks, vec![(0.0026, 100.0), (0.0027, 0.0)]);
+        assert_eq!(update.event_ts_ns, 1_700_000_000_123_000_000);
+        assert_eq!(parse_frame(br#"{"e":"depthUpdate","s":"X","U":1,"u":2,"b":[["x","1"]],"a":[]}"#), Err("MALFORMED_DEPTH"));
+
+        let mut book = L2Orderbook::new(symbol_hash("BNBBTC"));
+        let mut sync = DepthSync::new(16);
+        for (first, last, price) in [(140, 150, 0.0010), (151, 158, 0.0011), (159, 163, 0.0012), (164, 170, 0.0013)] {
+            assert_eq!(sync.on_update(depth(first, last, (price, 1.0)), &mut book), DepthSyncOutcome::Buffered);
+        }
+
+        // lastUpdateId 160: 140..150 is covered, 151..158 too, 159..163 straddles 161
+        let body = br#"{"lastUpdateId":160,"bids":[["0.0020","5"]],"asks":[["0.0030","5"]]}"#;
+        let snapshot = parse_depth_snapshot("BNBBTC", body).unwrap();
+        assert_eq!(sync.on_snapshot(&snapshot, &mut book), Ok(2));
+        assert!(sync.is_synced());
+        assert_eq!(sync.stale_dropped, 2);
+        assert_eq!(book.best_bid(), Some(0.0020));
+        assert_eq!(book.last_seq_id.load(Ordering::Relaxed), 170);
+        assert_eq!(book.bids.len(), 3);
+
+        // Live: chained update applies, a skipped range forces a resync
+        assert_eq!(sync.on_update(depth(171, 172, (0.0014, 1.0)), &mut book), DepthSyncOutcome::Applied(DeltaOutcome::Applied));
+        assert_eq!(sync.on_update(depth(170, 171, (0.0015, 1.0)), &mut book), DepthSyncOutcome::Stale);
+        assert_eq!(sync.on_update(depth(180, 181, (0.0016, 1.0)), &mut book), DepthSyncOutcome::Gap);
+        assert!(!sync.is_synced() && book.has_open_gap());
+        assert_eq!(sync.on_update(depth(182, 183, (0.0017, 1.0)), &mut book), DepthSyncOutcome::Buffered);
+
+        // A snapshot older than the buffered updates can't be joined
+        let stale = OrderbookSnapshot { seq_id: 175, ..snapshot.clone() };
+        assert_eq!(sync.on_snapshot(&stale, &mut book), Err("SNAPSHOT_TOO_OLD"));
+        let fresh = OrderbookSnapshot { seq_id: 180, ..snapshot };
+        assert_eq!(sync.on_snapshot(&fresh, &mut book), Ok(2));
+        assert!(!book.has_open_gap());
+    }
+
+    #[test]
+    fn test_binance_stream_url_combines_depth_and_ticker() {
+        assert_eq!(
+            binance_stream_url(BINANCE_WS_URL, &["BTCUSDT", "ethusdt"]),
+            "wss://stream.binance.com:9443/stream?streams=btcusdt@depth@100ms/btcusdt@bookTicker/ethusdt@depth@100ms/ethusdt@bookTicker"
+        );
+        let mut ws = WebSocketTransport::new("ws://127.0.0.1:1", Duration::from_millis(10));
+        assert_eq!(ws.recv(), Err("WS_NOT_CONNECTED"));
+        assert_eq!(ws.connect(), Err("WS_CONNECT_FAILED"));
+
+        // Local server: one frame, an idle period, then close
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("ws://{}/stream", listener.local_addr().unwrap());
+        let server = std::thread::spawn(move || {
+            let mut socket = tungstenite::accept(listener.accept().unwrap().0).unwrap();
+            socket.send(tungstenite::Message::Text(r#"{"e":"kline"}"#.into())).unwrap();
+            std::thread::sleep(Duration::from_millis(50));
+            let _ = socket.close(None);
+            let _ = socket.flush();
+        });
+        let mut ws = WebSocketTransport::new(url, Duration::from_millis(10));
+        ws.connect().unwrap();
+        assert_eq!(ws.recv(), Ok(Some(br#"{"e":"kline"}"#.to_vec())));
+        assert_eq!(ws.recv(), Ok(None));
+        let closed = loop {
+            match ws.recv() {
+                Ok(None) => continue,
+                other => break other,
+            }
+        };
+        assert_eq!(closed, Err("WS_CLOSED"));
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn test_websocket_rejects_messages_over_max_frame_bytes() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("ws://{}/stream", listener.local_addr().unwrap());
+        let server = std::thread::spawn(move || {
+            let mut socket = tungstenite::accept(listener.accept().unwrap().0).unwrap();
+            socket.send(tungstenite::Message::Binary(vec![b'x'; 32])).unwrap();
+            socket.send(tungstenite::Message::Binary(vec![b'x'; 64])).unwrap();
+            std::thread::sleep(Duration::from_millis(50));
+        });
+        let mut ws = WebSocketTransport::new(url, Duration::from_millis(10)).with_max_frame_bytes(32);
+        ws.connect().unwrap();
+        assert_eq!(ws.recv(), Ok(Some(vec![b'x'; 32])));
+        let oversized = loop {
+            match ws.recv() {
+                Ok(None) => continue,
+                other => break other,
+            }
+        };
+        assert_eq!(oversized, Err("WS_FRAME_TOO_LARGE"));
+        assert_eq!(ws.recv(), Err("WS_NOT_CONNECTED"));
+        server.join().unwrap();
+    }
+
+    /// Connects every time; after one frame the feed goes quiet until the
+    /// heartbeat forces a reconnect
+    struct StallingTransport {
+        health: Option<Arc<FeedHealth>>,
+        shutdown: Arc<AtomicBool>,
+        connects: u32,
+        reads: u32,
+    }
+
+    impl FeedTransport for StallingTransport {
+        fn connect(&mut self) -> Result<(), &'static str> {
+            self.connects += 1;
+            self.reads = 0;
+            Ok(())
+        }
+
+        fn recv(&mut self) -> Result<Option<Vec<u8>>, &'static str> {
+            self.reads += 1;
+            match (self.connects, self.reads) {
+                (_, 1) => Ok(Some(vec![self.connects as u8])),
+                (1, _) => {
+                    // Heartbeat fires as if 10s of silence had passed
+                    let health = self.health.as_ref().unwrap();
+                    health.check(health.last_message_ns() + 10_000_000_000, 5_000_000_000);
+                    Ok(None)
+                }
+                _ => {
+                    self.shutdown.store(true, Ordering::Release);
+                    Ok(None)
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_stalled_feed_reconnects_and_requests_resync() {
+        let health = FeedHealth::default();
+        assert!(!health.check(1_000, 10), "never-connected feed is not stalled");
+        health.touch(1_000);
+        assert!(!health.check(1_010, 10));
+        assert!(health.check(1_011, 10));
+        assert!(!health.check(1_012, 10), "one request per stall");
+
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let transport = StallingTransport { health: None, shutdown: shutdown.clone(), connects: 0, reads: 0 };
+        let mut runner = FeedRunner::new(transport, config(3), shutdown.clone());
+        runner.transport_mut().health = Some(runner.health());
+        let (tx, rx) = unbounded();
+
+        assert_eq!(runner.run(&tx), Ok(()));
+        let health = runner.health();
+        assert_eq!(runner.transport().connects, 2);
+        assert_eq!(runner.reconnects.load(Ordering::Relaxed), 1);
+        assert_eq!(health.stall_reconnects.load(Ordering::Relaxed), 1);
+        assert_eq!(health.reconnects(), 1);
+        assert_eq!(rx.try_iter().collect::<Vec<_>>(), vec![vec![1], vec![2]]);
+        assert!(health.take_resync());
+        assert!(!health.take_resync());
+    }
+}
@@ -0,0 +1,1085 @@
+// Indicators module — Streaming Technical Indicators
+//
+// Features:
+// - OHLC candle input shared by all indicators
+// - OHLCV bars aggregated from trade prints, completed bars sent over a
+//   channel: fixed-interval time bars (gaps skipped or filled flat), or
+//   volume/dollar bars that split overshooting trades across bars
+// - Welles-Wilder Average True Range (O(1) update)
+// - Gann retracement levels (eighths and thirds of a swing)
+// - Gann square-of-nine support/resistance levels
+// - Gann angle fan lines anchored at a pivot bar
+// - Ehlers Super Smoother (2-pole Butterworth, O(1) update)
+// - Ehlers Fisher Transform over a rolling min/max window (O(period) update)
+// - Ehlers MESA Adaptive Moving Average (MAMA/FAMA) via the Hilbert
+//   transform homodyne discriminator (O(1) update)
+// - Ehlers dominant cycle period and Instantaneous Trendline
+// - Trailing stop combining ATR distance with Gann levels
+// - `ehlers` and `gann` sub-paths grouping the indicators by method
+
+pub mod indicators {
+    use crate::feed::TradeEvent;
+    use crossbeam_channel::Sender;
+    use std::collections::VecDeque;
+
+    /// OHLC bar
+    #[derive(Clone, Copy, Debug, Default, PartialEq)]
+    pub struct Candle {
+        pub open: f64,
+        pub high: f64,
+        pub low: f64,
+        pub close: f64,
+        pub ts_ns: i64,
+    }
+
+    impl Candle {
+        /// Wilder's true range against the previous close
+        #[inline(always)]
+        pub fn true_range(&self, prev_close: Option<f64>) -> f64 {
+            let range = self.high - self.low;
+            match prev_close {
+                Some(pc) => range.max((self.high - pc).abs()).max((self.low - pc).abs()),
+                None => range,
+            }
+        }
+    }
+
+    /// Completed OHLCV bar covering [start_ns, end_ns)
+    #[derive(Clone, Copy, Debug, Default, PartialEq)]
+    pub struct Bar {
+        pub open: f64,
+        pub high: f64,
+        pub low: f64,
+        pub close: f64,
+        pub volume: f64,
+        pub start_ns: i64,
+        pub end_ns: i64,
+    }
+
+    impl Bar {
+        /// Zero-volume bar at the previous close, for empty intervals
+        fn flat(close: f64, start_ns: i64, end_ns: i64) -> Self {
+            Self { open: close, high: close, low: close, close, volume: 0.0, start_ns, end_ns }
+        }
+
+        /// Indicator input, stamped with the bar's close time
+        pub fn candle(&self) -> Candle {
+            Candle { open: self.open, high: self.high, low: self.low, close: self.close, ts_ns: self.end_ns }
+        }
+    }
+
+    /// What to emit for time-bar intervals without trades
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub enum GapFill {
+        Skip,   // No bar
+        Flat,   // Zero-volume bar at the previous close
+    }
+
+    /// When a bar closes
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub enum BarMode {
+        Time { interval_ns: i64 },  // Aligned to multiples of the interval
+        Volume(f64),                // Cumulative qty reaches the threshold
+        Dollar(f64),                // Cumulative price * qty reaches the threshold
+    }
+
+    /// Relative slack so float accumulation can't leave a bar a hair short
+    const BAR_FILL_TOLERANCE: f64 = 1e-12;
+
+    /// Bar builder for one symbol's trade prints
+    ///
+    /// Time bars are aligned to multiples of the interval; a trade exactly
+    /// on a boundary opens the next bar. Volume and dollar bars split a
+    /// trade that overshoots the threshold: the fill closes the current
+    /// bar and the remainder opens the next at the same price, so a given
+    /// trade sequence always yields the same bars. Completed bars go out
+    /// on `bar_tx`.
+    pub struct BarAggregator {
+        symbol_hash: u64,
+        mode: BarMode,
+        gap_fill: GapFill,
+        bar_tx: Sender<Bar>,
+        current: Option<Bar>,
+        filled: f64,                // Volume or dollars in the open bar
+        late_dropped: u64,
+    }
+
+    impl BarAggregator {
+        /// `gap_fill` only applies to time bars
+        pub fn new(symbol_hash: u64, mode: BarMode, gap_fill: GapFill, bar_tx: Sender<Bar>) -> Self {
+            let mode = match mode {
+                BarMode::Time { interval_ns } => BarMode::Time { interval_ns: interval_ns.max(1) },
+                BarMode::Volume(threshold) => BarMode::Volume(threshold.max(f64::MIN_POSITIVE)),
+                BarMode::Dollar(threshold) => BarMode::Dollar(threshold.max(f64::MIN_POSITIVE)),
+            };
+            Self {
+                symbol_hash,
+                mode,
+                gap_fill,
+                bar_tx,
+                current: None,
+                filled: 0.0,
+                late_dropped: 0,
+            }
+        }
+
+        /// Fold a trade into the open bar, emitting any bars it completes;
+        /// other symbols are ignored - O(1) plus one bar per threshold
+        /// crossed or (GapFill::Flat) interval skipped
+        pub fn on_trade(&mut self, trade: &TradeEvent) {
+            if trade.symbol_hash != self.symbol_hash {
+                return;
+            }
+            match self.mode {
+                BarMode::Time { interval_ns } => self.on_time_trade(trade, interval_ns),
+                BarMode::Volume(threshold) => self.on_threshold_trade(trade, threshold, 1.0),
+                BarMode::Dollar(threshold) => self.on_threshold_trade(trade, threshold, trade.price.0),
+            }
+        }
+
+        fn on_time_trade(&mut self, trade: &TradeEvent, interval_ns: i64) {
+            let start_ns = trade.ts_ns.div_euclid(interval_ns) * interval_ns;
+            match &self.current {
+                Some(bar) if start_ns < bar.start_ns => self.late_dropped += 1,
+                Some(bar) if start_ns == bar.start_ns => self.fold(trade.price.0, trade.qty.0),
+                _ => {
+                    self.close_until(start_ns);
+                    self.open(trade.price.0, trade.qty.0, start_ns, start_ns + interval_ns);
+                }
+            }
+        }
+
+        /// `unit_value` converts qty into threshold units (1 or price)
+        fn on_threshold_trade(&mut self, trade: &TradeEvent, threshold: f64, unit_value: f64) {
+            if unit_value <= 0.0 {
+                return;
+            }
+            let price = trade.price.0;
+            let mut remaining = trade.qty.0;
+            while remaining > 0.0 {
+                let room = (threshold - self.filled) / unit_value;
+                let take = remaining.min(room);
+                match self.current {
+                    Some(_) => self.fold(price, take),
+                    None => self.open(price, take, trade.ts_ns, trade.ts_ns),
+                }
+                self.filled += take * unit_value;
+                remaining -= take;
+
+                if self.filled >= threshold * (1.0 - BAR_FILL_TOLERANCE) {
+                    if let Some(mut bar) = self.current.take() {
+                        bar.end_ns = trade.ts_ns;
+                        let _ = self.bar_tx.send(bar);
+                    }
+                    self.filled = 0.0;
+                }
+                if remaining <= threshold * BAR_FILL_TOLERANCE / unit_value {
+                    break;
+                }
+            }
+        }
+
+        fn open(&mut self, price: f64, qty: f64, start_ns: i64, end_ns: i64) {
+            self.current = Some(Bar { open: price, high: price, low: price, close: price, volume: qty, start_ns, end_ns });
+        }
+
+        fn fold(&mut self, price: f64, qty: f64) {
+            if let Some(bar) = &mut self.current {
+                bar.high = bar.high.max(price);
+                bar.low = bar.low.min(price);
+                bar.close = price;
+                bar.volume += qty;
+            }
+        }
+
+        /// Close the open time bar once `now_ns` reaches its end, so quiet
+        /// markets still produce bars - returns bars emitted
+        /// Volume and dollar bars only close on trades
+        pub fn flush(&mut self, now_ns: i64) -> usize {
+            let BarMode::Time { interval_ns } = self.mode else {
+                return 0;
+            };
+            let bucket_ns = now_ns.div_euclid(interval_ns) * interval_ns;
+            match self.current {
+                Some(bar) if bucket_ns >= bar.end_ns => self.close_until(bucket_ns),
+                _ => 0,
+            }
+        }
+
+        /// Emit the open time bar and, under GapFill::Flat, flat bars up to
+        /// `next_start_ns`
+        fn close_until(&mut self, next_start_ns: i64) -> usize {
+            let Some(bar) = self.current.take() else {
+                return 0;
+            };
+            let _ = self.bar_tx.send(bar);
+            let mut emitted = 1;
+            if self.gap_fill == GapFill::Flat {
+                let interval_ns = bar.end_ns - bar.start_ns;
+                let mut start_ns = bar.end_ns;
+                while start_ns < next_start_ns {
+                    let _ = self.bar_tx.send(Bar::flat(bar.close, start_ns, start_ns + interval_ns));
+                    start_ns += interval_ns;
+                    emitted += 1;
+                }
+            }
+            emitted
+        }
+
+        /// Bar still accumulating
+        pub fn current(&self) -> Option<&Bar> {
+            self.current.as_ref()
+        }
+
+        /// Time-bar trades older than the open bar, which are dropped
+        pub fn late_dropped(&self) -> u64 {
+            self.late_dropped
+        }
+    }
+
+    /// Welles-Wilder Average True Range
+    ///
+    /// Seeded with the simple mean of the first `period` true ranges, then
+    /// smoothed as `atr = (atr * (n - 1) + tr) / n`.
+    #[derive(Clone, Debug)]
+    pub struct Atr {
+        period: usize,
+        prev_close: Option<f64>,
+        seed_sum: f64,
+        seen: usize,
+        value: Option<f64>,
+    }
+
+    impl Atr {
+        pub fn new(period: usize) -> Self {
+            Self {
+                period: period.max(1),
+                prev_close: None,
+                seed_sum: 0.0,
+                seen: 0,
+                value: None,
+            }
+        }
+
+        /// Feed a closed candle - None until `period` candles are seen - O(1)
+        pub fn update(&mut self, candle: &Candle) -> Option<f64> {
+            let tr = candle.true_range(self.prev_close);
+            self.prev_close = Some(candle.close);
+            let n = self.period as f64;
+
+            self.value = match self.value {
+                Some(atr) => Some((atr * (n - 1.0) + tr) / n),
+                None => {
+                    self.seed_sum += tr;
+                    self.seen += 1;
+                    (self.seen == self.period).then(|| self.seed_sum / n)
+                }
+            };
+            self.value
+        }
+
+        pub fn value(&self) -> Option<f64> {
+            self.value
+        }
+
+        pub fn period(&self) -> usize {
+            self.period
+        }
+    }
+
+    /// Ehlers Super Smoother - 2-pole Butterworth low-pass
+    ///
+    /// `filt = c1 * (x + x[1]) / 2 + c2 * filt[1] + c3 * filt[2]` with
+    /// `a1 = exp(-1.414π / period)`, `c2 = 2a1·cos(1.414π / period)`,
+    /// `c3 = -a1²`, `c1 = 1 - c2 - c3`. Averaging the last two inputs puts
+    /// a zero at Nyquist, so bar-to-bar flicker is removed entirely.
+    #[derive(Clone, Debug)]
+    pub struct SuperSmoother {
+        period: f64,
+        c1: f64,
+        c2: f64,
+        c3: f64,
+        prev_price: Option<f64>,
+        filt: [f64; 2],         // [filt[1], filt[2]]
+        seen: usize,
+    }
+
+    impl SuperSmoother {
+        pub fn new(period: f64) -> Self {
+            let period = period.max(2.0);
+            let arg = 1.414 * std::f64::consts::PI / period;
+            let a1 = (-arg).exp();
+            let c2 = 2.0 * a1 * arg.cos();
+            let c3 = -a1 * a1;
+            Self {
+                period,
+                c1: 1.0 - c2 - c3,
+                c2,
+                c3,
+                prev_price: None,
+                filt: [0.0; 2],
+                seen: 0,
+            }
+        }
+
+        /// Feed the next price, returns the filtered value - O(1)
+        /// The first two outputs pass the input through to seed the recursion
+        pub fn next(&mut self, price: f64) -> f64 {
+            let out = if self.seen < 2 {
+                price
+            } else {
+                let prev = self.prev_price.unwrap_or(price);
+                self.c1 * (price + prev) / 2.0 + self.c2 * self.filt[0] + self.c3 * self.filt[1]
+            };
+            self.seen += 1;
+            self.prev_price = Some(price);
+            self.filt = [out, self.filt[0]];
+            out
+        }
+
+        /// Last output; None before the first price
+        pub fn value(&self) -> Option<f64> {
+            (self.seen > 0).then_some(self.filt[0])
+        }
+
+        pub fn period(&self) -> f64 {
+            self.period
+        }
+    }
+
+    /// Ehlers Fisher Transform
+    ///
+    /// Price is scaled into [-1, 1] against the min/max of the last
+    /// `period` prices, smoothed (`0.33 * x + 0.67 * x[1]`), clamped to
+    /// ±0.999 and transformed with `0.5 * ln((1 + x) / (1 - x))`, itself
+    /// smoothed by half the previous output. The Gaussian-like output
+    /// turns sharply near price extremes.
+    #[derive(Clone, Debug)]
+    pub struct FisherTransform {
+        period: usize,
+        window: VecDeque<f64>,
+        normalized: f64,
+        fisher: f64,
+        trigger: f64,       // Previous output
+    }
+
+    /// Normalized input bound keeping the ln finite
+    const FISHER_CLAMP: f64 = 0.999;
+
+    impl FisherTransform {
+        pub fn new(period: usize) -> Self {
+            let period = period.max(2);
+            Self {
+                period,
+                window: VecDeque::with_capacity(period),
+                normalized: 0.0,
+                fisher: 0.0,
+                trigger: 0.0,
+            }
+        }
+
+        /// Feed the next price, returns the Fisher value - O(period)
+        /// A flat window normalizes to the middle of the range
+        pub fn next(&mut self, price: f64) -> f64 {
+            if self.window.len() == self.period {
+                self.window.pop_front();
+            }
+            self.window.push_back(price);
+
+            let (lo, hi) = self
+                .window
+                .iter()
+                .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), &p| (lo.min(p), hi.max(p)));
+            let position = if hi > lo { (price - lo) / (hi - lo) - 0.5 } else { 0.0 };
+
+            self.normalized = (0.66 * position + 0.67 * self.normalized).clamp(-FISHER_CLAMP, FISHER_CLAMP);
+            self.trigger = self.fisher;
+            self.fisher = 0.5 * ((1.0 + self.normalized) / (1.0 - self.normalized)).ln() + 0.5 * self.fisher;
+            self.fisher
+        }
+
+        pub fn value(&self) -> f64 {
+            self.fisher
+        }
+
+        /// Previous output; crossings of value() over it mark turns
+        pub fn trigger(&self) -> f64 {
+            self.trigger
+        }
+
+        pub fn period(&self) -> usize {
+            self.period
+        }
+    }
+
+    /// Shift `value` into the front of a bar history ([0] = current bar)
+    #[inline(always)]
+    fn shift_in<const N: usize>(history: &mut [f64; N], value: f64) {
+        history.rotate_right(1);
+        history[0] = value;
+    }
+
+    /// Ehlers' 7-tap Hilbert transform, scaled by the previous period
+    #[inline(always)]
+    fn hilbert(history: &[f64; 7], gain: f64) -> f64 {
+        (0.0962 * history[0] + 0.5769 * history[2] - 0.5769 * history[4] - 0.0962 * history[6]) * gain
+    }
+
+    /// Hilbert transform homodyne discriminator
+    ///
+    /// Measures the dominant cycle period (bars, limited to [6, 50]) and
+    /// produces the in-phase/quadrature components of the detrended price.
+    /// Follows Ehlers' "Rocket Science for Traders" bar by bar.
+    #[derive(Clone, Debug, Default)]
+    struct Homodyne {
+        prices: [f64; 4],
+        smooth: [f64; 7],
+        detrender: [f64; 7],
+        i1: [f64; 7],
+        q1: [f64; 7],
+        i2: f64,
+        q2: f64,
+        re: f64,
+        im: f64,
+        raw_period: f64,        // Before the final 0.33 smoothing
+        smooth_period: f64,
+        seen: usize,
+    }
+
+    /// Bars before the 4-bar smoother and Hilbert taps are populated
+    const HOMODYNE_WARMUP: usize = 6;
+
+    impl Homodyne {
+        /// Feed a price, returns (I1, Q1) of the current bar - O(1)
+        fn update(&mut self, price: f64) -> (f64, f64) {
+            shift_in(&mut self.prices, price);
+            self.seen += 1;
+            if self.seen < HOMODYNE_WARMUP {
+                return (0.0, 0.0);
+            }
+
+            let p = &self.prices;
+            shift_in(&mut self.smooth, (4.0 * p[0] + 3.0 * p[1] + 2.0 * p[2] + p[3]) / 10.0);
+            let gain = 0.075 * self.raw_period + 0.54;
+            shift_in(&mut self.detrender, hilbert(&self.smooth, gain));
+
+            // In-phase and quadrature, then a 90° advance of each
+            let q1 = hilbert(&self.detrender, gain);
+            let i1 = self.detrender[3];
+            shift_in(&mut self.i1, i1);
+            shift_in(&mut self.q1, q1);
+            let ji = hilbert(&self.i1, gain);
+            let jq = hilbert(&self.q1, gain);
+
+            // Phasor addition for 3-bar averaging, then smoothing
+            let i2 = 0.2 * (i1 - jq) + 0.8 * self.i2;
+            let q2 = 0.2 * (q1 + ji) + 0.8 * self.q2;
+
+            // Homodyne: multiply by the previous bar's conjugate
+            self.re = 0.2 * (i2 * self.i2 + q2 * self.q2) + 0.8 * self.re;
+            self.im = 0.2 * (i2 * self.q2 - q2 * self.i2) + 0.8 * self.im;
+            self.i2 = i2;
+            self.q2 = q2;
+
+            let prev = self.raw_period;
+            let mut period = prev;
+            if self.im != 0.0 && self.re != 0.0 {
+                period = 360.0 / (self.im / self.re).atan().to_degrees();
+            }
+            period = period.min(1.5 * prev).max(0.67 * prev).clamp(6.0, 50.0);
+            self.raw_period = 0.2 * period + 0.8 * prev;
+            self.smooth_period = 0.33 * self.raw_period + 0.67 * self.smooth_period;
+            (i1, q1)
+        }
+    }
+
+    /// Ehlers dominant cycle period measured by the homodyne discriminator
+    #[derive(Clone, Debug, Default)]
+    pub struct DominantCycle {
+        homodyne: Homodyne,
+    }
+
+    impl DominantCycle {
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        /// Feed the next price - O(1)
+        pub fn next(&mut self, price: f64) -> f64 {
+            self.homodyne.update(price);
+            self.period()
+        }
+
+        /// Smoothed dominant cycle length in bars, within [6, 50] once warm;
+        /// 0 for the first bars and ramping up over roughly 50 bars
+        pub fn period(&self) -> f64 {
+            self.homodyne.smooth_period
+        }
+    }
+
+    /// Longest cycle the discriminator reports
+    const MAX_CYCLE_BARS: usize = 50;
+
+    /// Bars before the Instantaneous Trendline stops echoing price
+    const TRENDLINE_WARMUP: usize = 12;
+
+    /// Ehlers Instantaneous Trendline
+    ///
+    /// Averages price over the current dominant cycle, which cancels the
+    /// cycle and leaves the trend, then applies a 4-bar WMA.
+    #[derive(Clone, Debug, Default)]
+    pub struct InstantaneousTrendline {
+        cycle: DominantCycle,
+        prices: VecDeque<f64>,      // Newest first, up to MAX_CYCLE_BARS
+        itrend: [f64; 4],
+        trendline: Option<f64>,
+        seen: usize,
+    }
+
+    impl InstantaneousTrendline {
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        /// Feed the next price, returns the trendline - O(cycle period)
+        pub fn next(&mut self, price: f64) -> f64 {
+            let period = self.cycle.next(price);
+            if self.prices.len() == MAX_CYCLE_BARS {
+                self.prices.pop_back();
+            }
+            self.prices.push_front(price);
+            self.seen += 1;
+
+            let bars = ((period + 0.5) as usize).clamp(1, self.prices.len());
+            shift_in(&mut self.itrend, self.prices.iter().take(bars).sum::<f64>() / bars as f64);
+            let t = &self.itrend;
+            let trendline = if self.seen < TRENDLINE_WARMUP {
+                price
+            } else {
+                (4.0 * t[0] + 3.0 * t[1] + 2.0 * t[2] + t[3]) / 10.0
+            };
+            self.trendline = Some(trendline);
+            trendline
+        }
+
+        /// Latest trendline; None before the first price
+        pub fn value(&self) -> Option<f64> {
+            self.trendline
+        }
+
+        /// Dominant cycle driving the averaging window
+        pub fn cycle(&self) -> &DominantCycle {
+            &self.cycle
+        }
+    }
+
+    /// Ehlers MESA Adaptive Moving Average
+    ///
+    /// The EMA alpha is `fast_limit / Δphase` (floored at `slow_limit`),
+    /// where Δphase is the bar-to-bar change in the Hilbert phase of
+    /// price: fast in trends, slow in cycles. FAMA follows MAMA at half
+    /// the alpha; MAMA crossing FAMA is the trade signal.
+    #[derive(Clone, Debug)]
+    pub struct Mama {
+        fast_limit: f64,
+        slow_limit: f64,
+        homodyne: Homodyne,
+        phase: f64,         // Degrees
+        mama: Option<f64>,
+        fama: f64,
+    }
+
+    impl Mama {
+        /// Ehlers uses fast_limit 0.5 and slow_limit 0.05
+        pub fn new(fast_limit: f64, slow_limit: f64) -> Self {
+            Self {
+                fast_limit,
+                slow_limit,
+                homodyne: Homodyne::default(),
+                phase: 0.0,
+                mama: None,
+                fama: 0.0,
+            }
+        }
+
+        /// Feed the next price, returns (MAMA, FAMA) - O(1)
+        /// Both start at the first price
+        pub fn next(&mut self, price: f64) -> (f64, f64) {
+            let (i1, q1) = self.homodyne.update(price);
+            let Some(mama) = self.mama else {
+                self.mama = Some(price);
+                self.fama = price;
+                return (price, price);
+            };
+
+            let prev_phase = self.phase;
+            if i1 != 0.0 {
+                self.phase = (q1 / i1).atan().to_degrees();
+            }
+            let delta_phase = (prev_phase - self.phase).max(1.0);
+            let alpha = (self.fast_limit / delta_phase).max(self.slow_limit);
+
+            let mama = alpha * price + (1.0 - alpha) * mama;
+            self.fama = 0.5 * alpha * mama + (1.0 - 0.5 * alpha) * self.fama;
+            self.mama = Some(mama);
+            (mama, self.fama)
+        }
+
+        /// Latest (MAMA, FAMA); None before the first price
+        pub fn value(&self) -> Option<(f64, f64)> {
+            self.mama.map(|mama| (mama, self.fama))
+        }
+    }
+
+    /// Gann retracement fractions: eighths plus the thirds
+    pub const GANN_RETRACEMENTS: [f64; 9] =
+        [0.125, 0.25, 1.0 / 3.0, 0.375, 0.5, 0.625, 2.0 / 3.0, 0.75, 0.875];
+
+    /// Retracement prices of a `low`..`high` swing, ascending
+    pub fn gann_retracement_levels(low: f64, high: f64) -> [f64; 9] {
+        let range = high - low;
+        let mut levels = GANN_RETRACEMENTS.map(|r| low + r * range);
+        levels.sort_unstable_by(f64::total_cmp);
+        levels
+    }
+
+    /// Gann square-of-nine levels around `base_price`, ascending
+    ///
+    /// One full rotation of the square adds 2 to the square root of price,
+    /// so the level `k` steps out is `(sqrt(base) ± 2 * k * increment)^2`,
+    /// with `increment` the fraction of a rotation per step (0.125 = 45°,
+    /// 0.5 = 180°). `rings` full rotations are generated on each side; the
+    /// base itself and non-positive roots below it are left out.
+    pub fn square_of_nine(base_price: f64, increment: f64, rings: usize) -> Vec<f64> {
+        if !(base_price > 0.0 && increment > 0.0 && increment.is_finite()) {
+            return Vec::new();
+        }
+        let root = base_price.sqrt();
+        let steps = (rings as f64 / increment).round() as usize;
+        let mut levels = Vec::with_capacity(steps * 2);
+        for k in 1..=steps {
+            let offset = 2.0 * k as f64 * increment;
+            levels.push((root + offset).powi(2));
+            if root - offset > 0.0 {
+                levels.push((root - offset).powi(2));
+            }
+        }
+        levels.sort_unstable_by(f64::total_cmp);
+        levels
+    }
+
+    /// Gann fan ratios as (price units, time units), steepest first
+    pub const GANN_ANGLES: [(u8, u8); 9] =
+        [(8, 1), (4, 1), (3, 1), (2, 1), (1, 1), (1, 2), (1, 3), (1, 4), (1, 8)];
+
+    /// One Gann angle projected from a pivot bar
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub struct GannLine {
+        pub price_units: u8,    // 2 in a 2x1
+        pub time_units: u8,     // 1 in a 2x1
+        pub pivot_price: f64,
+        pub pivot_index: usize,
+        pub slope: f64,         // Price per bar
+    }
+
+    impl GannLine {
+        /// Line price at `bar_index`; bars before the pivot extrapolate back - O(1)
+        #[inline(always)]
+        pub fn price_at(&self, bar_index: usize) -> f64 {
+            self.pivot_price + self.slope * (bar_index as f64 - self.pivot_index as f64)
+        }
+    }
+
+    /// Gann fan anchored at a pivot, steepest line first
+    ///
+    /// `points_per_bar` is the price scale of the 1x1 line. Pass a negative
+    /// scale to fan downwards from a top.
+    pub fn gann_angles(pivot_price: f64, pivot_time_index: usize, points_per_bar: f64) -> Vec<GannLine> {
+        GANN_ANGLES
+            .iter()
+            .map(|&(price_units, time_units)| GannLine {
+                price_units,
+                time_units,
+                pivot_price,
+                pivot_index: pivot_time_index,
+                slope: points_per_bar * price_units as f64 / time_units as f64,
+            })
+            .collect()
+    }
+
+    /// Volatility-aware trailing stop
+    ///
+    /// An ATR-multiple trail snapped to a Gann level: the raw stop sits
+    /// `multiplier * ATR` beyond the close, then moves out to the nearest
+    /// retracement level of the swing seen so far that lies on or beyond
+    /// it (at or below for longs, at or above for shorts). With no such
+    /// level the raw stop is used. The stop only ratchets in the trade's
+    /// favour.
+    #[derive(Clone, Debug)]
+    pub struct GannAtrStop {
+        atr: Atr,
+        multiplier: f64,
+        is_long: bool,
+        swing_low: f64,
+        swing_high: f64,
+        stop: Option<f64>,
+    }
+
+    impl GannAtrStop {
+        pub fn new(atr_period: usize, multiplier: f64, is_long: bool) -> Self {
+            Self {
+                atr: Atr::new(atr_period),
+                multiplier,
+                is_long,
+                swing_low: f64::INFINITY,
+                swing_high: f64::NEG_INFINITY,
+                stop: None,
+            }
+        }
+
+        /// Feed a closed candle - None until the ATR is warm - O(1)
+        pub fn update(&mut self, candle: &Candle) -> Option<f64> {
+            self.swing_low = self.swing_low.min(candle.low);
+            self.swing_high = self.swing_high.max(candle.high);
+            let distance = self.multiplier * self.atr.update(candle)?;
+            let levels = gann_retracement_levels(self.swing_low, self.swing_high);
+
+            let candidate = if self.is_long {
+                let limit = candle.close - distance;
+                levels.iter().rev().copied().find(|&l| l <= limit).unwrap_or(limit)
+            } else {
+                let limit = candle.close + distance;
+                levels.iter().copied().find(|&l| l >= limit).unwrap_or(limit)
+            };
+
+            let stop = match self.stop {
+                Some(prev) if self.is_long => prev.max(candidate),
+                Some(prev) => prev.min(candidate),
+                None => candidate,
+            };
+            self.stop = Some(stop);
+            self.stop
+        }
+
+        pub fn stop(&self) -> Option<f64> {
+            self.stop
+        }
+
+        pub fn atr(&self) -> Option<f64> {
+            self.atr.value()
+        }
+    }
+}
+
+pub use indicators::*;
+
+/// Ehlers cycle-analysis filters, fed one price at a time via `next`
+pub mod ehlers {
+    pub use super::indicators::{DominantCycle, FisherTransform, InstantaneousTrendline, Mama, SuperSmoother};
+}
+
+/// Gann price/time geometry and the Gann-level trailing stop
+pub mod gann {
+    pub use super::indicators::{
+        gann_angles, gann_retracement_levels, square_of_nine, GannAtrStop, GannLine, GANN_RETRACEMENTS,
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::indicators::*;
+    use super::{ehlers, gann};
+    use crate::feed::TradeEvent;
+    use crate::execution::Side;
+    use crate::orderbook::{Price, Qty};
+    use crossbeam_channel::unbounded;
+
+    fn candle(high: f64, low: f64, close: f64) -> Candle {
+        Candle { open: close, high, low, close, ts_ns: 0 }
+    }
+
+    #[test]
+    fn test_atr_matches_wilder_smoothing() {
+        let series = [
+            candle(10.0, 8.0, 9.0),     // TR 2
+            candle(11.0, 9.0, 10.5),    // TR 2
+            candle(12.0, 10.0, 11.0),   // TR 2   -> seed 2.0
+            candle(15.0, 11.0, 14.0),   // TR 4   -> (2*2 + 4) / 3
+            candle(14.0, 13.0, 13.5),   // TR 1   -> (2.6667*2 + 1) / 3
+            candle(20.0, 19.0, 19.5),   // TR 6.5 (gap from 13.5)
+        ];
+        let expected = [None, None, Some(2.0), Some(8.0 / 3.0), Some(19.0 / 9.0), Some(193.0 / 54.0)];
+        let mut atr = Atr::new(3);
+
+        for (candle, want) in series.iter().zip(expected) {
+            match (atr.update(candle), want) {
+                (None, None) => {}
+                (Some(got), Some(want)) => assert!((got - want).abs() < 1e-9, "{} vs {}", got, want),
+                (got, want) => panic!("{:?} vs {:?}", got, want),
+            }
+        }
+        assert_eq!(atr.period(), 3);
+    }
+
+    #[test]
+    fn test_gann_atr_stop_ratchets_up_in_uptrend() {
+        let mut stop = GannAtrStop::new(3, 2.0, true);
+        let mut last = f64::NEG_INFINITY;
+        let mut emitted = 0;
+
+        for i in 0..40 {
+            let base = 100.0 + i as f64;
+            // Every fifth bar pulls back; the stop must not follow it down
+            let c = if i % 5 == 4 {
+                candle(base + 1.0, base - 4.0, base - 2.5)
+            } else {
+                candle(base + 1.0, base - 1.0, base + 0.5)
+            };
+            if let Some(level) = stop.update(&c) {
+                assert!(level >= last, "stop fell from {} to {}", last, level);
+                assert!(level < c.close);
+                last = level;
+                emitted += 1;
+            }
+        }
+        assert_eq!(emitted, 38);
+        assert!(last > 100.0);
+
+        let levels = gann_retracement_levels(100.0, 200.0);
+        assert_eq!(levels[0], 112.5);
+        assert_eq!(levels[4], 150.0);
+    }
+
+    #[test]
+    fn test_square_of_nine_matches_published_levels() {
+        // 180° and 360° from 100: sqrt 10 -> 11 -> 12
+        assert_eq!(gann::square_of_nine(100.0, 0.5, 1), vec![64.0, 81.0, 121.0, 144.0]);
+
+        let eighths = gann::square_of_nine(100.0, 0.125, 1);
+        assert_eq!(eighths.len(), 16);
+        assert!(eighths.windows(2).all(|w| w[0] < w[1]));
+        assert!(eighths.contains(&110.25) && eighths.contains(&121.0) && eighths.contains(&144.0));
+
+        // Rotations below zero are dropped rather than mirrored back up
+        assert_eq!(gann::square_of_nine(4.0, 0.5, 1), vec![1.0, 9.0, 16.0]);
+        assert!(gann::square_of_nine(100.0, 0.0, 2).is_empty());
+        assert!(gann::square_of_nine(-1.0, 0.125, 2).is_empty());
+    }
+
+    #[test]
+    fn test_gann_angles_project_one_by_one_and_two_by_one() {
+        let fan = gann::gann_angles(100.0, 10, 0.5);
+        assert_eq!(fan.len(), GANN_ANGLES.len());
+        let line = |p, t| *fan.iter().find(|l| (l.price_units, l.time_units) == (p, t)).unwrap();
+
+        let one_by_one = line(1, 1);
+        let two_by_one = line(2, 1);
+        for (bar, one, two) in [(10, 100.0, 100.0), (11, 100.5, 101.0), (20, 105.0, 110.0), (4, 97.0, 94.0)] {
+            assert_eq!(one_by_one.price_at(bar), one);
+            assert_eq!(two_by_one.price_at(bar), two);
+        }
+        assert_eq!(line(1, 2).price_at(14), 101.0);
+        assert!(fan.windows(2).all(|w| w[0].slope > w[1].slope));
+
+        // Fanning down from a top
+        let down = gann::gann_angles(100.0, 0, -0.5);
+        assert_eq!(down[4].price_at(6), 97.0);
+    }
+
+    #[test]
+    fn test_super_smoother_tracks_steps_and_beats_ema_on_noise() {
+        let mut ss = ehlers::SuperSmoother::new(10.0);
+        assert_eq!(ss.value(), None);
+        for _ in 0..5 {
+            ss.next(100.0);
+        }
+        let mut last = 0.0;
+        for _ in 0..100 {
+            last = ss.next(110.0);
+        }
+        assert!((last - 110.0).abs() < 1e-6, "{}", last);
+
+        // Bar-to-bar noise around a flat level
+        let alpha = 2.0 / (10.0 + 1.0);
+        let mut ss = ehlers::SuperSmoother::new(10.0);
+        let mut ema = 100.0;
+        let (mut ss_dev, mut ema_dev) = (0.0f64, 0.0f64);
+        for i in 0..200 {
+            let price = if i % 2 == 0 { 101.0 } else { 99.0 };
+            let smoothed = ss.next(price);
+            ema += alpha * (price - ema);
+            if i >= 50 {
+                ss_dev = ss_dev.max((smoothed - 100.0).abs());
+                ema_dev = ema_dev.max((ema - 100.0).abs());
+            }
+        }
+        assert!(ss_dev < ema_dev / 10.0, "super smoother {} vs ema {}", ss_dev, ema_dev);
+    }
+
+    #[test]
+    fn test_fisher_turns_with_sine_extremes_ahead_of_smoothing() {
+        const CYCLE: f64 = 40.0;
+        let price = |i: usize| 100.0 + 5.0 * (2.0 * std::f64::consts::PI * i as f64 / CYCLE).sin();
+        let mut fisher = ehlers::FisherTransform::new(10);
+        let out: Vec<f64> = (0..400).map(|i| fisher.next(price(i))).collect();
+        assert!(out.iter().all(|v| v.is_finite()));
+        // 9-bar moving average (4 bars of lag) as the reference
+        let sma = |i: usize| (i - 8..=i).map(price).sum::<f64>() / 9.0;
+
+        let turns = |series: &dyn Fn(usize) -> f64| -> Vec<usize> {
+            (200..399).filter(|&i| (series(i) - series(i - 1)) * (series(i + 1) - series(i)) < 0.0).collect()
+        };
+        let price_turns = turns(&price);
+        let fisher_turns = turns(&|i| out[i]);
+        let sma_turns = turns(&sma);
+        assert_eq!(price_turns.len(), 10);
+        assert_eq!(fisher_turns.len(), price_turns.len());
+        assert_eq!(sma_turns.len(), price_turns.len());
+
+        for ((&p, &f), &m) in price_turns.iter().zip(&fisher_turns).zip(&sma_turns) {
+            assert!(f <= p, "fisher turned at {} after price at {}", f, p);
+            assert!(m >= f + 4, "sma turned at {} vs fisher {}", m, f);
+            // Sharp reversal: the first bar back gives up over 10% of the swing
+            assert!((out[f] - out[f + 1]).abs() > 0.1 * out[f].abs());
+        }
+    }
+
+    #[test]
+    fn test_mama_regression_snapshot() {
+        // Trending cycle plus a slower swing, fully deterministic
+        let price = |i: usize| {
+            let t = i as f64;
+            100.0 + 0.05 * t + 3.0 * (t * std::f64::consts::TAU / 20.0).sin() + 1.5 * (t * 0.05).cos()
+        };
+        let mut mama = ehlers::Mama::new(0.5, 0.05);
+        assert_eq!(mama.value(), None);
+        let out: Vec<(f64, f64)> = (0..300).map(|i| mama.next(price(i))).collect();
+        // Recorded from this implementation; update deliberately, never to make a refactor pass
+        let snapshot = [
+            (10, 103.254553207, 103.615509281),
+            (50, 102.535539390, 101.835393647),
+            (100, 103.071798640, 102.563334116),
+            (200, 106.982915284, 107.565466957),
+            (299, 113.033494034, 113.757483900),
+        ];
+        for (i, want_mama, want_fama) in snapshot {
+            let (got_mama, got_fama) = out[i];
+            assert!((got_mama - want_mama).abs() < 1e-8, "mama[{}] {} vs {}", i, got_mama, want_mama);
+            assert!((got_fama - want_fama).abs() < 1e-8, "fama[{}] {} vs {}", i, got_fama, want_fama);
+        }
+        let crosses = out.windows(2).filter(|w| (w[0].0 - w[0].1).signum() != (w[1].0 - w[1].1).signum()).count();
+        assert_eq!(crosses, 17);
+        assert_eq!(mama.value(), out.last().copied());
+    }
+
+    #[test]
+    fn test_dominant_cycle_locks_onto_period_twenty() {
+        let wave = |i: usize| 100.0 + 2.0 * (i as f64 * std::f64::consts::TAU / 20.0).sin();
+        let mut cycle = ehlers::DominantCycle::new();
+        assert_eq!(cycle.period(), 0.0);
+        for i in 0..100 {
+            cycle.next(wave(i));
+        }
+        for i in 100..300 {
+            let period = cycle.next(wave(i));
+            assert!((period - 20.0).abs() < 0.5, "bar {}: {}", i, period);
+        }
+
+        // Trend plus the same cycle: the trendline cancels the cycle
+        let mut itrend = ehlers::InstantaneousTrendline::new();
+        let mut worst = 0.0f64;
+        for i in 0..300 {
+            let trend = 100.0 + 0.1 * i as f64;
+            let line = itrend.next(trend + wave(i) - 100.0);
+            if i >= 150 {
+                // Lagging the trend by ~half a cycle plus the WMA
+                worst = worst.max((line - (trend - 0.1 * 10.5)).abs());
+            }
+        }
+        // Cycle amplitude is 2.0
+        assert!(worst < 0.25, "residual cycle {}", worst);
+        assert!(itrend.cycle().period() > 15.0);
+    }
+
+    fn trade(price: f64, qty: f64, ts_ns: i64) -> TradeEvent {
+        TradeEvent { symbol_hash: 1, trade_id: 0, price: Price(price), qty: Qty(qty), aggressor: Side::Buy, ts_ns }
+    }
+
+    #[test]
+    fn test_bar_aggregator_boundaries_and_gaps() {
+        const SEC: i64 = 1_000_000_000;
+        for gap_fill in [GapFill::Skip, GapFill::Flat] {
+            let (tx, rx) = unbounded();
+            let mut bars = BarAggregator::new(1, BarMode::Time { interval_ns: SEC }, gap_fill, tx);
+            bars.on_trade(&trade(100.0, 1.0, 100));
+            bars.on_trade(&trade(103.0, 0.5, SEC / 2));
+            bars.on_trade(&trade(99.0, 2.0, SEC - 1));
+            assert!(rx.is_empty());
+
+            // Exactly on the boundary: opens the next bar
+            bars.on_trade(&trade(101.0, 1.0, SEC));
+            let first = rx.try_recv().unwrap();
+            assert_eq!(first, Bar { open: 100.0, high: 103.0, low: 99.0, close: 99.0, volume: 3.5, start_ns: 0, end_ns: SEC });
+            assert_eq!(first.candle().close, 99.0);
+
+            bars.on_trade(&trade(0.0, 1.0, SEC / 2));
+            assert_eq!(bars.late_dropped(), 1);
+            bars.on_trade(&TradeEvent { symbol_hash: 2, ..trade(1.0, 1.0, 10 * SEC) });
+
+            // Nothing during [2s, 4s), then a trade at 4.2s
+            bars.on_trade(&trade(104.0, 1.0, 4 * SEC + 200));
+            let second = rx.try_recv().unwrap();
+            assert_eq!((second.start_ns, second.close, second.volume), (SEC, 101.0, 1.0));
+            let flats: Vec<Bar> = rx.try_iter().collect();
+            match gap_fill {
+                GapFill::Skip => assert!(flats.is_empty()),
+                GapFill::Flat => {
+                    let flat = |start_ns| Bar { open: 101.0, high: 101.0, low: 101.0, close: 101.0, volume: 0.0, start_ns, end_ns: start_ns + SEC };
+                    assert_eq!(flats, vec![flat(2 * SEC), flat(3 * SEC)]);
+                }
+            }
+
+            // Quiet market: the clock closes the bar
+            assert_eq!(bars.flush(4 * SEC + 900), 0);
+            assert_eq!(bars.flush(5 * SEC), 1);
+            assert_eq!(rx.try_recv().unwrap().start_ns, 4 * SEC);
+            assert!(bars.current().is_none());
+        }
+    }
+
+    #[test]
+    fn test_volume_and_dollar_bars_carry_overshoot() {
+        let (tx, rx) = unbounded();
+        let mut bars = BarAggregator::new(1, BarMode::Volume(10.0), GapFill::Skip, tx);
+        let stream = [trade(100.0, 4.0, 1), trade(101.0, 4.0, 2), trade(99.0, 5.0, 3), trade(102.0, 27.0, 4)];
+        for t in &stream {
+            bars.on_trade(t);
+        }
+        assert_eq!(bars.flush(i64::MAX), 0);
+        let got: Vec<Bar> = rx.try_iter().collect();
+        // 4 + 4 + 2 of the 5 close the first bar; 3 carry; 7 of the 27 close
+        // the second, 20 fill two more exactly
+        assert_eq!(got.len(), 4);
+        assert_eq!(got[0], Bar { open: 100.0, high: 101.0, low: 99.0, close: 99.0, volume: 10.0, start_ns: 1, end_ns: 3 });
+        assert_eq!(got[1], Bar { open: 99.0, high: 102.0, low: 99.0, close: 102.0, volume: 10.0, start_ns: 3, end_ns: 4 });
+        assert!(got.iter().all(|b| b.volume == 10.0));
+        assert!(bars.current().is_none());
+
+        // Same stream, same bars
+        let (tx, rx) = unbounded();
+        let mut again = BarAggregator::new(1, BarMode::Volume(10.0), GapFill::Skip, tx);
+        stream.iter().for_each(|t| again.on_trade(t));
+        assert_eq!(rx.try_iter().collect::<Vec<_>>(), got);
+
+        let (tx, rx) = unbounded();
+        let mut dollars = BarAggregator::new(1, BarMode::Dollar(1_000.0), GapFill::Skip, tx);
+        dollars.on_trade(&trade(100.0, 3.0, 1));      // $300
+        dollars.on_trade(&trade(200.0, 5.0, 2));      // $1000: 3.5 closes the bar, 1.5 carries
+        dollars.on_trade(&trade(0.1, 3.0, 3));        // $0.30
+        let got: Vec<Bar> = rx.try_iter().collect();
+        assert_eq!(got.len(), 1);
+        assert_eq!((got[0].volume, got[0].close, got[0].end_ns), (6.5, 200.0, 2));
+        let open = dollars.current().unwrap();
+        assert_eq!((open.open, open.volume, open.low), (200.0, 4.5, 0.1));
+    }
+}
@@ -0,0 +1,38 @@
+// ============================================================================
+// CENAYANG MARKET — Rust Zero-Bottleneck Engine (library)
+//
+// Embeddable building blocks behind the gateway binary:
+//   breaker    — per-symbol market data circuit breaker
+//   correlation — streaming pairs correlation of mid returns
+//   execution  — idempotent order execution + fill stream
+//   feed       — exchange feed connection management
+//   indicators — streaming technical indicators (ATR, Gann, Ehlers),
+//                also at `ehlers` and `gann`
+//   logging    — tracing subscriber setup (pretty / JSON)
+//   orderbook  — L2 orderbook with sequence tracking
+//   processor  — symbol-sharded book update processing
+//   quality    — post-run market data quality report
+//   risk       — pure fixed-point risk calculations
+//   rng        — replayable deterministic randomness
+//   shutdown   — coordinated pipeline shutdown
+//   simulation — fill simulation against the L2 book
+// ============================================================================
+
+// Each module wraps its items in a same-named inner module and re-exports them
+#![allow(clippy::module_inception)]
+
+pub mod breaker;
+pub mod correlation;
+pub mod execution;
+pub mod feed;
+pub mod indicators;
+pub mod logging;
+pub mod orderbook;
+pub mod processor;
+pub mod quality;
+pub mod risk;
+pub mod rng;
+pub mod shutdown;
+pub mod simulation;
+
+pub use indicators::{ehlers, gann};
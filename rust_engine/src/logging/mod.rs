@@ -0,0 +1,117 @@
+// Logging module — tracing Subscriber Setup
+//
+// Features:
+// - Human-readable output with thread ids (default)
+// - Structured JSON output for log aggregation (LOG_FORMAT=json)
+// - RUST_LOG env filter honoured by both formats
+
+pub mod logging {
+    use tracing_subscriber::fmt::MakeWriter;
+    use tracing_subscriber::EnvFilter;
+
+    /// Environment variable selecting the log format
+    pub const LOG_FORMAT_ENV: &str = "LOG_FORMAT";
+
+    /// Log output format
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub enum LogFormat {
+        Pretty,
+        Json,
+    }
+
+    impl LogFormat {
+        /// `LOG_FORMAT=json` selects JSON, anything else is human-readable
+        pub fn from_env() -> Self {
+            match std::env::var(LOG_FORMAT_ENV) {
+                Ok(v) if v.eq_ignore_ascii_case("json") => LogFormat::Json,
+                _ => LogFormat::Pretty,
+            }
+        }
+    }
+
+    fn env_filter() -> EnvFilter {
+        EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"))
+    }
+
+    /// Build a subscriber writing to `writer` in the given format
+    pub fn build_subscriber<W>(format: LogFormat, writer: W) -> Box<dyn tracing::Subscriber + Send + Sync>
+    where
+        W: for<'a> MakeWriter<'a> + Send + Sync + 'static,
+    {
+        let builder = tracing_subscriber::fmt()
+            .with_env_filter(env_filter())
+            .with_writer(writer)
+            .with_thread_ids(true)
+            .with_target(true);
+
+        match format {
+            LogFormat::Pretty => Box::new(builder.finish()),
+            LogFormat::Json => Box::new(
+                builder
+                    .json()
+                    .flatten_event(true)
+                    .with_current_span(true)
+                    .with_span_list(true)
+                    .finish(),
+            ),
+        }
+    }
+
+    /// Install the global subscriber - call once at startup
+    pub fn init(format: LogFormat) -> Result<(), tracing::subscriber::SetGlobalDefaultError> {
+        tracing::subscriber::set_global_default(build_subscriber(format, std::io::stdout))
+    }
+}
+
+pub use logging::*;
+
+#[cfg(test)]
+mod tests {
+    use super::logging::*;
+    use std::io::Write;
+    use std::sync::{Arc, Mutex};
+
+    /// Shared in-memory writer for capturing log output
+    #[derive(Clone, Default)]
+    struct Capture(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Capture {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl<'a> tracing_subscriber::fmt::MakeWriter<'a> for Capture {
+        type Writer = Capture;
+
+        fn make_writer(&'a self) -> Self::Writer {
+            self.clone()
+        }
+    }
+
+    #[test]
+    fn test_json_log_line_is_valid_json() {
+        let capture = Capture::default();
+        let subscriber = build_subscriber(LogFormat::Json, capture.clone());
+
+        tracing::subscriber::with_default(subscriber, || {
+            let span = tracing::info_span!("processor", symbol = "BTCUSDT");
+            let _enter = span.enter();
+            tracing::warn!(seq_id = 42u64, "sequence gap detected");
+        });
+
+        let output = String::from_utf8(capture.0.lock().unwrap().clone()).unwrap();
+        let line: serde_json::Value = serde_json::from_str(output.lines().next().unwrap()).unwrap();
+
+        assert_eq!(line["level"], "WARN");
+        assert_eq!(line["message"], "sequence gap detected");
+        assert_eq!(line["seq_id"], 42);
+        assert_eq!(line["span"]["symbol"], "BTCUSDT");
+        assert!(line["threadId"].is_string());
+    }
+}
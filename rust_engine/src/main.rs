@@ -24,12 +24,13 @@
 #![allow(dead_code)]
 #![allow(unused_variables)]
 
+use cenayang_market_zero_bottleneck::feed::{FeedHealth, ReplayClock, SystemClock};
+use cenayang_market_zero_bottleneck::logging::{self, LogFormat};
+use cenayang_market_zero_bottleneck::rng::{PipelineRng, StreamRng, DEFAULT_SEED};
 use crossbeam_channel::{bounded, Receiver, Sender};
-use std::alloc::alloc_zeroed;
-use std::alloc::{alloc, dealloc, Layout};
 use std::arch::x86_64::_mm_prefetch;
-use std::collections::BTreeMap;
-use std::sync::atomic::{AtomicBool, AtomicI64, AtomicU32, AtomicU64, Ordering};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicI64, AtomicU32, AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
@@ -53,7 +54,7 @@ const PRICE_SCALE: f64 = 100_000_000.0;
 
 /// Market tick with cache-line alignment - 64 bytes total
 #[repr(C, align(64))]
-#[derive(Clone, Copy, Default)]
+#[derive(Clone, Copy, Debug, Default)]
 pub struct MarketTickZeroCopy {
     pub symbol_hash: u64,      // 8 bytes - Pre-hashed symbol
     pub bid_price: i64,        // 8 bytes - Fixed-point price
@@ -69,14 +70,20 @@ pub struct MarketTickZeroCopy {
 }
 
 impl MarketTickZeroCopy {
+    /// Field bytes; the rest of the 128-byte slot is alignment padding
+    pub const SIZE: usize = 80;
+
     #[inline(always)]
-    pub fn to_bytes(&self) -> [u8; 80] {
+    pub fn to_bytes(&self) -> [u8; Self::SIZE] {
         unsafe { std::mem::transmute_copy(self) }
     }
 
     #[inline(always)]
-    pub fn from_bytes(bytes: &[u8; 80]) -> Self {
-        unsafe { std::mem::transmute_copy(bytes) }
+    pub fn from_bytes(bytes: &[u8; Self::SIZE]) -> Self {
+        // transmute_copy would read a full slot past the end of `bytes`
+        let mut tick = Self::default();
+        unsafe { std::ptr::copy_nonoverlapping(bytes.as_ptr(), &mut tick as *mut Self as *mut u8, Self::SIZE) };
+        tick
     }
 }
 
@@ -221,8 +228,8 @@ impl LockFreeHistogram {
 
     #[inline(always)]
     pub fn record(&self, value: i64) {
-        let bucket_idx = ((value - self.min_value) / self.bucket_width)
-            .max(0) as usize
+        let bucket_idx = (((value - self.min_value) / self.bucket_width)
+            .max(0) as usize)
             .min(HISTOGRAM_BUCKETS - 1);
         
         self.buckets[bucket_idx].fetch_add(1, Ordering::Relaxed);
@@ -288,6 +295,53 @@ impl LockFreeHistogram {
         self.max_seen.load(Ordering::Relaxed)
     }
 
+    #[inline(always)]
+    pub fn count(&self) -> u64 {
+        self.count.load(Ordering::Relaxed)
+    }
+
+    /// Median and median absolute deviation at bucket resolution - O(buckets)
+    pub fn median_abs_deviation(&self) -> Option<(i64, i64)> {
+        let total = self.count();
+        if total == 0 {
+            return None;
+        }
+        let half = total.div_ceil(2);
+
+        let mut cumulative = 0;
+        let mut median_idx = HISTOGRAM_BUCKETS - 1;
+        for (idx, bucket) in self.buckets.iter().enumerate() {
+            cumulative += bucket.load(Ordering::Relaxed);
+            if cumulative >= half {
+                median_idx = idx;
+                break;
+            }
+        }
+
+        // Widen a window around the median until it holds half the samples
+        let mut within = self.buckets[median_idx].load(Ordering::Relaxed);
+        let mut distance = 0;
+        while within < half && distance < HISTOGRAM_BUCKETS {
+            distance += 1;
+            if distance <= median_idx {
+                within += self.buckets[median_idx - distance].load(Ordering::Relaxed);
+            }
+            if median_idx + distance < HISTOGRAM_BUCKETS {
+                within += self.buckets[median_idx + distance].load(Ordering::Relaxed);
+            }
+        }
+
+        Some((
+            self.min_value + median_idx as i64 * self.bucket_width,
+            distance as i64 * self.bucket_width,
+        ))
+    }
+
+    #[inline(always)]
+    pub fn bucket_width(&self) -> i64 {
+        self.bucket_width
+    }
+
     pub fn reset(&self) {
         for bucket in self.buckets.iter() {
             bucket.store(0, Ordering::Relaxed);
@@ -389,7 +443,7 @@ impl<T> ObjectPool<T> {
     }
 
     #[inline(always)]
-    pub fn acquire(&self, factory: impl Fn() -> T) -> PooledObject<T> {
+    pub fn acquire(&self, factory: impl Fn() -> T) -> PooledObject<'_, T> {
         self.stats.acquired.fetch_add(1, Ordering::Relaxed);
         
         let obj = self.pool.pop().unwrap_or_else(|| {
@@ -551,12 +605,166 @@ impl ShardedOrderbook {
 // LOCK-FREE LATENCY TRACKER - Complete Implementation
 // ============================================================================
 
+/// Sentinel returned for percentiles computed from too few samples
+pub const PERCENTILE_SUPPRESSED: i64 = -1;
+
+/// Typed per-metric snapshot - sample count travels with the percentiles
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct MetricSnapshot {
+    pub samples: u64,
+    pub p50_ns: i64,        // PERCENTILE_SUPPRESSED below min_samples
+    pub p99_ns: i64,        // PERCENTILE_SUPPRESSED below min_samples
+    pub mean_ns: i64,
+}
+
+impl MetricSnapshot {
+    /// Percentiles are suppressed below `min_samples`
+    pub fn from_histogram(hist: &LockFreeHistogram, min_samples: u64) -> Self {
+        let samples = hist.count();
+        let (p50_ns, p99_ns) = if samples < min_samples {
+            (PERCENTILE_SUPPRESSED, PERCENTILE_SUPPRESSED)
+        } else {
+            (hist.percentile(50.0), hist.percentile(99.0))
+        };
+
+        Self {
+            samples,
+            p50_ns,
+            p99_ns,
+            mean_ns: hist.mean(),
+        }
+    }
+
+    #[inline(always)]
+    pub fn is_reliable(&self) -> bool {
+        self.p50_ns != PERCENTILE_SUPPRESSED
+    }
+}
+
+/// Typed snapshot of all latency metrics
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct LatencySnapshot {
+    pub ingestion: MetricSnapshot,
+    pub processing: MetricSnapshot,
+    pub publish: MetricSnapshot,
+    pub risk: MetricSnapshot,
+}
+
+/// Display unit for a latency metric - storage is always nanoseconds
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LatencyUnit {
+    Nanos,
+    Micros,
+    Millis,
+}
+
+impl LatencyUnit {
+    #[inline(always)]
+    pub fn from_ns(&self, ns: i64) -> f64 {
+        match self {
+            LatencyUnit::Nanos => ns as f64,
+            LatencyUnit::Micros => ns as f64 / 1_000.0,
+            LatencyUnit::Millis => ns as f64 / 1_000_000.0,
+        }
+    }
+
+    pub fn suffix(&self) -> &'static str {
+        match self {
+            LatencyUnit::Nanos => "ns",
+            LatencyUnit::Micros => "μs",
+            LatencyUnit::Millis => "ms",
+        }
+    }
+
+    fn decimals(&self) -> usize {
+        match self {
+            LatencyUnit::Nanos => 0,
+            LatencyUnit::Micros => 1,
+            LatencyUnit::Millis => 3,
+        }
+    }
+
+    fn format(&self, ns: i64) -> String {
+        format!("{:.*}{}", self.decimals(), self.from_ns(ns), self.suffix())
+    }
+}
+
+/// Latency sample outlier rejection - rejected samples are counted, not recorded
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OutlierGuard {
+    Disabled,
+    AboveNs(i64),       // Absolute ceiling
+    Mad { k: f64 },     // median + k * MAD, once MAD_MIN_SAMPLES are recorded
+}
+
+/// Samples needed before the MAD guard starts rejecting
+pub const MAD_MIN_SAMPLES: u64 = 100;
+
+/// MAD ceiling is recomputed after this many new samples
+const MAD_REFRESH_SAMPLES: u64 = 256;
+
+/// Cached MAD ceiling for one metric
+#[derive(Default)]
+struct MadLimit {
+    limit_ns: AtomicI64,        // 0 = not computed yet
+    refreshed_at: AtomicU64,    // Sample count at last refresh
+}
+
+impl MadLimit {
+    fn limit(&self, hist: &LockFreeHistogram, k: f64) -> Option<i64> {
+        let count = hist.count();
+        if count < MAD_MIN_SAMPLES {
+            return None;
+        }
+        let cached = self.limit_ns.load(Ordering::Relaxed);
+        if cached > 0 && count - self.refreshed_at.load(Ordering::Relaxed) < MAD_REFRESH_SAMPLES {
+            return Some(cached);
+        }
+
+        let (median, mad) = hist.median_abs_deviation()?;
+        // Bucket resolution: never narrower than one bucket past the median
+        let width = hist.bucket_width();
+        let limit = median + width + (k * mad.max(width) as f64) as i64;
+        self.limit_ns.store(limit, Ordering::Relaxed);
+        self.refreshed_at.store(count, Ordering::Relaxed);
+        Some(limit)
+    }
+}
+
+/// Per-metric display units for `summary()`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SummaryUnits {
+    pub ingestion: LatencyUnit,
+    pub processing: LatencyUnit,
+    pub publish: LatencyUnit,
+    pub risk: LatencyUnit,
+}
+
+impl Default for SummaryUnits {
+    fn default() -> Self {
+        Self {
+            ingestion: LatencyUnit::Micros,
+            processing: LatencyUnit::Micros,
+            publish: LatencyUnit::Micros,
+            risk: LatencyUnit::Micros,
+        }
+    }
+}
+
 /// Zero-bottleneck latency tracker with atomic histograms
 pub struct ZeroBottleneckLatencyTracker {
     ingestion_hist: LockFreeHistogram,
     processing_hist: LockFreeHistogram,
     publish_hist: LockFreeHistogram,
     risk_hist: LockFreeHistogram,
+    min_samples: u64,       // Percentiles below this count are suppressed
+    units: SummaryUnits,
+    outlier_guard: OutlierGuard,
+    ingestion_limit: MadLimit,
+    processing_limit: MadLimit,
+    publish_limit: MadLimit,
+    risk_limit: MadLimit,
+    latency_outliers: AtomicU64,
     
     ticks_processed: AtomicU64,
     fills_processed: AtomicU64,
@@ -564,43 +772,88 @@ pub struct ZeroBottleneckLatencyTracker {
     gaps_detected: AtomicU64,
     risk_rejections: AtomicU64,
     broadcast_drops: AtomicU64,
+    feed_health: Option<Arc<FeedHealth>>,  // Source of the reconnect count
 }
 
 impl ZeroBottleneckLatencyTracker {
     pub fn new() -> Self {
+        Self::with_min_samples(0)
+    }
+
+    /// Tracker that suppresses percentiles backed by fewer than `min_samples`
+    pub fn with_min_samples(min_samples: u64) -> Self {
         Self {
             ingestion_hist: LockFreeHistogram::new(0, 10_000_000),    // 0-10ms
             processing_hist: LockFreeHistogram::new(0, 1_000_000),    // 0-1ms
             publish_hist: LockFreeHistogram::new(0, 1_000_000),       // 0-1ms
             risk_hist: LockFreeHistogram::new(0, 100_000),            // 0-100μs
+            min_samples,
+            units: SummaryUnits::default(),
+            outlier_guard: OutlierGuard::Disabled,
+            ingestion_limit: MadLimit::default(),
+            processing_limit: MadLimit::default(),
+            publish_limit: MadLimit::default(),
+            risk_limit: MadLimit::default(),
+            latency_outliers: AtomicU64::new(0),
             ticks_processed: AtomicU64::new(0),
             fills_processed: AtomicU64::new(0),
             orders_submitted: AtomicU64::new(0),
             gaps_detected: AtomicU64::new(0),
             risk_rejections: AtomicU64::new(0),
             broadcast_drops: AtomicU64::new(0),
+            feed_health: None,
         }
     }
 
+    /// Export the feed runner's reconnect count alongside the pipeline totals
+    pub fn set_feed_health(&mut self, health: Arc<FeedHealth>) {
+        self.feed_health = Some(health);
+    }
+
+    /// Reject samples the guard flags before they reach the histograms
+    pub fn set_outlier_guard(&mut self, guard: OutlierGuard) {
+        self.outlier_guard = guard;
+    }
+
+    /// Samples discarded by the outlier guard
+    pub fn latency_outliers(&self) -> u64 {
+        self.latency_outliers.load(Ordering::Relaxed)
+    }
+
+    /// Record unless the guard flags the sample as an outlier
+    #[inline(always)]
+    fn record_guarded(&self, hist: &LockFreeHistogram, limit: &MadLimit, latency_ns: i64) {
+        let ceiling = match self.outlier_guard {
+            OutlierGuard::Disabled => None,
+            OutlierGuard::AboveNs(max_ns) => Some(max_ns),
+            OutlierGuard::Mad { k } => limit.limit(hist, k),
+        };
+        if ceiling.is_some_and(|c| latency_ns > c) {
+            self.latency_outliers.fetch_add(1, Ordering::Relaxed);
+            return;
+        }
+        hist.record(latency_ns);
+    }
+
     #[inline(always)]
     pub fn record_ingestion(&self, latency_ns: i64) {
-        self.ingestion_hist.record(latency_ns);
+        self.record_guarded(&self.ingestion_hist, &self.ingestion_limit, latency_ns);
         self.ticks_processed.fetch_add(1, Ordering::Relaxed);
     }
 
     #[inline(always)]
     pub fn record_processing(&self, latency_ns: i64) {
-        self.processing_hist.record(latency_ns);
+        self.record_guarded(&self.processing_hist, &self.processing_limit, latency_ns);
     }
 
     #[inline(always)]
     pub fn record_publish(&self, latency_ns: i64) {
-        self.publish_hist.record(latency_ns);
+        self.record_guarded(&self.publish_hist, &self.publish_limit, latency_ns);
     }
 
     #[inline(always)]
     pub fn record_risk(&self, latency_ns: i64) {
-        self.risk_hist.record(latency_ns);
+        self.record_guarded(&self.risk_hist, &self.risk_limit, latency_ns);
     }
 
     #[inline(always)]
@@ -628,29 +881,114 @@ impl ZeroBottleneckLatencyTracker {
         self.broadcast_drops.fetch_add(1, Ordering::Relaxed);
     }
 
+    fn metric_snapshot(&self, hist: &LockFreeHistogram) -> MetricSnapshot {
+        MetricSnapshot::from_histogram(hist, self.min_samples)
+    }
+
+    /// Typed snapshot with per-metric sample counts
+    pub fn snapshot(&self) -> LatencySnapshot {
+        LatencySnapshot {
+            ingestion: self.metric_snapshot(&self.ingestion_hist),
+            processing: self.metric_snapshot(&self.processing_hist),
+            publish: self.metric_snapshot(&self.publish_hist),
+            risk: self.metric_snapshot(&self.risk_hist),
+        }
+    }
+
+    /// Set per-metric display units used by `summary()`
+    pub fn set_units(&mut self, units: SummaryUnits) {
+        self.units = units;
+    }
+
+    fn format_metric(name: &str, m: &MetricSnapshot, unit: LatencyUnit) -> String {
+        if !m.is_reliable() {
+            return format!(
+                "{}: P50=n/a P99=n/a Mean={} (n={}, too few samples)",
+                name, unit.format(m.mean_ns), m.samples
+            );
+        }
+        format!(
+            "{}: P50={} P99={} Mean={} (n={})",
+            name,
+            unit.format(m.p50_ns),
+            unit.format(m.p99_ns),
+            unit.format(m.mean_ns),
+            m.samples,
+        )
+    }
+
     pub fn summary(&self) -> String {
+        let snap = self.snapshot();
         format!(
-            "Ticks:{} Fills:{} Orders:{} Gaps:{} Rejects:{} Drops:{}\n\
-             Ingestion: P50={:.1}μs P99={:.1}μs Mean={:.1}μs\n\
-             Processing: P50={:.1}μs P99={:.1}μs Mean={:.1}μs\n\
-             Risk: P50={:.1}μs P99={:.1}μs Mean={:.1}μs",
+            "Ticks:{} Fills:{} Orders:{} Gaps:{} Rejects:{} Drops:{} Outliers:{}\n{}\n{}\n{}\n{}",
             self.ticks_processed.load(Ordering::Relaxed),
             self.fills_processed.load(Ordering::Relaxed),
             self.orders_submitted.load(Ordering::Relaxed),
             self.gaps_detected.load(Ordering::Relaxed),
             self.risk_rejections.load(Ordering::Relaxed),
             self.broadcast_drops.load(Ordering::Relaxed),
-            self.ingestion_hist.percentile(50.0) as f64 / 1000.0,
-            self.ingestion_hist.percentile(99.0) as f64 / 1000.0,
-            self.ingestion_hist.mean() as f64 / 1000.0,
-            self.processing_hist.percentile(50.0) as f64 / 1000.0,
-            self.processing_hist.percentile(99.0) as f64 / 1000.0,
-            self.processing_hist.mean() as f64 / 1000.0,
-            self.risk_hist.percentile(50.0) as f64 / 1000.0,
-            self.risk_hist.percentile(99.0) as f64 / 1000.0,
-            self.risk_hist.mean() as f64 / 1000.0,
+            self.latency_outliers.load(Ordering::Relaxed),
+            Self::format_metric("Ingestion", &snap.ingestion, self.units.ingestion),
+            Self::format_metric("Processing", &snap.processing, self.units.processing),
+            Self::format_metric("Publish", &snap.publish, self.units.publish),
+            Self::format_metric("Risk", &snap.risk, self.units.risk),
         )
     }
+
+    /// Prometheus text exposition (format 0.0.4)
+    /// Event totals are counters; P50/P99 are gauges in seconds, omitted
+    /// while a stage is below `min_samples`. Feed reconnects are emitted
+    /// once a `FeedHealth` is attached.
+    pub fn prometheus_text(&self) -> String {
+        use std::fmt::Write;
+
+        let counters = [
+            ("ticks_processed", "Ticks ingested", &self.ticks_processed),
+            ("fills_processed", "Fills processed", &self.fills_processed),
+            ("orders_submitted", "Orders submitted", &self.orders_submitted),
+            ("gaps_detected", "Sequence gaps detected", &self.gaps_detected),
+            ("risk_rejections", "Orders rejected by risk checks", &self.risk_rejections),
+            ("broadcast_drops", "Broadcast events dropped", &self.broadcast_drops),
+            ("latency_outliers", "Latency samples rejected as outliers", &self.latency_outliers),
+        ];
+
+        let mut out = String::with_capacity(2048);
+        for (name, help, counter) in counters {
+            let _ = writeln!(out, "# HELP cenayang_{}_total {}", name, help);
+            let _ = writeln!(out, "# TYPE cenayang_{}_total counter", name);
+            let _ = writeln!(out, "cenayang_{}_total {}", name, counter.load(Ordering::Relaxed));
+        }
+        if let Some(health) = &self.feed_health {
+            out.push_str("# HELP cenayang_feed_reconnects_total Feed reconnects, stalls included\n");
+            out.push_str("# TYPE cenayang_feed_reconnects_total counter\n");
+            let _ = writeln!(out, "cenayang_feed_reconnects_total {}", health.reconnects());
+        }
+
+        let snap = self.snapshot();
+        let stages = [
+            ("ingestion", snap.ingestion),
+            ("processing", snap.processing),
+            ("publish", snap.publish),
+            ("risk", snap.risk),
+        ];
+        out.push_str("# HELP cenayang_latency_seconds Pipeline stage latency percentiles\n");
+        out.push_str("# TYPE cenayang_latency_seconds gauge\n");
+        for (stage, m) in stages.iter().filter(|(_, m)| m.is_reliable()) {
+            for (quantile, ns) in [("0.5", m.p50_ns), ("0.99", m.p99_ns)] {
+                let _ = writeln!(
+                    out,
+                    "cenayang_latency_seconds{{stage=\"{}\",quantile=\"{}\"}} {}",
+                    stage, quantile, ns as f64 / 1e9
+                );
+            }
+        }
+        out.push_str("# HELP cenayang_latency_samples Latency samples recorded per stage\n");
+        out.push_str("# TYPE cenayang_latency_samples gauge\n");
+        for (stage, m) in &stages {
+            let _ = writeln!(out, "cenayang_latency_samples{{stage=\"{}\"}} {}", stage, m.samples);
+        }
+        out
+    }
 }
 
 impl Default for ZeroBottleneckLatencyTracker {
@@ -659,6 +997,378 @@ impl Default for ZeroBottleneckLatencyTracker {
     }
 }
 
+/// Default cap on symbols with their own histograms (~96KB each)
+pub const MAX_TRACKED_SYMBOLS: usize = 64;
+
+/// One symbol's latency histograms and counters
+pub struct SymbolLatency {
+    ingestion_hist: LockFreeHistogram,
+    processing_hist: LockFreeHistogram,
+    publish_hist: LockFreeHistogram,
+    ticks_processed: AtomicU64,
+    gaps_detected: AtomicU64,
+}
+
+impl SymbolLatency {
+    fn new() -> Self {
+        Self {
+            ingestion_hist: LockFreeHistogram::new(0, 10_000_000),
+            processing_hist: LockFreeHistogram::new(0, 1_000_000),
+            publish_hist: LockFreeHistogram::new(0, 1_000_000),
+            ticks_processed: AtomicU64::new(0),
+            gaps_detected: AtomicU64::new(0),
+        }
+    }
+
+    pub fn ticks_processed(&self) -> u64 {
+        self.ticks_processed.load(Ordering::Relaxed)
+    }
+
+    pub fn gaps_detected(&self) -> u64 {
+        self.gaps_detected.load(Ordering::Relaxed)
+    }
+}
+
+/// Latency split by symbol, plus the aggregate across all of them
+///
+/// Every sample reaches the aggregate; symbols past the cap are counted
+/// as untracked instead of getting histograms of their own.
+pub struct PerSymbolLatencyTracker {
+    aggregate: ZeroBottleneckLatencyTracker,
+    symbols: HashMap<String, SymbolLatency>,
+    max_symbols: usize,
+    untracked_samples: AtomicU64,
+}
+
+impl PerSymbolLatencyTracker {
+    pub fn new(max_symbols: usize) -> Self {
+        Self::with_min_samples(max_symbols, 0)
+    }
+
+    pub fn with_min_samples(max_symbols: usize, min_samples: u64) -> Self {
+        Self {
+            aggregate: ZeroBottleneckLatencyTracker::with_min_samples(min_samples),
+            symbols: HashMap::with_capacity(max_symbols),
+            max_symbols,
+            untracked_samples: AtomicU64::new(0),
+        }
+    }
+
+    /// Symbol entry, created while under the cap - no allocation once present
+    fn symbol(&mut self, symbol: &str) -> Option<&SymbolLatency> {
+        if !self.symbols.contains_key(symbol) {
+            if self.symbols.len() >= self.max_symbols {
+                if self.untracked_samples.fetch_add(1, Ordering::Relaxed) == 0 {
+                    tracing::warn!(symbol, max_symbols = self.max_symbols, "per-symbol latency cap reached");
+                }
+                return None;
+            }
+            self.symbols.insert(symbol.to_string(), SymbolLatency::new());
+        }
+        self.symbols.get(symbol)
+    }
+
+    #[inline(always)]
+    pub fn record_ingestion(&mut self, symbol: &str, latency_ns: i64) {
+        self.aggregate.record_ingestion(latency_ns);
+        if let Some(s) = self.symbol(symbol) {
+            s.ingestion_hist.record(latency_ns);
+            s.ticks_processed.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    #[inline(always)]
+    pub fn record_processing(&mut self, symbol: &str, latency_ns: i64) {
+        self.aggregate.record_processing(latency_ns);
+        if let Some(s) = self.symbol(symbol) {
+            s.processing_hist.record(latency_ns);
+        }
+    }
+
+    #[inline(always)]
+    pub fn record_publish(&mut self, symbol: &str, latency_ns: i64) {
+        self.aggregate.record_publish(latency_ns);
+        if let Some(s) = self.symbol(symbol) {
+            s.publish_hist.record(latency_ns);
+        }
+    }
+
+    #[inline(always)]
+    pub fn increment_gaps(&mut self, symbol: &str) {
+        self.aggregate.increment_gaps();
+        if let Some(s) = self.symbol(symbol) {
+            s.gaps_detected.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    pub fn aggregate(&self) -> &ZeroBottleneckLatencyTracker {
+        &self.aggregate
+    }
+
+    pub fn get(&self, symbol: &str) -> Option<&SymbolLatency> {
+        self.symbols.get(symbol)
+    }
+
+    /// Samples recorded only in the aggregate because the cap was reached
+    pub fn untracked_samples(&self) -> u64 {
+        self.untracked_samples.load(Ordering::Relaxed)
+    }
+
+    /// Snapshot for one symbol (risk is not tracked per symbol)
+    pub fn snapshot(&self, symbol: &str) -> Option<LatencySnapshot> {
+        let min_samples = self.aggregate.min_samples;
+        self.symbols.get(symbol).map(|s| LatencySnapshot {
+            ingestion: MetricSnapshot::from_histogram(&s.ingestion_hist, min_samples),
+            processing: MetricSnapshot::from_histogram(&s.processing_hist, min_samples),
+            publish: MetricSnapshot::from_histogram(&s.publish_hist, min_samples),
+            risk: MetricSnapshot::default(),
+        })
+    }
+
+    /// Aggregate summary, then one line per symbol in name order
+    pub fn summary(&self) -> String {
+        let units = self.aggregate.units;
+        let mut names: Vec<&String> = self.symbols.keys().collect();
+        names.sort();
+
+        let mut out = self.aggregate.summary();
+        for name in names {
+            let (Some(s), Some(snap)) = (self.symbols.get(name), self.snapshot(name)) else {
+                continue;
+            };
+            out.push_str(&format!(
+                "\n[{}] Ticks:{} Gaps:{} | {} | {} | {}",
+                name,
+                s.ticks_processed(),
+                s.gaps_detected(),
+                ZeroBottleneckLatencyTracker::format_metric("Ingestion", &snap.ingestion, units.ingestion),
+                ZeroBottleneckLatencyTracker::format_metric("Processing", &snap.processing, units.processing),
+                ZeroBottleneckLatencyTracker::format_metric("Publish", &snap.publish, units.publish),
+            ));
+        }
+        out
+    }
+}
+
+// ============================================================================
+// SYNTHETIC LATENCY INJECTION - Staging/Test Only
+// ============================================================================
+
+/// Deployment environment variable; "production" forbids latency injection
+pub const DEPLOY_ENV: &str = "CENAYANG_ENV";
+
+/// Artificial delay added to a pipeline stage
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum LatencyInjection {
+    #[default]
+    Off,
+    Fixed { ns: u64 },
+    Uniform { min_ns: u64, max_ns: u64 },
+}
+
+/// Sleeps inside the processing/publish paths so SLA-breach, slow-tick and
+/// percentile handling can be exercised without real slow conditions
+pub struct LatencyInjector {
+    processing: LatencyInjection,
+    publish: LatencyInjection,
+    rng: StreamRng,
+}
+
+impl LatencyInjector {
+    /// Refuses any active injection when `environment` is production
+    pub fn new(
+        processing: LatencyInjection,
+        publish: LatencyInjection,
+        seed: u64,
+        environment: &str,
+    ) -> Result<Self, &'static str> {
+        let active = processing != LatencyInjection::Off || publish != LatencyInjection::Off;
+        if active && environment.eq_ignore_ascii_case("production") {
+            return Err("LATENCY_INJECTION_IN_PRODUCTION");
+        }
+        Ok(Self {
+            processing,
+            publish,
+            rng: StreamRng::new(seed),
+        })
+    }
+
+    /// `new` with the environment taken from `CENAYANG_ENV` (unset = production)
+    pub fn from_env(processing: LatencyInjection, publish: LatencyInjection, seed: u64) -> Result<Self, &'static str> {
+        let environment = std::env::var(DEPLOY_ENV).unwrap_or_else(|_| "production".to_string());
+        Self::new(processing, publish, seed, &environment)
+    }
+
+    fn inject(rng: &mut StreamRng, injection: LatencyInjection) -> Duration {
+        let ns = match injection {
+            LatencyInjection::Off => return Duration::ZERO,
+            LatencyInjection::Fixed { ns } => ns,
+            LatencyInjection::Uniform { min_ns, max_ns } => {
+                let span = max_ns.saturating_sub(min_ns);
+                min_ns + (rng.next_f64() * span as f64) as u64
+            }
+        };
+        let delay = Duration::from_nanos(ns);
+        std::thread::sleep(delay);
+        delay
+    }
+
+    /// Call inside the timed processing section - returns the delay added
+    #[inline(always)]
+    pub fn inject_processing(&mut self) -> Duration {
+        Self::inject(&mut self.rng, self.processing)
+    }
+
+    /// Call inside the timed publish section - returns the delay added
+    #[inline(always)]
+    pub fn inject_publish(&mut self) -> Duration {
+        Self::inject(&mut self.rng, self.publish)
+    }
+}
+
+// ============================================================================
+// TICK SIMULATOR - Seeded Fault Injection
+// ============================================================================
+
+/// Simulator fault knobs, e.g. "gap=0.01,dup=0.001,spike=0.05,crossed=0.001,seed=42"
+pub const SIM_FAULTS_ENV: &str = "CENAYANG_SIM_FAULTS";
+
+/// Per-tick fault probabilities for the simulated feed; all zero (the
+/// default) gives the old always-in-sync stream
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SimConfig {
+    pub gap_probability: f64,           // Skip a seq_id before the tick
+    pub duplicate_probability: f64,     // Repeat the previous seq_id
+    pub latency_spike_probability: f64,
+    pub latency_spike_ns: i32,          // latency_ns of a spiked tick
+    pub crossed_probability: f64,       // Bid above ask
+    pub base_latency_ns: i32,
+    pub half_spread: f64,               // Price units either side of last
+    pub seed: u64,
+}
+
+impl Default for SimConfig {
+    fn default() -> Self {
+        Self {
+            gap_probability: 0.0,
+            duplicate_probability: 0.0,
+            latency_spike_probability: 0.0,
+            latency_spike_ns: 5_000_000,    // 5ms
+            crossed_probability: 0.0,
+            base_latency_ns: 800,
+            half_spread: 0.5,
+            seed: DEFAULT_SEED,
+        }
+    }
+}
+
+impl SimConfig {
+    /// Comma-separated key=value overrides on top of the defaults
+    pub fn parse(spec: &str) -> Result<Self, &'static str> {
+        let mut config = Self::default();
+        for pair in spec.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            let (key, value) = pair.split_once('=').ok_or("SIM_FAULT_SYNTAX")?;
+            let probability = || match value.parse::<f64>() {
+                Ok(p) if (0.0..=1.0).contains(&p) => Ok(p),
+                _ => Err("SIM_FAULT_PROBABILITY"),
+            };
+            match key {
+                "gap" => config.gap_probability = probability()?,
+                "dup" => config.duplicate_probability = probability()?,
+                "spike" => config.latency_spike_probability = probability()?,
+                "crossed" => config.crossed_probability = probability()?,
+                "spike_ns" => config.latency_spike_ns = value.parse().map_err(|_| "SIM_FAULT_VALUE")?,
+                "seed" => config.seed = value.parse().map_err(|_| "SIM_FAULT_VALUE")?,
+                _ => return Err("SIM_FAULT_KEY"),
+            }
+        }
+        Ok(config)
+    }
+
+    pub fn from_env() -> Result<Self, &'static str> {
+        Self::parse(&std::env::var(SIM_FAULTS_ENV).unwrap_or_default())
+    }
+}
+
+/// Builds simulated ticks around a caller-supplied last price, injecting
+/// sequence gaps, duplicates, latency spikes and crossed quotes
+///
+/// Every fault is drawn on every tick in a fixed order from the
+/// "tick_sim" sub-stream, so a seed always reproduces the same stream.
+pub struct TickSimulator {
+    config: SimConfig,
+    rng: StreamRng,
+    next_seq: u64,
+    last_seq: Option<u64>,
+    pub gaps_injected: u64,
+    pub duplicates_injected: u64,
+    pub spikes_injected: u64,
+    pub crossed_injected: u64,
+}
+
+impl TickSimulator {
+    pub fn new(config: SimConfig) -> Self {
+        Self {
+            config,
+            rng: PipelineRng::new(config.seed).stream("tick_sim"),
+            next_seq: 0,
+            last_seq: None,
+            gaps_injected: 0,
+            duplicates_injected: 0,
+            spikes_injected: 0,
+            crossed_injected: 0,
+        }
+    }
+
+    pub fn next_tick(&mut self, symbol_hash: u64, last_price: f64, timestamp_ns: i64) -> MarketTickZeroCopy {
+        let c = self.config;
+        let gap = self.rng.next_f64() < c.gap_probability;
+        let duplicate = self.rng.next_f64() < c.duplicate_probability;
+        let spike = self.rng.next_f64() < c.latency_spike_probability;
+        let crossed = self.rng.next_f64() < c.crossed_probability;
+
+        let seq_id = match self.last_seq {
+            Some(last) if duplicate => {
+                self.duplicates_injected += 1;
+                last
+            }
+            _ => {
+                if gap {
+                    self.next_seq += 1;
+                    self.gaps_injected += 1;
+                }
+                let seq = self.next_seq;
+                self.next_seq += 1;
+                seq
+            }
+        };
+        self.last_seq = Some(seq_id);
+
+        let (mut bid, mut ask) = (last_price - c.half_spread, last_price + c.half_spread);
+        if crossed {
+            std::mem::swap(&mut bid, &mut ask);
+            self.crossed_injected += 1;
+        }
+        let latency_ns = if spike {
+            self.spikes_injected += 1;
+            c.latency_spike_ns
+        } else {
+            c.base_latency_ns
+        };
+
+        MarketTickZeroCopy {
+            symbol_hash,
+            bid_price: (bid * PRICE_SCALE) as i64,
+            ask_price: (ask * PRICE_SCALE) as i64,
+            last_price: (last_price * PRICE_SCALE) as i64,
+            timestamp_ns,
+            seq_id,
+            latency_ns,
+            ..Default::default()
+        }
+    }
+}
+
 // ============================================================================
 // BINARY PROTOCOL - Zero-Copy Serialization
 // ============================================================================
@@ -676,7 +1386,7 @@ pub struct BinaryHeader {
 }
 
 impl BinaryHeader {
-    pub const MAGIC: u32 = 0xCE_NA_YA_NG;
+    pub const MAGIC: u32 = 0xCE4A_7A46;
     pub const SIZE: usize = 24;
 
     #[inline(always)]
@@ -706,7 +1416,7 @@ impl<T: Copy + Default> BatchBroadcaster<T> {
     }
 
     #[inline(always)]
-    pub fn add(&self, event: T) -> bool {
+    pub fn add(&mut self, event: T) -> bool {
         let idx = self.count.fetch_add(1, Ordering::Relaxed) as usize;
         
         if idx >= BATCH_SIZE {
@@ -792,11 +1502,259 @@ impl Default for IdempotentExecution {
     }
 }
 
+// ============================================================================
+// TICK RECORD / REPLAY - Incident Reproduction
+// ============================================================================
+
+/// Tick source for the gateway: `sim` (default), `replay:<path>` or `live`
+pub const FEED_SOURCE_ENV: &str = "CENAYANG_FEED_SOURCE";
+/// Replay pacing: multiple of recorded time (2 = twice as fast), 0/unset = max speed
+pub const REPLAY_SPEED_ENV: &str = "CENAYANG_REPLAY_SPEED";
+/// Simulator ticks are also recorded to this path when set
+pub const RECORD_PATH_ENV: &str = "CENAYANG_RECORD_PATH";
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum FeedSource {
+    Simulator,
+    Replay(std::path::PathBuf),
+    Live,
+}
+
+impl FeedSource {
+    pub fn parse(value: &str) -> Result<Self, &'static str> {
+        match value {
+            "" | "sim" => Ok(FeedSource::Simulator),
+            "live" => Ok(FeedSource::Live),
+            _ => match value.strip_prefix("replay:") {
+                Some(path) if !path.is_empty() => Ok(FeedSource::Replay(path.into())),
+                _ => Err("UNKNOWN_FEED_SOURCE"),
+            },
+        }
+    }
+
+    pub fn from_env() -> Result<Self, &'static str> {
+        Self::parse(&std::env::var(FEED_SOURCE_ENV).unwrap_or_default())
+    }
+}
+
+/// Writes ticks as length-prefixed records: u32 LE length, then the
+/// tick's `to_bytes` image
+pub struct TickRecorder<W: std::io::Write> {
+    writer: W,
+    pub ticks_recorded: u64,
+}
+
+impl TickRecorder<std::io::BufWriter<std::fs::File>> {
+    pub fn create(path: impl AsRef<std::path::Path>) -> std::io::Result<Self> {
+        Ok(Self::new(std::io::BufWriter::new(std::fs::File::create(path)?)))
+    }
+}
+
+impl<W: std::io::Write> TickRecorder<W> {
+    pub fn new(writer: W) -> Self {
+        Self { writer, ticks_recorded: 0 }
+    }
+
+    pub fn record(&mut self, tick: &MarketTickZeroCopy) -> std::io::Result<()> {
+        self.writer.write_all(&(MarketTickZeroCopy::SIZE as u32).to_le_bytes())?;
+        self.writer.write_all(&tick.to_bytes())?;
+        self.ticks_recorded += 1;
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> std::io::Result<()> {
+        self.writer.flush()
+    }
+
+    /// Flushes and hands back the writer
+    pub fn into_inner(mut self) -> std::io::Result<W> {
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+/// Reads a TickRecorder file back in recorded order
+///
+/// Speed 0 (the default) replays as fast as possible; otherwise ticks are
+/// released at their recorded `timestamp_ns` offsets divided by `speed`,
+/// anchored to the first tick.
+pub struct TickReplayer<R: std::io::Read> {
+    reader: R,
+    speed: f64,
+    clock: Box<dyn ReplayClock>,
+    anchor: Option<(i64, i64)>,     // (first tick ts, clock at release)
+    pub ticks_replayed: u64,
+}
+
+impl TickReplayer<std::io::BufReader<std::fs::File>> {
+    pub fn open(path: impl AsRef<std::path::Path>) -> std::io::Result<Self> {
+        Ok(Self::new(std::io::BufReader::new(std::fs::File::open(path)?)))
+    }
+}
+
+impl<R: std::io::Read> TickReplayer<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            speed: 0.0,
+            clock: Box::new(SystemClock::default()),
+            anchor: None,
+            ticks_replayed: 0,
+        }
+    }
+
+    pub fn with_speed(mut self, speed: f64) -> Self {
+        self.speed = speed.max(0.0);
+        self
+    }
+
+    pub fn with_clock(mut self, clock: Box<dyn ReplayClock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    fn pace(&mut self, ts_ns: i64) {
+        if self.speed == 0.0 {
+            return;
+        }
+        let now = self.clock.now_ns();
+        let Some((first_ts, start)) = self.anchor else {
+            self.anchor = Some((ts_ns, now));
+            return;
+        };
+        let due = start + ((ts_ns - first_ts) as f64 / self.speed) as i64;
+        if due > now {
+            self.clock.sleep(Duration::from_nanos((due - now) as u64));
+        }
+    }
+
+    /// Next tick; None at a clean end of file, InvalidData on a record
+    /// that isn't a tick, UnexpectedEof on a truncated one
+    pub fn next_tick(&mut self) -> std::io::Result<Option<MarketTickZeroCopy>> {
+        let mut len = [0u8; 4];
+        match self.reader.read_exact(&mut len) {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+            Err(e) => return Err(e),
+        }
+        if u32::from_le_bytes(len) as usize != MarketTickZeroCopy::SIZE {
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, "TICK_RECORD_SIZE"));
+        }
+        let mut bytes = [0u8; MarketTickZeroCopy::SIZE];
+        self.reader.read_exact(&mut bytes)?;
+        let tick = MarketTickZeroCopy::from_bytes(&bytes);
+        self.pace(tick.timestamp_ns);
+        self.ticks_replayed += 1;
+        Ok(Some(tick))
+    }
+
+    /// Push every remaining tick into `tick_tx`, blocking on a full
+    /// channel so none are dropped - returns ticks sent
+    pub fn replay_into(&mut self, tick_tx: &Sender<MarketTickZeroCopy>) -> std::io::Result<u64> {
+        let mut sent = 0;
+        while let Some(tick) = self.next_tick()? {
+            if tick_tx.send(tick).is_err() {
+                break;
+            }
+            sent += 1;
+        }
+        Ok(sent)
+    }
+}
+
+/// Replay a recording into the tick channel and consume it on this
+/// thread - returns (ticks, seq_id regressions seen)
+fn run_replay(
+    replayer: TickReplayer<std::io::BufReader<std::fs::File>>,
+    latency: &ZeroBottleneckLatencyTracker,
+    tick_tx: Sender<MarketTickZeroCopy>,
+    tick_rx: &Receiver<MarketTickZeroCopy>,
+) -> std::io::Result<(u64, u64)> {
+    let mut replayer = replayer;
+    let producer = std::thread::spawn(move || replayer.replay_into(&tick_tx));
+
+    let (mut ticks, mut regressions, mut last_seq) = (0u64, 0u64, None);
+    for tick in tick_rx.iter() {
+        latency.record_ingestion(tick.latency_ns as i64);
+        if last_seq.is_some_and(|last| tick.seq_id < last) {
+            regressions += 1;
+        }
+        last_seq = Some(tick.seq_id);
+        ticks += 1;
+    }
+    producer.join().map_err(|_| std::io::Error::other("replay thread panicked"))??;
+    Ok((ticks, regressions))
+}
+
+// ============================================================================
+// METRICS ENDPOINT - Prometheus scrape target
+// ============================================================================
+
+/// Port for the /metrics endpoint; unset disables it
+pub const METRICS_PORT_ENV: &str = "CENAYANG_METRICS_PORT";
+/// Bind address for the /metrics endpoint, default loopback only
+pub const METRICS_BIND_ENV: &str = "CENAYANG_METRICS_BIND";
+
+/// Socket address for the /metrics endpoint from the env values
+/// An empty `bind` means 127.0.0.1; a bad port or address is an error
+/// rather than a fallback, so a typo never exposes the endpoint elsewhere
+pub fn metrics_addr(port: &str, bind: &str) -> Result<std::net::SocketAddr, &'static str> {
+    let port: u16 = port.trim().parse().map_err(|_| "INVALID_METRICS_PORT")?;
+    let ip: std::net::IpAddr = match bind.trim() {
+        "" => std::net::Ipv4Addr::LOCALHOST.into(),
+        bind => bind.parse().map_err(|_| "INVALID_METRICS_BIND")?,
+    };
+    Ok((ip, port).into())
+}
+
+/// Serve `GET /metrics` from a background thread - one request per connection
+pub fn serve_metrics(
+    listener: std::net::TcpListener,
+    latency: Arc<ZeroBottleneckLatencyTracker>,
+) -> std::thread::JoinHandle<()> {
+    use std::io::{Read, Write};
+
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let Ok(mut stream) = stream else { continue };
+            let _ = stream.set_read_timeout(Some(Duration::from_secs(1)));
+
+            // Only the request line matters; it may arrive over several reads
+            let mut request = [0u8; 1024];
+            let mut n = 0;
+            while n < request.len() && !request[..n].windows(2).any(|w| w == b"\r\n") {
+                match stream.read(&mut request[n..]) {
+                    Ok(0) | Err(_) => break,
+                    Ok(read) => n += read,
+                }
+            }
+            let response = if request[..n].starts_with(b"GET /metrics ") {
+                let body = latency.prometheus_text();
+                format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                )
+            } else {
+                "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string()
+            };
+            if let Err(e) = stream.write_all(response.as_bytes()) {
+                tracing::warn!(error = %e, "metrics response failed");
+            }
+        }
+    })
+}
+
 // ============================================================================
 // MAIN - Zero Bottleneck Entry Point
 // ============================================================================
 
 fn main() {
+    // LOG_FORMAT=json for structured logs, human-readable otherwise
+    if let Err(e) = logging::init(LogFormat::from_env()) {
+        eprintln!("[Init] Logging already initialised: {}", e);
+    }
+
     println!("╔═══════════════════════════════════════════════════════════════╗");
     println!("║  CENAYANG MARKET — Rust Zero-Bottleneck Edition v3.0          ║");
     println!("║  ═══════════════════════════════════════════════════════════  ║");
@@ -810,7 +1768,18 @@ fn main() {
 
     // Initialize zero-bottleneck components
     let latency = Arc::new(ZeroBottleneckLatencyTracker::new());
-    let tick_pool = Arc::new(ObjectPool::new(10000, || MarketTickZeroCopy::default()));
+    if let Ok(port) = std::env::var(METRICS_PORT_ENV) {
+        let bind = std::env::var(METRICS_BIND_ENV).unwrap_or_default();
+        match metrics_addr(&port, &bind).map(std::net::TcpListener::bind) {
+            Ok(Ok(listener)) => {
+                println!("[Init] Metrics: http://{}/metrics", listener.local_addr().map(|a| a.to_string()).unwrap_or_default());
+                serve_metrics(listener, Arc::clone(&latency));
+            }
+            Ok(Err(e)) => eprintln!("[Init] Metrics endpoint disabled: {}", e),
+            Err(e) => eprintln!("[Init] Metrics endpoint disabled: {} ({}={:?}, {}={:?})", e, METRICS_PORT_ENV, port, METRICS_BIND_ENV, bind),
+        }
+    }
+    let tick_pool = Arc::new(ObjectPool::new(10000, MarketTickZeroCopy::default));
     
     // Lock-free channels
     let (tick_tx, tick_rx): (Sender<MarketTickZeroCopy>, Receiver<MarketTickZeroCopy>) = 
@@ -822,6 +1791,64 @@ fn main() {
     println!("[Init] Sin/Cos LUT: 65536 entries");
     println!("[Init] Sharded orderbook: {} shards", NUM_SHARDS);
 
+    let source = match FeedSource::from_env() {
+        Ok(source) => source,
+        Err(e) => {
+            eprintln!("[Init] {}: {} (use sim, replay:<path> or live)", FEED_SOURCE_ENV, e);
+            return;
+        }
+    };
+    match source {
+        FeedSource::Simulator => {}
+        FeedSource::Live => {
+            eprintln!("[Init] Live exchange ingestion is not wired into this binary; use sim or replay:<path>");
+            return;
+        }
+        FeedSource::Replay(path) => {
+            let speed = std::env::var(REPLAY_SPEED_ENV).ok().and_then(|s| s.parse().ok()).unwrap_or(0.0);
+            let replayer = match TickReplayer::open(&path) {
+                Ok(replayer) => replayer.with_speed(speed),
+                Err(e) => {
+                    eprintln!("[Replay] Cannot open {}: {}", path.display(), e);
+                    return;
+                }
+            };
+            println!("\n[Replay] {} (speed {})", path.display(), if speed > 0.0 { format!("{}x", speed) } else { "max".into() });
+            let start = Instant::now();
+            match run_replay(replayer, &latency, tick_tx, &tick_rx) {
+                Ok((ticks, regressions)) => {
+                    println!("[Replay] {} ticks in {:?}, {} seq_id regressions", ticks, start.elapsed(), regressions);
+                }
+                Err(e) => eprintln!("[Replay] Stopped: {}", e),
+            }
+            println!("\n[Metrics] ═════════════════════════════════════════════════");
+            println!("{}", latency.summary());
+            return;
+        }
+    }
+
+    let mut simulator = match SimConfig::from_env() {
+        Ok(config) => TickSimulator::new(config),
+        Err(e) => {
+            eprintln!("[Init] {}: {}", SIM_FAULTS_ENV, e);
+            return;
+        }
+    };
+
+    let mut recorder = match std::env::var(RECORD_PATH_ENV) {
+        Ok(path) => match TickRecorder::create(&path) {
+            Ok(recorder) => {
+                println!("[Init] Recording ticks to {}", path);
+                Some(recorder)
+            }
+            Err(e) => {
+                eprintln!("[Init] Tick recording disabled: {}", e);
+                None
+            }
+        },
+        Err(_) => None,
+    };
+
     // Benchmark: 10 million operations
     println!("\n[Benchmark] Running 10,000,000 operations...");
     
@@ -834,7 +1861,7 @@ fn main() {
         let price = base_price + jitter;
         
         // Acquire from pool - zero allocation
-        let _tick = tick_pool.acquire(|| MarketTickZeroCopy::default());
+        let _tick = tick_pool.acquire(MarketTickZeroCopy::default);
         
         // Record latencies - lock-free atomic
         latency.record_ingestion(800);      // Simulated 800ns
@@ -843,17 +1870,23 @@ fn main() {
         
         // Non-blocking channel send
         if i % 100 == 0 {
-            let tick = MarketTickZeroCopy {
-                symbol_hash: SYMBOL_HASH_BTC,
-                last_price: (price * PRICE_SCALE) as i64,
-                seq_id: i,
-                ..Default::default()
-            };
+            let tick = simulator.next_tick(SYMBOL_HASH_BTC, price, start.elapsed().as_nanos() as i64);
+            if let Some(Err(e)) = recorder.as_mut().map(|r| r.record(&tick)) {
+                eprintln!("[Record] Stopped: {}", e);
+                recorder = None;
+            }
             let _ = tick_tx.try_send(tick);
         }
     }
     
     let elapsed = start.elapsed();
+    if let Some(recorder) = recorder {
+        let recorded = recorder.ticks_recorded;
+        match recorder.into_inner() {
+            Ok(_) => println!("[Record] {} ticks written", recorded),
+            Err(e) => eprintln!("[Record] Flush failed: {}", e),
+        }
+    }
     
     println!("\n[Performance] ═════════════════════════════════════════════");
     println!("[Performance] Processed 10,000,000 operations in {:?}", elapsed);
@@ -866,7 +1899,381 @@ fn main() {
     println!("\n[Verification] ════════════════════════════════════════════");
     println!("[Verification] Ring buffer capacity: {}", RING_BUFFER_SIZE);
     println!("[Verification] Pending ticks: {}", tick_rx.len());
+    println!(
+        "[Verification] Injected faults: gaps={} dups={} spikes={} crossed={}",
+        simulator.gaps_injected, simulator.duplicates_injected, simulator.spikes_injected, simulator.crossed_injected
+    );
     println!("[Verification] Pool stats: Acquired from pre-allocated pool");
     
     println!("\n✅ Zero Bottleneck Verified: No mutex locks, no heap allocations, no GC");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_percentiles_suppressed_below_min_samples() {
+        let tracker = ZeroBottleneckLatencyTracker::with_min_samples(100);
+        for _ in 0..12 {
+            tracker.record_ingestion(800);
+        }
+
+        let snap = tracker.snapshot();
+        assert_eq!(snap.ingestion.samples, 12);
+        assert_eq!(snap.ingestion.p99_ns, PERCENTILE_SUPPRESSED);
+        assert!(!snap.ingestion.is_reliable());
+        assert!(tracker.summary().contains("Ingestion: P50=n/a P99=n/a Mean=0.8μs (n=12"));
+
+        for _ in 0..88 {
+            tracker.record_ingestion(800);
+        }
+
+        let snap = tracker.snapshot();
+        assert_eq!(snap.ingestion.samples, 100);
+        assert!(snap.ingestion.is_reliable());
+        assert!(snap.ingestion.p99_ns >= 0);
+        assert!(tracker.summary().contains("(n=100)"));
+    }
+
+    #[test]
+    fn test_summary_renders_configured_units() {
+        let mut tracker = ZeroBottleneckLatencyTracker::new();
+        tracker.set_units(SummaryUnits {
+            publish: LatencyUnit::Nanos,
+            risk: LatencyUnit::Millis,
+            ..SummaryUnits::default()
+        });
+        tracker.record_ingestion(1_500);
+        tracker.record_publish(350);
+        tracker.record_risk(45_000);
+
+        let summary = tracker.summary();
+        assert!(summary.contains("Ingestion: P50=") && summary.contains("Mean=1.5μs"));
+        assert!(summary.contains("Mean=350ns"));
+        assert!(summary.contains("Mean=0.045ms"));
+
+        // Storage stays in nanoseconds
+        assert_eq!(tracker.snapshot().publish.mean_ns, 350);
+    }
+
+    #[test]
+    fn test_outlier_excluded_from_percentiles_but_counted() {
+        let mut tracker = ZeroBottleneckLatencyTracker::new();
+        tracker.set_outlier_guard(OutlierGuard::Mad { k: 10.0 });
+        for i in 0..200 {
+            tracker.record_processing(800 + (i % 50) * 8);
+        }
+        let before = tracker.snapshot().processing;
+
+        // 50ms scheduler hiccup
+        tracker.record_processing(50_000_000);
+
+        let after = tracker.snapshot().processing;
+        assert_eq!(tracker.latency_outliers(), 1);
+        assert_eq!(after, before);
+        assert!(after.p99_ns < 2_000);
+        assert!(tracker.summary().contains("Outliers:1"));
+
+        // Absolute ceiling applies from the first sample
+        let mut tracker = ZeroBottleneckLatencyTracker::new();
+        tracker.set_outlier_guard(OutlierGuard::AboveNs(5_000_000));
+        tracker.record_ingestion(50_000_000);
+        tracker.record_ingestion(1_000);
+        assert_eq!(tracker.latency_outliers(), 1);
+        assert_eq!(tracker.snapshot().ingestion.samples, 1);
+        assert_eq!(tracker.snapshot().ingestion.mean_ns, 1_000);
+    }
+
+    #[test]
+    fn test_injected_latency_shows_up_in_recorded_percentiles() {
+        let fixed = LatencyInjection::Fixed { ns: 300_000 };
+        let uniform = LatencyInjection::Uniform { min_ns: 200_000, max_ns: 400_000 };
+        assert_eq!(
+            LatencyInjector::new(fixed, LatencyInjection::Off, 7, "PRODUCTION").err(),
+            Some("LATENCY_INJECTION_IN_PRODUCTION")
+        );
+        assert!(LatencyInjector::new(LatencyInjection::Off, LatencyInjection::Off, 7, "production").is_ok());
+
+        let mut injector = LatencyInjector::new(fixed, uniform, 7, "staging").unwrap();
+        let tracker = ZeroBottleneckLatencyTracker::new();
+        for _ in 0..20 {
+            let start = Instant::now();
+            assert_eq!(injector.inject_processing(), Duration::from_micros(300));
+            tracker.record_processing(start.elapsed().as_nanos() as i64);
+
+            let start = Instant::now();
+            let added = injector.inject_publish();
+            assert!(added >= Duration::from_micros(200) && added <= Duration::from_micros(400));
+            tracker.record_publish(start.elapsed().as_nanos() as i64);
+        }
+
+        let snap = tracker.snapshot();
+        assert!(snap.processing.p50_ns >= 300_000, "{:?}", snap.processing);
+        assert!(snap.processing.mean_ns >= 300_000);
+        assert!(snap.publish.p50_ns >= 200_000, "{:?}", snap.publish);
+    }
+
+    /// Minimal exposition check: HELP/TYPE precede samples, values are numbers
+    fn assert_valid_exposition(text: &str) {
+        let mut typed: std::collections::HashMap<String, String> = Default::default();
+        for line in text.lines() {
+            if let Some(rest) = line.strip_prefix("# HELP ") {
+                assert!(rest.split_once(' ').is_some(), "{}", line);
+            } else if let Some(rest) = line.strip_prefix("# TYPE ") {
+                let (name, kind) = rest.split_once(' ').unwrap();
+                assert!(["counter", "gauge"].contains(&kind), "{}", line);
+                typed.insert(name.to_string(), kind.to_string());
+            } else {
+                let (series, value) = line.rsplit_once(' ').unwrap();
+                let name = series.split('{').next().unwrap();
+                assert!(name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_'), "{}", line);
+                assert!(typed.contains_key(name), "untyped sample {}", line);
+                assert!(value.parse::<f64>().is_ok(), "{}", line);
+                if let Some(labels) = series.strip_prefix(name) {
+                    assert!(labels.is_empty() || (labels.starts_with('{') && labels.ends_with('}')), "{}", line);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_prometheus_text_is_valid_exposition() {
+        let tracker = Arc::new(ZeroBottleneckLatencyTracker::with_min_samples(10));
+        for _ in 0..10 {
+            tracker.record_ingestion(800);
+        }
+        tracker.record_publish(350);
+        tracker.increment_gaps();
+
+        let text = tracker.prometheus_text();
+        assert_valid_exposition(&text);
+        assert!(text.contains("# TYPE cenayang_ticks_processed_total counter\ncenayang_ticks_processed_total 10\n"));
+        assert!(text.contains("cenayang_gaps_detected_total 1\n"));
+        assert!(text.contains("# TYPE cenayang_latency_seconds gauge"));
+        assert!(text.contains("cenayang_latency_seconds{stage=\"ingestion\",quantile=\"0.5\"}"));
+        // One publish sample is below min_samples: count only, no percentiles
+        assert!(!text.contains("stage=\"publish\",quantile"));
+        assert!(text.contains("cenayang_latency_samples{stage=\"publish\"} 1\n"));
+        assert!(!text.contains("cenayang_feed_reconnects_total"));
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        serve_metrics(listener, Arc::clone(&tracker));
+
+        let get = |path: &str| {
+            use std::io::{Read, Write};
+            let mut stream = std::net::TcpStream::connect(addr).unwrap();
+            stream.write_all(format!("GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", path).as_bytes()).unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).unwrap();
+            response
+        };
+        let response = get("/metrics");
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.ends_with(&text));
+        assert!(get("/health").starts_with("HTTP/1.1 404"));
+    }
+
+    #[test]
+    fn test_prometheus_text_exports_feed_reconnects() {
+        use cenayang_market_zero_bottleneck::feed::{FeedConfig, FeedRunner, FeedTransport};
+        use std::sync::atomic::AtomicBool;
+
+        // Drops the connection twice, then shuts the runner down
+        struct Flaky {
+            drops: u32,
+            shutdown: Arc<AtomicBool>,
+        }
+        impl FeedTransport for Flaky {
+            fn connect(&mut self) -> Result<(), &'static str> {
+                Ok(())
+            }
+
+            fn recv(&mut self) -> Result<Option<Vec<u8>>, &'static str> {
+                if self.drops == 0 {
+                    self.shutdown.store(true, Ordering::Release);
+                    return Ok(None);
+                }
+                self.drops -= 1;
+                Err("RESET")
+            }
+        }
+
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let transport = Flaky { drops: 2, shutdown: shutdown.clone() };
+        let mut runner = FeedRunner::new(transport, FeedConfig::default(), shutdown);
+        let mut tracker = ZeroBottleneckLatencyTracker::new();
+        tracker.set_feed_health(runner.health());
+        let (tx, _rx) = bounded(1);
+        assert_eq!(runner.run(&tx), Ok(()));
+
+        let text = tracker.prometheus_text();
+        assert_valid_exposition(&text);
+        assert!(text.contains("# TYPE cenayang_feed_reconnects_total counter\ncenayang_feed_reconnects_total 2\n"));
+    }
+
+    #[test]
+    fn test_metrics_addr_rejects_bad_port_and_defaults_to_loopback() {
+        assert_eq!(metrics_addr("9184", ""), Ok("127.0.0.1:9184".parse().unwrap()));
+        assert_eq!(metrics_addr("9184", "0.0.0.0"), Ok("0.0.0.0:9184".parse().unwrap()));
+        assert_eq!(metrics_addr("9184", "::1"), Ok("[::1]:9184".parse().unwrap()));
+        assert_eq!(metrics_addr("91o4", ""), Err("INVALID_METRICS_PORT"));
+        assert_eq!(metrics_addr("70000", ""), Err("INVALID_METRICS_PORT"));
+        assert_eq!(metrics_addr("9184", "localhost:1"), Err("INVALID_METRICS_BIND"));
+    }
+
+    #[test]
+    fn test_per_symbol_percentiles_are_independent() {
+        let mut tracker = PerSymbolLatencyTracker::new(2);
+        for i in 0..100 {
+            tracker.record_ingestion("BTCUSDT", 1_000 + i);
+            tracker.record_ingestion("ETHUSDT", 900_000 + i);
+        }
+        tracker.increment_gaps("ETHUSDT");
+
+        let btc = tracker.snapshot("BTCUSDT").unwrap().ingestion;
+        let eth = tracker.snapshot("ETHUSDT").unwrap().ingestion;
+        assert_eq!((btc.samples, eth.samples), (100, 100));
+        assert!(btc.p99_ns < 10_000, "{:?}", btc);
+        assert!(eth.p50_ns >= 890_000, "{:?}", eth);
+        assert_eq!(tracker.get("BTCUSDT").unwrap().gaps_detected(), 0);
+        assert_eq!(tracker.get("ETHUSDT").unwrap().gaps_detected(), 1);
+
+        // Aggregate sees both; a third symbol only reaches the aggregate
+        tracker.record_ingestion("SOLUSDT", 5_000);
+        assert!(tracker.get("SOLUSDT").is_none());
+        assert_eq!(tracker.untracked_samples(), 1);
+        assert_eq!(tracker.aggregate().snapshot().ingestion.samples, 201);
+
+        let summary = tracker.summary();
+        assert!(summary.starts_with("Ticks:201 "));
+        let btc_line = summary.lines().find(|l| l.starts_with("[BTCUSDT]")).unwrap();
+        assert!(btc_line.contains("Ticks:100 Gaps:0") && btc_line.contains("(n=100)"));
+        assert!(summary.lines().any(|l| l.starts_with("[ETHUSDT] Ticks:100 Gaps:1")));
+    }
+
+    /// Hand-cranked clock: sleeps advance time and are logged
+    struct StepClock {
+        now_ns: i64,
+        sleeps: Arc<std::sync::Mutex<Vec<i64>>>,
+    }
+
+    impl ReplayClock for StepClock {
+        fn now_ns(&self) -> i64 {
+            self.now_ns
+        }
+
+        fn sleep(&mut self, duration: Duration) {
+            self.now_ns += duration.as_nanos() as i64;
+            self.sleeps.lock().unwrap().push(duration.as_nanos() as i64);
+        }
+    }
+
+    fn recorded_ticks(n: u64) -> Vec<MarketTickZeroCopy> {
+        (0..n)
+            .map(|i| MarketTickZeroCopy {
+                symbol_hash: SYMBOL_HASH_BTC,
+                bid_price: 6_750_000_000_000 + i as i64,
+                ask_price: 6_750_001_000_000 + i as i64,
+                last_price: 6_750_000_500_000 - i as i64,
+                volume: (i * 7 % 13) as i64,
+                timestamp_ns: 1_700_000_000_000_000_000 + i as i64 * 1_000,
+                seq_id: 10 + i * 3,
+                latency_ns: (i % 900) as i32,
+                flags: i as u32,
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_tick_record_replay_round_trips_byte_identical() {
+        let ticks = recorded_ticks(1_000);
+        let mut recorder = TickRecorder::new(Vec::new());
+        for tick in &ticks {
+            recorder.record(tick).unwrap();
+        }
+        assert_eq!(recorder.ticks_recorded, 1_000);
+        let file = recorder.into_inner().unwrap();
+        assert_eq!(file.len(), 1_000 * (4 + MarketTickZeroCopy::SIZE));
+
+        let (tx, rx) = bounded(16);
+        let reader = std::thread::spawn(move || TickReplayer::new(file.as_slice()).replay_into(&tx).unwrap());
+        let replayed: Vec<MarketTickZeroCopy> = rx.iter().collect();
+        assert_eq!(reader.join().unwrap(), 1_000);
+
+        assert_eq!(replayed.len(), ticks.len());
+        for (got, want) in replayed.iter().zip(&ticks) {
+            assert_eq!(got.to_bytes(), want.to_bytes());
+        }
+        assert!(replayed.windows(2).all(|w| w[0].seq_id < w[1].seq_id));
+
+        // Truncated record and foreign record size are errors, not silent ends
+        let mut recorder = TickRecorder::new(Vec::new());
+        recorder.record(&ticks[0]).unwrap();
+        let mut bytes = recorder.into_inner().unwrap();
+        bytes.truncate(40);
+        assert!(TickReplayer::new(bytes.as_slice()).next_tick().is_err());
+        let foreign = [16u8, 0, 0, 0];
+        assert_eq!(
+            TickReplayer::new(&foreign[..]).next_tick().unwrap_err().kind(),
+            std::io::ErrorKind::InvalidData
+        );
+    }
+
+    #[test]
+    fn test_paced_tick_replay_follows_recorded_timestamps() {
+        let ticks = recorded_ticks(4);
+        let mut recorder = TickRecorder::new(Vec::new());
+        ticks.iter().for_each(|t| recorder.record(t).unwrap());
+        let file = recorder.into_inner().unwrap();
+
+        let sleeps = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let clock = StepClock { now_ns: 0, sleeps: Arc::clone(&sleeps) };
+        let mut replayer = TickReplayer::new(file.as_slice()).with_speed(2.0).with_clock(Box::new(clock));
+        while replayer.next_tick().unwrap().is_some() {}
+        // 1μs apart at 2x: 500ns each after the anchor tick
+        assert_eq!(*sleeps.lock().unwrap(), vec![500, 500, 500]);
+        assert_eq!(replayer.ticks_replayed, 4);
+
+        assert_eq!(FeedSource::parse("").unwrap(), FeedSource::Simulator);
+        assert_eq!(FeedSource::parse("replay:/tmp/t.bin").unwrap(), FeedSource::Replay("/tmp/t.bin".into()));
+        assert_eq!(FeedSource::parse("live").unwrap(), FeedSource::Live);
+        assert!(FeedSource::parse("replay:").is_err());
+    }
+
+    #[test]
+    fn test_tick_simulator_injects_seeded_faults() {
+        let always_gap = SimConfig { gap_probability: 1.0, ..SimConfig::default() };
+        let mut sim = TickSimulator::new(always_gap);
+        let seqs: Vec<u64> = (0..5).map(|i| sim.next_tick(SYMBOL_HASH_BTC, 100.0, i).seq_id).collect();
+        assert_eq!(seqs, vec![1, 3, 5, 7, 9]);
+        assert_eq!(sim.gaps_injected, 5);
+
+        // Defaults keep the stream in sync
+        let mut clean = TickSimulator::new(SimConfig::default());
+        let ticks: Vec<MarketTickZeroCopy> = (0..100).map(|i| clean.next_tick(SYMBOL_HASH_BTC, 100.0, i)).collect();
+        assert!(ticks.iter().enumerate().all(|(i, t)| t.seq_id == i as u64 && t.bid_price < t.ask_price && t.latency_ns == 800));
+
+        let config = SimConfig::parse("gap=0.1, dup=0.1, spike=0.2, crossed=0.05, seed=7").unwrap();
+        let run = || {
+            let mut sim = TickSimulator::new(config);
+            let ticks: Vec<[u8; MarketTickZeroCopy::SIZE]> =
+                (0..2_000).map(|i| sim.next_tick(SYMBOL_HASH_BTC, 100.0, i).to_bytes()).collect();
+            (ticks, sim.gaps_injected, sim.duplicates_injected, sim.spikes_injected, sim.crossed_injected)
+        };
+        let (ticks, gaps, dups, spikes, crossed) = run();
+        assert!(run() == (ticks.clone(), gaps, dups, spikes, crossed), "same seed, same stream");
+        assert!(gaps > 100 && dups > 100 && spikes > 300 && crossed > 50);
+
+        let decoded: Vec<MarketTickZeroCopy> = ticks.iter().map(MarketTickZeroCopy::from_bytes).collect();
+        assert_eq!(decoded.windows(2).filter(|w| w[1].seq_id == w[0].seq_id).count() as u64, dups);
+        assert_eq!(decoded.iter().filter(|t| t.bid_price > t.ask_price).count() as u64, crossed);
+        assert_eq!(decoded.iter().filter(|t| t.latency_ns == 5_000_000).count() as u64, spikes);
+
+        assert_eq!(SimConfig::parse("").unwrap(), SimConfig::default());
+        assert_eq!(SimConfig::parse("gap=1.5"), Err("SIM_FAULT_PROBABILITY"));
+        assert_eq!(SimConfig::parse("lag=0.1"), Err("SIM_FAULT_KEY"));
+    }
+}
@@ -3,20 +3,121 @@
 // Features:
 // - BTreeMap O(log n) price level operations
 // - Sequence gap detection with atomic counter
-// - Pre-computed price keys (fixed-point)
+// - Explicit prev-seq continuity checks (Binance `pu`)
+// - Pre-computed price keys (fixed-point, per-book tick scale)
+// - Unrepresentable prices rejected instead of saturating the key
+// - Price/Qty newtypes so positional arguments can't be swapped
+// - Integer quantity units: fixed-point or instrument lots
+// - Optional per-level update times with stale level expiry
 // - Cache-line aligned for false sharing prevention
+// - Bounded mid/microprice history ring
+// - Snapshot scheduling by update count and/or time
+// - Rate-limited on-demand snapshots for late joiners
+// - Idempotent snapshot application (colliding price keys merged)
+// - Decaying per-level trade activity (bounded memory)
+// - Threshold tick filter ahead of book application
+// - Top-N diff snapshots (only changed levels)
+// - Periodic reconciliation against reference snapshots (state hash)
+// - Multi-symbol manager with portfolio liquidity aggregates
+// - Books created on first sight of a symbol, stats summed across books
+// - Top-of-book-only (BBO) mode for symbols that need no depth
+// - Exchange CRC32 checksum of the top 25 levels (OKX layout)
+// - Depth imbalance over the top N levels
+// - Size-weighted mid and bid/ask book pressure over the top N levels
+// - Price-bucketed depth aggregation with CSV export (heatmaps)
+// - VWAP to fill a quantity (partial last level pro-rated)
+// - Crossed-book detection with pluggable heal (drop level / resync)
+// - Snapshots persisted to disk for warm restarts (seq preserved)
+// - Top-of-book change events: only deltas/snapshots that move the BBO
+// - Optional cap on levels per side; the levels furthest from the touch go
+// - Property tests over random snapshot/delta feeds (proptests.rs)
 
 pub mod orderbook {
-    use std::collections::BTreeMap;
+    use crossbeam_channel::Sender;
+    use serde::{Deserialize, Serialize};
+    use std::collections::{BTreeMap, HashMap};
+    use std::ops::{Add, Div, Mul, Sub};
+    use std::path::Path;
     use std::sync::atomic::{AtomicU64, Ordering};
 
     /// Price precision: 1e8 = 8 decimal places
     pub const PRICE_SCALE: f64 = 100_000_000.0;
 
-    /// Convert float price to fixed-point key
+    /// Price in quote currency - serializes as a bare number
+    ///
+    /// Distinct from `Qty`, so swapped arguments fail to compile:
+    ///
+    /// ```compile_fail
+    /// use cenayang_market_zero_bottleneck::orderbook::{L2Orderbook, Price, Qty};
+    /// let mut book = L2Orderbook::new(1);
+    /// book.apply_delta(Qty(1.0), Price(100.0), true, 1);
+    /// ```
+    #[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd, Serialize, Deserialize)]
+    #[serde(transparent)]
+    pub struct Price(pub f64);
+
+    /// Quantity in base units - serializes as a bare number
+    #[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd, Serialize, Deserialize)]
+    #[serde(transparent)]
+    pub struct Qty(pub f64);
+
+    /// Same-unit add/sub and scaling by a plain factor
+    macro_rules! unit_ops {
+        ($($ty:ident),*) => {$(
+            impl Add for $ty {
+                type Output = $ty;
+                fn add(self, rhs: $ty) -> $ty {
+                    $ty(self.0 + rhs.0)
+                }
+            }
+
+            impl Sub for $ty {
+                type Output = $ty;
+                fn sub(self, rhs: $ty) -> $ty {
+                    $ty(self.0 - rhs.0)
+                }
+            }
+
+            impl Mul<f64> for $ty {
+                type Output = $ty;
+                fn mul(self, rhs: f64) -> $ty {
+                    $ty(self.0 * rhs)
+                }
+            }
+
+            impl Div<f64> for $ty {
+                type Output = $ty;
+                fn div(self, rhs: f64) -> $ty {
+                    $ty(self.0 / rhs)
+                }
+            }
+        )*};
+    }
+
+    unit_ops!(Price, Qty);
+
+    /// Price x quantity = notional (quote currency)
+    impl Mul<Qty> for Price {
+        type Output = f64;
+        fn mul(self, rhs: Qty) -> f64 {
+            self.0 * rhs.0
+        }
+    }
+
+    /// Convert float price to fixed-point key (rounded, never truncated)
+    /// Prices closer than 1/PRICE_SCALE share a key and become one level
     #[inline(always)]
     pub fn price_to_key(price: f64) -> i64 {
-        (price * PRICE_SCALE) as i64
+        (price * PRICE_SCALE).round() as i64
+    }
+
+    /// Price -> key at `tick_scale` keys per 1.0; None when the scaled price
+    /// isn't finite or doesn't fit in i64 (a plain cast would saturate)
+    #[inline(always)]
+    pub fn checked_price_to_key(price: f64, tick_scale: i64) -> Option<i64> {
+        let scaled = (price * tick_scale as f64).round();
+        // i64::MAX as f64 rounds up to 2^63, itself out of range
+        (scaled.is_finite() && scaled.abs() < i64::MAX as f64).then_some(scaled as i64)
     }
 
     /// Convert fixed-point key to float price
@@ -25,64 +126,549 @@ pub mod orderbook {
         key as f64 / PRICE_SCALE
     }
 
+    /// FNV-1a hash of a symbol name - matches the gateway's symbol hashes
+    #[inline(always)]
+    pub fn symbol_hash(symbol: &str) -> u64 {
+        let mut hash: u64 = 14695981039346656037;
+        for byte in symbol.bytes() {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(1099511628211);
+        }
+        hash
+    }
+
+    /// Price levels as (price, quantity) pairs, best first
+    pub type Levels = Vec<(f64, f64)>;
+
+    /// `side,price,qty` CSV of both sides (bids first) for spreadsheets
+    pub fn depth_csv(bids: &[(f64, f64)], asks: &[(f64, f64)]) -> String {
+        let mut csv = String::from("side,price,qty\n");
+        for (side, levels) in [("bid", bids), ("ask", asks)] {
+            for (price, qty) in levels {
+                csv.push_str(&format!("{},{},{}\n", side, price, qty));
+            }
+        }
+        csv
+    }
+
+    /// Levels per side covered by exchange checksums
+    pub const CHECKSUM_DEPTH: usize = 25;
+
+    /// CRC-32 (IEEE 802.3, reflected 0xEDB88320) lookup table
+    const CRC32_TABLE: [u32; 256] = {
+        let mut table = [0u32; 256];
+        let mut i = 0;
+        while i < 256 {
+            let mut crc = i as u32;
+            let mut bit = 0;
+            while bit < 8 {
+                crc = if crc & 1 != 0 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 };
+                bit += 1;
+            }
+            table[i] = crc;
+            i += 1;
+        }
+        table
+    };
+
+    /// CRC-32 as used by zlib and exchange book checksums - O(n)
+    pub fn crc32(bytes: &[u8]) -> u32 {
+        !bytes.iter().fold(!0u32, |crc, &b| (crc >> 8) ^ CRC32_TABLE[((crc ^ b as u32) & 0xFF) as usize])
+    }
+
+    /// Full book snapshot as (price, quantity) levels
+    #[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+    pub struct OrderbookSnapshot {
+        pub symbol_hash: u64,
+        pub seq_id: u64,
+        pub bids: Levels,
+        pub asks: Levels,
+    }
+
+    /// Result of walking one side of the book for a quantity
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub struct DepthFill {
+        pub avg_price: f64,     // Volume-weighted over what was fillable
+        pub filled_qty: f64,    // < requested when the side ran out
+    }
+
+    /// Last-update time (ns) per price key, one map per side
+    #[derive(Debug, Default)]
+    pub struct LevelTimes {
+        pub bids: HashMap<i64, i64>,
+        pub asks: HashMap<i64, i64>,
+    }
+
+    /// Result of applying one delta
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub enum DeltaOutcome {
+        Applied,
+        Gap { expected: u64, got: u64 },    // Book and seq untouched
+        Crossed,                            // Seq advanced, healed per `cross_heal`
+        InvalidPrice,                       // Book and seq untouched
+        Filtered,                           // Seq advanced, book untouched
+    }
+
+    impl DeltaOutcome {
+        #[inline(always)]
+        pub fn is_applied(&self) -> bool {
+            matches!(self, DeltaOutcome::Applied)
+        }
+    }
+
+    /// How `apply_delta` heals an insert that crosses the opposite side
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub enum CrossHeal {
+        DropLevel,          // Discard the offending level, keep the seq moving
+        RequestSnapshot,    // Apply it, flag the book until the next resync
+    }
+
+    /// Best bid/ask after a change at the touch
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub struct BboUpdate {
+        pub symbol_hash: u64,
+        pub bid: Option<f64>,   // None = side empty
+        pub bid_size: f64,
+        pub ask: Option<f64>,
+        pub ask_size: f64,
+        pub seq_id: u64,
+        pub ts_ns: i64,         // Unix time the change was detected
+    }
+
+    /// (price key, quantity units) at the touch, per side
+    type Touch = (Option<(i64, i64)>, Option<(i64, i64)>);
+
     /// L2 Orderbook with sequence tracking
     pub struct L2Orderbook {
         pub symbol_hash: u64,
-        pub bids: BTreeMap<i64, i64>,  // price_key -> quantity (fixed-point)
+        pub bids: BTreeMap<i64, i64>,  // price_key -> quantity units
         pub asks: BTreeMap<i64, i64>,
+        pub qty_scale: f64,            // Units per 1.0 qty: PRICE_SCALE or 1/lot_size
+        pub tick_scale: i64,           // Price keys per 1.0 price, default PRICE_SCALE
         pub last_seq_id: AtomicU64,
         pub total_updates: AtomicU64,
         pub gaps_detected: AtomicU64,
+        pub duplicate_snapshots: AtomicU64,
+        pub merged_levels: AtomicU64,           // Snapshot prices that collided on a key
+        pub level_times: Option<LevelTimes>,   // Only kept when expiry is enabled
+        pub expired_levels: AtomicU64,
+        pub invalid_prices: AtomicU64,          // Deltas/levels rejected by key overflow
+        pub cross_heal: Option<CrossHeal>,      // None = crossed inserts not checked
+        pub crossed_updates: AtomicU64,
+        pub checksum_mismatches: AtomicU64,
+        pub gaps_resynced: AtomicU64,           // Resyncs that closed an open gap
+        pub bbo_events_dropped: AtomicU64,      // BBO channel full or closed
+        pub max_levels_per_side: Option<usize>, // None = unbounded
+        pub trimmed_levels: AtomicU64,          // Dropped to stay within the cap
+        resync_requested: bool,
+        gap_pending: bool,
+        bbo_tx: Option<Sender<BboUpdate>>,
+        last_touch: Touch,
     }
 
     impl L2Orderbook {
         pub fn new(symbol_hash: u64) -> Self {
+            Self::with_qty_scale(symbol_hash, PRICE_SCALE)
+        }
+
+        /// Book storing quantities as whole instrument lots - sums stay exact
+        /// None unless `lot_size` is positive and finite
+        pub fn with_lot_size(symbol_hash: u64, lot_size: f64) -> Option<Self> {
+            let qty_scale = 1.0 / lot_size;
+            (lot_size > 0.0 && lot_size.is_finite() && qty_scale.is_finite()).then(|| Self::with_qty_scale(symbol_hash, qty_scale))
+        }
+
+        fn with_qty_scale(symbol_hash: u64, qty_scale: f64) -> Self {
             Self {
                 symbol_hash,
                 bids: BTreeMap::new(),
                 asks: BTreeMap::new(),
+                qty_scale,
+                tick_scale: PRICE_SCALE as i64,
                 last_seq_id: AtomicU64::new(0),
                 total_updates: AtomicU64::new(0),
                 gaps_detected: AtomicU64::new(0),
+                duplicate_snapshots: AtomicU64::new(0),
+                merged_levels: AtomicU64::new(0),
+                level_times: None,
+                expired_levels: AtomicU64::new(0),
+                invalid_prices: AtomicU64::new(0),
+                cross_heal: None,
+                crossed_updates: AtomicU64::new(0),
+                checksum_mismatches: AtomicU64::new(0),
+                gaps_resynced: AtomicU64::new(0),
+                bbo_events_dropped: AtomicU64::new(0),
+                max_levels_per_side: None,
+                trimmed_levels: AtomicU64::new(0),
+                resync_requested: false,
+                gap_pending: false,
+                bbo_tx: None,
+                last_touch: (None, None),
             }
         }
 
+        /// Check every insert against the opposite touch and heal crosses
+        pub fn with_cross_heal(mut self, heal: CrossHeal) -> Self {
+            self.cross_heal = Some(heal);
+            self
+        }
+
+        /// Send a BboUpdate whenever a delta or snapshot changes the best
+        /// bid/ask price or size; deeper changes send nothing. Never blocks:
+        /// a full channel drops the event (`bbo_events_dropped`).
+        pub fn with_bbo_events(mut self, bbo_tx: Sender<BboUpdate>) -> Self {
+            self.last_touch = self.touch();
+            self.bbo_tx = Some(bbo_tx);
+            self
+        }
+
+        /// Bound each side to `max_levels` so a hostile feed can't grow the
+        /// maps without limit; inserts past the cap drop the level furthest
+        /// from the touch (lowest bid / highest ask)
+        pub fn with_max_levels_per_side(mut self, max_levels: usize) -> Self {
+            self.max_levels_per_side = Some(max_levels.max(1));
+            self
+        }
+
+        /// Price keys per 1.0 price - lower for very high prices (indices),
+        /// higher for sub-satoshi tokens. Set before the book is populated.
+        pub fn with_tick_scale(mut self, tick_scale: i64) -> Self {
+            self.tick_scale = tick_scale.max(1);
+            self
+        }
+
+        /// Price -> key at this book's tick scale; None if unrepresentable
+        #[inline(always)]
+        pub fn price_to_key(&self, price: f64) -> Option<i64> {
+            checked_price_to_key(price, self.tick_scale)
+        }
+
+        #[inline(always)]
+        pub fn key_to_price(&self, key: i64) -> f64 {
+            key as f64 / self.tick_scale as f64
+        }
+
+        /// Track per-level update times so stale levels can be expired
+        pub fn with_level_expiry(mut self) -> Self {
+            self.level_times = Some(LevelTimes::default());
+            self
+        }
+
+        /// Convert a quantity to integer book units (rounded, never truncated)
+        #[inline(always)]
+        pub fn qty_to_units(&self, qty: f64) -> i64 {
+            (qty * self.qty_scale).round() as i64
+        }
+
+        /// Convert integer book units back to a quantity
+        #[inline(always)]
+        pub fn units_to_qty(&self, units: i64) -> f64 {
+            units as f64 / self.qty_scale
+        }
+
+        /// Total resting quantity on one side - summed in exact units
+        pub fn total_qty(&self, is_bid: bool) -> f64 {
+            let side = if is_bid { &self.bids } else { &self.asks };
+            self.units_to_qty(side.values().sum())
+        }
+
         /// Apply price level delta - O(log n)
-        /// Returns false if sequence gap detected
+        /// Unkeyable prices are counted in `invalid_prices`; crossing inserts
+        /// (only checked with `cross_heal` set) in `crossed_updates`
         #[inline(always)]
-        pub fn apply_delta(&mut self, price: f64, qty: f64, is_bid: bool, seq_id: u64) -> bool {
+        pub fn apply_delta(&mut self, Price(price): Price, Qty(qty): Qty, is_bid: bool, seq_id: u64) -> DeltaOutcome {
             // Sequence gap detection
             let last = self.last_seq_id.load(Ordering::Relaxed);
             if last > 0 && seq_id != last + 1 {
                 self.gaps_detected.fetch_add(1, Ordering::Relaxed);
-                return false;
+                self.gap_pending = true;
+                return DeltaOutcome::Gap { expected: last + 1, got: seq_id };
+            }
+            self.write_level(price, qty, is_bid, seq_id)
+        }
+
+        /// Apply a delta unless `filter` drops it - O(log n)
+        /// A dropped delta still takes its place in the sequence, so the next
+        /// delta isn't reported as a gap.
+        pub fn apply_filtered_delta(
+            &mut self,
+            filter: &mut TickFilter,
+            price: Price,
+            qty: Qty,
+            is_bid: bool,
+            seq_id: u64,
+        ) -> DeltaOutcome {
+            let last = self.last_seq_id.load(Ordering::Relaxed);
+            if last > 0 && seq_id != last + 1 {
+                return self.apply_delta(price, qty, is_bid, seq_id);
+            }
+            if !filter.accept(self, price, qty, is_bid) {
+                self.last_seq_id.store(seq_id, Ordering::Relaxed);
+                return DeltaOutcome::Filtered;
+            }
+            self.apply_delta(price, qty, is_bid, seq_id)
+        }
+
+        /// Apply a delta whose message carries the previous update's seq
+        /// (Binance diff depth `pu`) - O(log n)
+        /// Continuity requires `prev_seq == last_seq_id`, so venues stepping
+        /// seq by more than 1 per message aren't flagged as gaps. Without a
+        /// `prev_seq` this is `apply_delta`. A gap reports the expected and
+        /// received `prev_seq`.
+        #[inline(always)]
+        pub fn apply_delta_with_prev(
+            &mut self,
+            Price(price): Price,
+            Qty(qty): Qty,
+            is_bid: bool,
+            prev_seq: Option<u64>,
+            seq_id: u64,
+        ) -> DeltaOutcome {
+            let Some(prev_seq) = prev_seq else {
+                return self.apply_delta(Price(price), Qty(qty), is_bid, seq_id);
+            };
+            let last = self.last_seq_id.load(Ordering::Relaxed);
+            if last > 0 && prev_seq != last {
+                self.gaps_detected.fetch_add(1, Ordering::Relaxed);
+                self.gap_pending = true;
+                return DeltaOutcome::Gap { expected: last, got: prev_seq };
             }
+            self.write_level(price, qty, is_bid, seq_id)
+        }
 
-            let key = price_to_key(price);
-            let qty_fixed = (qty * PRICE_SCALE) as i64;
+        /// Set or delete a level and advance the seq - no continuity check
+        #[inline(always)]
+        fn write_level(&mut self, price: f64, qty: f64, is_bid: bool, seq_id: u64) -> DeltaOutcome {
+            let Some(key) = self.price_to_key(price) else {
+                self.invalid_prices.fetch_add(1, Ordering::Relaxed);
+                return DeltaOutcome::InvalidPrice;
+            };
+            let qty_fixed = self.qty_to_units(qty);
+            let crossed = qty_fixed > 0 && self.cross_heal.is_some() && self.crosses(key, is_bid);
             let book = if is_bid { &mut self.bids } else { &mut self.asks };
 
             if qty_fixed <= 0 {
                 book.remove(&key);
-            } else {
-                book.insert(key, qty_fixed);
+            } else if !(crossed && self.cross_heal == Some(CrossHeal::DropLevel)) {
+                // Only a new level can push the side over the cap
+                if book.insert(key, qty_fixed).is_none() {
+                    self.trim_side(is_bid);
+                }
             }
 
             self.last_seq_id.store(seq_id, Ordering::Relaxed);
             self.total_updates.fetch_add(1, Ordering::Relaxed);
+            self.publish_bbo(seq_id);
+            if crossed {
+                self.crossed_updates.fetch_add(1, Ordering::Relaxed);
+                self.resync_requested |= self.cross_heal == Some(CrossHeal::RequestSnapshot);
+                tracing::warn!(symbol_hash = self.symbol_hash, seq_id, price, is_bid, "delta crossed the book");
+                return DeltaOutcome::Crossed;
+            }
+            DeltaOutcome::Applied
+        }
+
+        /// Drop far levels until the side fits `max_levels_per_side` - O(k log n)
+        fn trim_side(&mut self, is_bid: bool) -> usize {
+            let Some(max_levels) = self.max_levels_per_side else {
+                return 0;
+            };
+            let side = if is_bid { &mut self.bids } else { &mut self.asks };
+            let mut trimmed = 0;
+            while side.len() > max_levels {
+                let far = if is_bid { side.pop_first() } else { side.pop_last() };
+                if let (Some((key, _)), Some(times)) = (far, self.level_times.as_mut()) {
+                    let side_times = if is_bid { &mut times.bids } else { &mut times.asks };
+                    side_times.remove(&key);
+                }
+                trimmed += 1;
+            }
+            if trimmed > 0 {
+                self.trimmed_levels.fetch_add(trimmed as u64, Ordering::Relaxed);
+                tracing::warn!(symbol_hash = self.symbol_hash, is_bid, trimmed, max_levels, "book side over level cap, far levels dropped");
+            }
+            trimmed
+        }
+
+        #[inline(always)]
+        fn touch(&self) -> Touch {
+            (
+                self.bids.iter().next_back().map(|(&k, &q)| (k, q)),
+                self.asks.iter().next().map(|(&k, &q)| (k, q)),
+            )
+        }
+
+        /// Emit a BboUpdate if the touch moved since the last one - O(log n)
+        #[inline(always)]
+        fn publish_bbo(&mut self, seq_id: u64) {
+            if self.bbo_tx.is_none() {
+                return;
+            }
+            let touch = self.touch();
+            if touch == self.last_touch {
+                return;
+            }
+            self.last_touch = touch;
+            let level = |side: Option<(i64, i64)>| match side {
+                Some((key, units)) => (Some(self.key_to_price(key)), self.units_to_qty(units)),
+                None => (None, 0.0),
+            };
+            let ((bid, bid_size), (ask, ask_size)) = (level(touch.0), level(touch.1));
+            let update = BboUpdate {
+                symbol_hash: self.symbol_hash,
+                bid,
+                bid_size,
+                ask,
+                ask_size,
+                seq_id,
+                ts_ns: std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .map_or(0, |d| d.as_nanos() as i64),
+            };
+            if self.bbo_tx.as_ref().is_some_and(|tx| tx.try_send(update).is_err()) {
+                self.bbo_events_dropped.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        /// Would a level at `key` on this side lock or cross the other side
+        #[inline(always)]
+        fn crosses(&self, key: i64, is_bid: bool) -> bool {
+            if is_bid {
+                self.asks.keys().next().is_some_and(|&ask| key >= ask)
+            } else {
+                self.bids.keys().next_back().is_some_and(|&bid| key <= bid)
+            }
+        }
+
+        /// Best bid at or above best ask - O(log n)
+        pub fn is_crossed(&self) -> bool {
+            match (self.bids.keys().next_back(), self.asks.keys().next()) {
+                (Some(bid), Some(ask)) => bid >= ask,
+                _ => false,
+            }
+        }
+
+        /// A delta gapped and no snapshot has been applied since
+        pub fn has_open_gap(&self) -> bool {
+            self.gap_pending
+        }
+
+        /// A `RequestSnapshot` heal fired; cleared by the next resync
+        pub fn needs_resync(&self) -> bool {
+            self.resync_requested
+        }
+
+        /// Apply a delta and stamp the level with its update time - O(log n)
+        pub fn apply_delta_at(
+            &mut self,
+            Price(price): Price,
+            Qty(qty): Qty,
+            is_bid: bool,
+            seq_id: u64,
+            ts_ns: i64,
+        ) -> DeltaOutcome {
+            let outcome = self.apply_delta(Price(price), Qty(qty), is_bid, seq_id);
+            if !outcome.is_applied() {
+                return outcome;
+            }
+            // Stamp only a level still in the book; one trimmed by the level
+            // cap or dropped by cross healing must not keep a stamp
+            let key = self.price_to_key(price);
+            if let (Some(times), Some(key)) = (self.level_times.as_mut(), key) {
+                let live = if is_bid { self.bids.contains_key(&key) } else { self.asks.contains_key(&key) };
+                let side = if is_bid { &mut times.bids } else { &mut times.asks };
+                if live {
+                    side.insert(key, ts_ns);
+                } else {
+                    side.remove(&key);
+                }
+            }
+            outcome
+        }
+
+        /// Remove levels not refreshed within `max_age_ns` - O(n)
+        /// Levels never stamped (e.g. from a snapshot) start aging now.
+        /// Returns the number of levels removed; no-op without expiry enabled.
+        pub fn expire_stale_levels(&mut self, now_ns: i64, max_age_ns: i64) -> usize {
+            let Some(times) = self.level_times.as_mut() else {
+                return 0;
+            };
+            let removed = expire_side(&mut self.bids, &mut times.bids, now_ns, max_age_ns)
+                + expire_side(&mut self.asks, &mut times.asks, now_ns, max_age_ns);
+            self.expired_levels.fetch_add(removed as u64, Ordering::Relaxed);
+            if removed > 0 {
+                self.publish_bbo(self.last_seq_id.load(Ordering::Relaxed));
+            }
+            removed
+        }
+
+        /// Rebuild the book from a full snapshot - O(n log n)
+        /// Returns false if skipped: a retried snapshot with the seq already
+        /// applied to a populated book is a no-op.
+        /// Distinct prices that map to one key are summed, never overwritten,
+        /// so no snapshot liquidity is lost (counted in `merged_levels`).
+        pub fn apply_snapshot(&mut self, snapshot: &OrderbookSnapshot) -> bool {
+            let last = self.last_seq_id.load(Ordering::Relaxed);
+            let populated = !self.bids.is_empty() || !self.asks.is_empty();
+            if populated && snapshot.seq_id == last {
+                self.duplicate_snapshots.fetch_add(1, Ordering::Relaxed);
+                return false;
+            }
+            self.resync(snapshot);
             true
         }
 
+        /// Unconditionally rebuild from a snapshot - O(n log n)
+        /// Unlike `apply_snapshot`, a snapshot at the current seq still rebuilds
+        pub fn resync(&mut self, snapshot: &OrderbookSnapshot) {
+            self.clear();
+            self.resync_requested = false;
+            if std::mem::take(&mut self.gap_pending) {
+                self.gaps_resynced.fetch_add(1, Ordering::Relaxed);
+            }
+            let (mut merged, mut invalid) = (0, 0);
+            for (levels, is_bid) in [(&snapshot.bids, true), (&snapshot.asks, false)] {
+                for &(price, qty) in levels {
+                    let qty_fixed = self.qty_to_units(qty);
+                    if qty_fixed <= 0 {
+                        continue;
+                    }
+                    let Some(key) = self.price_to_key(price) else {
+                        invalid += 1;
+                        continue;
+                    };
+                    let side = if is_bid { &mut self.bids } else { &mut self.asks };
+                    let level = side.entry(key).or_insert(0);
+                    if *level > 0 {
+                        merged += 1;
+                    }
+                    *level += qty_fixed;
+                }
+            }
+            if merged > 0 {
+                self.merged_levels.fetch_add(merged, Ordering::Relaxed);
+            }
+            if invalid > 0 {
+                self.invalid_prices.fetch_add(invalid, Ordering::Relaxed);
+            }
+
+            self.trim_side(true);
+            self.trim_side(false);
+            self.last_seq_id.store(snapshot.seq_id, Ordering::Relaxed);
+            self.total_updates.store(0, Ordering::Relaxed);
+            self.publish_bbo(snapshot.seq_id);
+        }
+
         /// Get best bid price - O(log n)
         #[inline(always)]
         pub fn best_bid(&self) -> Option<f64> {
-            self.bids.keys().next_back().map(|&k| key_to_price(k))
+            self.bids.keys().next_back().map(|&k| self.key_to_price(k))
         }
 
         /// Get best ask price - O(log n)
         #[inline(always)]
         pub fn best_ask(&self) -> Option<f64> {
-            self.asks.keys().next().map(|&k| key_to_price(k))
+            self.asks.keys().next().map(|&k| self.key_to_price(k))
         }
 
         /// Get mid price - O(log n)
@@ -94,6 +680,60 @@ pub mod orderbook {
             }
         }
 
+        /// Get microprice - mid weighted by opposite top-of-book size - O(log n)
+        #[inline(always)]
+        pub fn microprice(&self) -> Option<f64> {
+            let (&bid_key, &bid_qty) = self.bids.iter().next_back()?;
+            let (&ask_key, &ask_qty) = self.asks.iter().next()?;
+            let total = bid_qty as f64 + ask_qty as f64;
+            if total <= 0.0 {
+                return None;
+            }
+            let (bid, ask) = (self.key_to_price(bid_key), self.key_to_price(ask_key));
+            Some((bid * ask_qty as f64 + ask * bid_qty as f64) / total)
+        }
+
+        /// (bid_qty - ask_qty) / (bid_qty + ask_qty) over the top `levels`
+        /// of each side, in [-1, 1] - O(levels)
+        /// None when either side is empty; positive means bid-heavy
+        pub fn imbalance(&self, levels: usize) -> Option<f64> {
+            let bid: i64 = self.bids.values().rev().take(levels).sum();
+            let ask: i64 = self.asks.values().take(levels).sum();
+            if bid <= 0 || ask <= 0 {
+                return None;
+            }
+            // Units are exact integers; f64 only for the final ratio
+            Some(((bid - ask) as f64 / (bid + ask) as f64).clamp(-1.0, 1.0))
+        }
+
+        /// Size-weighted average price of the top `levels` on both sides -
+        /// O(levels)
+        /// Unlike `microprice`, each price is weighted by its own size, so a
+        /// bid-heavy book pulls this below the mid. None when either side
+        /// is empty.
+        pub fn weighted_mid(&self, levels: usize) -> Option<f64> {
+            if self.bids.is_empty() || self.asks.is_empty() || levels == 0 {
+                return None;
+            }
+            let depth = self.bids.iter().rev().take(levels).chain(self.asks.iter().take(levels));
+            let (notional, units) = depth.fold((0.0, 0i64), |(notional, units), (&key, &qty)| {
+                (notional + self.key_to_price(key) * qty as f64, units + qty)
+            });
+            (units > 0).then(|| notional / units as f64)
+        }
+
+        /// Cumulative bid size over cumulative ask size across the top
+        /// `levels` - O(levels)
+        /// Above 1 means more resting bids; None when either side is empty
+        pub fn book_pressure(&self, levels: usize) -> Option<f64> {
+            let bid: i64 = self.bids.values().rev().take(levels).sum();
+            let ask: i64 = self.asks.values().take(levels).sum();
+            if bid <= 0 || ask <= 0 {
+                return None;
+            }
+            Some(bid as f64 / ask as f64)
+        }
+
         /// Get spread in basis points - O(log n)
         #[inline(always)]
         pub fn spread_bps(&self) -> Option<i64> {
@@ -106,27 +746,187 @@ pub mod orderbook {
         }
 
         /// Get top N levels - O(n)
-        pub fn top_levels(&self, n: usize) -> (Vec<(f64, f64)>, Vec<(f64, f64)>) {
-            let bids: Vec<(f64, f64)> = self.bids
+        pub fn top_levels(&self, n: usize) -> (Levels, Levels) {
+            let bids: Levels = self.bids
                 .iter()
                 .rev()
                 .take(n)
-                .map(|(&k, &q)| (key_to_price(k), q as f64 / PRICE_SCALE))
+                .map(|(&k, &q)| (self.key_to_price(k), self.units_to_qty(q)))
                 .collect();
 
-            let asks: Vec<(f64, f64)> = self.asks
+            let asks: Levels = self.asks
                 .iter()
                 .take(n)
-                .map(|(&k, &q)| (key_to_price(k), q as f64 / PRICE_SCALE))
+                .map(|(&k, &q)| (self.key_to_price(k), self.units_to_qty(q)))
                 .collect();
 
             (bids, asks)
         }
 
+        /// Depth summed into `bucket`-wide price buckets - O(n)
+        /// Bids floor and asks ceil to the bucket, so aggregated sides never
+        /// cross; same ordering as `top_levels`. `bucket <= 0` = no grouping
+        pub fn aggregated_depth(&self, bucket: f64) -> (Levels, Levels) {
+            let bucket_key = self.price_to_key(bucket).unwrap_or(0);
+            if bucket_key <= 0 {
+                return self.top_levels(usize::MAX);
+            }
+            let group = |levels: &BTreeMap<i64, i64>, ceil: bool| {
+                let mut buckets: BTreeMap<i64, i64> = BTreeMap::new();
+                for (&key, &qty) in levels {
+                    let floor = key.div_euclid(bucket_key) * bucket_key;
+                    let edge = if ceil && floor != key { floor + bucket_key } else { floor };
+                    *buckets.entry(edge).or_insert(0) += qty;
+                }
+                buckets
+            };
+
+            let bids = group(&self.bids, false)
+                .into_iter()
+                .rev()
+                .map(|(k, q)| (self.key_to_price(k), self.units_to_qty(q)))
+                .collect();
+            let asks = group(&self.asks, true)
+                .into_iter()
+                .map(|(k, q)| (self.key_to_price(k), self.units_to_qty(q)))
+                .collect();
+            (bids, asks)
+        }
+
+        /// Walk bids (`is_bid`, i.e. selling) or asks best-first until `qty`
+        /// is covered - O(levels touched)
+        /// The last level contributes only the remainder. Returns whatever
+        /// was fillable; None for a non-positive qty or an empty side.
+        pub fn walk_depth(&self, qty: f64, is_bid: bool) -> Option<DepthFill> {
+            let mut remaining = self.qty_to_units(qty);
+            if remaining <= 0 {
+                return None;
+            }
+            let levels: Box<dyn Iterator<Item = (&i64, &i64)>> = if is_bid {
+                Box::new(self.bids.iter().rev())
+            } else {
+                Box::new(self.asks.iter())
+            };
+
+            let (mut filled, mut notional) = (0i64, 0.0);
+            for (&key, &level_qty) in levels {
+                let take = level_qty.min(remaining);
+                filled += take;
+                notional += self.key_to_price(key) * take as f64;
+                remaining -= take;
+                if remaining == 0 {
+                    break;
+                }
+            }
+            (filled > 0).then(|| DepthFill {
+                avg_price: notional / filled as f64,
+                filled_qty: self.units_to_qty(filled),
+            })
+        }
+
+        /// VWAP to fill `qty` against bids (`is_bid`) or asks - None when
+        /// the side can't fill it all; see `walk_depth` for the shortfall
+        pub fn vwap_for_qty(&self, qty: f64, is_bid: bool) -> Option<f64> {
+            let fill = self.walk_depth(qty, is_bid)?;
+            (self.qty_to_units(fill.filled_qty) == self.qty_to_units(qty)).then_some(fill.avg_price)
+        }
+
+        /// Full snapshot of the current book - O(n)
+        pub fn snapshot(&self) -> OrderbookSnapshot {
+            let (bids, asks) = self.top_levels(usize::MAX);
+            OrderbookSnapshot {
+                symbol_hash: self.symbol_hash,
+                seq_id: self.last_seq_id.load(Ordering::Relaxed),
+                bids,
+                asks,
+            }
+        }
+
+        /// Persist `snapshot()` as JSON for a warm restart - O(n)
+        /// Written via a temp file + rename so a crash never leaves a torn file
+        pub fn save_snapshot(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+            let path = path.as_ref();
+            let tmp = path.with_extension("tmp");
+            std::fs::write(&tmp, serde_json::to_vec(&self.snapshot())?)?;
+            std::fs::rename(tmp, path)
+        }
+
+        /// Rebuild from a saved snapshot via `apply_snapshot` - O(n log n)
+        /// The saved seq becomes `last_seq_id`, so the next delta is
+        /// validated against it as if the process had never stopped
+        pub fn restore_snapshot(&mut self, path: impl AsRef<Path>) -> std::io::Result<bool> {
+            let snapshot = load_snapshot(path)?;
+            if snapshot.symbol_hash != self.symbol_hash {
+                return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, "SNAPSHOT_SYMBOL_MISMATCH"));
+            }
+            Ok(self.apply_snapshot(&snapshot))
+        }
+
+        /// FNV-1a hash of every level (price key, quantity units) - O(n)
+        /// Equal hashes mean identical level sets; the seq is not included
+        pub fn state_hash(&self) -> u64 {
+            let mut hash: u64 = 14695981039346656037;
+            let mut mix = |value: i64| {
+                for byte in value.to_le_bytes() {
+                    hash ^= byte as u64;
+                    hash = hash.wrapping_mul(1099511628211);
+                }
+            };
+            for (&key, &qty) in &self.bids {
+                mix(key);
+                mix(qty);
+            }
+            mix(-1); // Side separator
+            for (&key, &qty) in &self.asks {
+                mix(key);
+                mix(qty);
+            }
+            hash
+        }
+
+        /// Exchange book checksum over the top `CHECKSUM_DEPTH` levels - O(25)
+        /// Layout (OKX): `bidPx:bidQty:askPx:askQty:...` interleaved from
+        /// the touch outwards, the longer side's remainder appended, numbers
+        /// in shortest form ("3366.1", "7"). OKX reports it as `i32`; compare
+        /// with `expected as u32`. Binance publishes no depth checksum.
+        pub fn checksum(&self) -> u32 {
+            use std::fmt::Write;
+            let mut bids = self.bids.iter().rev().take(CHECKSUM_DEPTH);
+            let mut asks = self.asks.iter().take(CHECKSUM_DEPTH);
+            let mut payload = String::with_capacity(CHECKSUM_DEPTH * 2 * 24);
+            loop {
+                let (bid, ask) = (bids.next(), asks.next());
+                if bid.is_none() && ask.is_none() {
+                    break;
+                }
+                for (&key, &units) in bid.into_iter().chain(ask) {
+                    let sep = if payload.is_empty() { "" } else { ":" };
+                    let _ = write!(payload, "{}{}:{}", sep, self.key_to_price(key), self.units_to_qty(units));
+                }
+            }
+            crc32(payload.as_bytes())
+        }
+
+        /// Compare against the exchange checksum after a batch of deltas
+        /// A mismatch is counted and raises `needs_resync()`
+        pub fn verify_checksum(&mut self, expected: u32) -> bool {
+            if self.checksum() == expected {
+                return true;
+            }
+            self.checksum_mismatches.fetch_add(1, Ordering::Relaxed);
+            self.resync_requested = true;
+            tracing::warn!(symbol_hash = self.symbol_hash, expected, "book checksum mismatch");
+            false
+        }
+
         /// Clear all levels
         pub fn clear(&mut self) {
             self.bids.clear();
             self.asks.clear();
+            if let Some(times) = self.level_times.as_mut() {
+                times.bids.clear();
+                times.asks.clear();
+            }
         }
 
         /// Get statistics
@@ -139,6 +939,1632 @@ pub mod orderbook {
             )
         }
     }
-}
 
-pub use orderbook::*;
+    fn expire_side(
+        levels: &mut BTreeMap<i64, i64>,
+        times: &mut HashMap<i64, i64>,
+        now_ns: i64,
+        max_age_ns: i64,
+    ) -> usize {
+        let before = levels.len();
+        levels.retain(|key, _| {
+            let updated = *times.entry(*key).or_insert(now_ns);
+            let fresh = now_ns - updated <= max_age_ns;
+            if !fresh {
+                times.remove(key);
+            }
+            fresh
+        });
+        before - levels.len()
+    }
+
+    /// Read a snapshot written by `L2Orderbook::save_snapshot`
+    pub fn load_snapshot(path: impl AsRef<Path>) -> std::io::Result<OrderbookSnapshot> {
+        serde_json::from_slice(&std::fs::read(path)?).map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+    }
+
+    /// Point-in-time price sample
+    #[derive(Clone, Copy, Debug, Default, PartialEq)]
+    pub struct PricePoint {
+        pub ts_ns: i64,
+        pub mid: f64,
+        pub microprice: f64,
+    }
+
+    /// Bounded price history ring - O(1) push, contiguous `recent(n)`
+    ///
+    /// Every point is written twice (at `i` and `i + capacity`) so the most
+    /// recent window is always one contiguous slice, oldest first.
+    ///
+    /// A `ShardedProcessor` keeps one per symbol, recorded on every applied
+    /// delta and read back with `ShardedProcessor::recent`; size it with
+    /// `ProcessorConfig::history_capacity`.
+    pub struct PriceHistory {
+        buffer: Vec<PricePoint>,
+        capacity: usize,
+        next: usize,
+        len: usize,
+    }
+
+    impl PriceHistory {
+        pub fn new(capacity: usize) -> Self {
+            let capacity = capacity.max(1);
+            Self {
+                buffer: vec![PricePoint::default(); capacity * 2],
+                capacity,
+                next: 0,
+                len: 0,
+            }
+        }
+
+        #[inline(always)]
+        pub fn push(&mut self, point: PricePoint) {
+            self.buffer[self.next] = point;
+            self.buffer[self.next + self.capacity] = point;
+            self.next = (self.next + 1) % self.capacity;
+            self.len = (self.len + 1).min(self.capacity);
+        }
+
+        /// Capture mid/microprice from a two-sided book
+        /// Returns false (nothing recorded) for a one-sided book
+        #[inline(always)]
+        pub fn record(&mut self, book: &L2Orderbook, ts_ns: i64) -> bool {
+            match (book.mid_price(), book.microprice()) {
+                (Some(mid), Some(microprice)) => {
+                    self.push(PricePoint { ts_ns, mid, microprice });
+                    true
+                }
+                _ => false,
+            }
+        }
+
+        /// Last `n` points, oldest first - O(1)
+        #[inline(always)]
+        pub fn recent(&self, n: usize) -> &[PricePoint] {
+            let n = n.min(self.len);
+            let end = self.next + self.capacity;
+            &self.buffer[end - n..end]
+        }
+
+        pub fn len(&self) -> usize {
+            self.len
+        }
+
+        pub fn is_empty(&self) -> bool {
+            self.len == 0
+        }
+
+        pub fn capacity(&self) -> usize {
+            self.capacity
+        }
+    }
+
+    /// Full-snapshot publish triggers - whichever fires first
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub struct SnapshotPolicy {
+        pub every_n_deltas: u64,    // 0 = disabled
+        pub interval_ns: i64,       // 0 = disabled
+        pub min_request_interval_ns: i64,   // On-demand rate limit per symbol, 0 = unlimited
+    }
+
+    impl Default for SnapshotPolicy {
+        fn default() -> Self {
+            Self {
+                every_n_deltas: 1_000,
+                interval_ns: 1_000_000_000, // 1s
+                min_request_interval_ns: 100_000_000,   // 100ms
+            }
+        }
+    }
+
+    #[derive(Clone, Copy, Debug, Default)]
+    struct SnapshotState {
+        deltas_since: u64,
+        last_snapshot_ns: i64,
+    }
+
+    /// Per-symbol snapshot scheduler
+    pub struct SnapshotScheduler {
+        policy: SnapshotPolicy,
+        symbols: HashMap<u64, SnapshotState>,   // symbol_hash -> state
+        last_request_ns: HashMap<u64, i64>,     // symbol_hash -> last served request
+        pub snapshots_by_count: u64,
+        pub snapshots_by_time: u64,
+        pub snapshots_on_demand: u64,
+        pub requests_rate_limited: u64,
+    }
+
+    impl SnapshotScheduler {
+        pub fn new(policy: SnapshotPolicy) -> Self {
+            Self {
+                policy,
+                symbols: HashMap::new(),
+                last_request_ns: HashMap::new(),
+                snapshots_by_count: 0,
+                snapshots_by_time: 0,
+                snapshots_on_demand: 0,
+                requests_rate_limited: 0,
+            }
+        }
+
+        /// Count an applied delta - returns true when a snapshot is due
+        #[inline(always)]
+        pub fn record_delta(&mut self, symbol_hash: u64, now_ns: i64) -> bool {
+            let policy = self.policy;
+            let state = self.symbols.entry(symbol_hash).or_insert(SnapshotState {
+                deltas_since: 0,
+                last_snapshot_ns: now_ns,
+            });
+            state.deltas_since += 1;
+
+            if policy.every_n_deltas > 0 && state.deltas_since >= policy.every_n_deltas {
+                *state = SnapshotState { deltas_since: 0, last_snapshot_ns: now_ns };
+                self.snapshots_by_count += 1;
+                return true;
+            }
+            if policy.interval_ns > 0 && now_ns - state.last_snapshot_ns >= policy.interval_ns {
+                *state = SnapshotState { deltas_since: 0, last_snapshot_ns: now_ns };
+                self.snapshots_by_time += 1;
+                return true;
+            }
+            false
+        }
+
+        /// Timer path - symbols whose interval elapsed, even without deltas
+        pub fn due_by_time(&mut self, now_ns: i64) -> Vec<u64> {
+            if self.policy.interval_ns <= 0 {
+                return Vec::new();
+            }
+            let mut due = Vec::new();
+            for (&symbol_hash, state) in self.symbols.iter_mut() {
+                if now_ns - state.last_snapshot_ns >= self.policy.interval_ns {
+                    *state = SnapshotState { deltas_since: 0, last_snapshot_ns: now_ns };
+                    due.push(symbol_hash);
+                }
+            }
+            self.snapshots_by_time += due.len() as u64;
+            due
+        }
+
+        /// Deltas applied since the symbol's last snapshot
+        pub fn deltas_since(&self, symbol_hash: u64) -> u64 {
+            self.symbols.get(&symbol_hash).map_or(0, |s| s.deltas_since)
+        }
+
+        /// On-demand request (e.g. a consumer joining mid-stream)
+        /// Returns true if a snapshot should be published now; requests
+        /// inside `min_request_interval_ns` of the last served one are dropped
+        pub fn request_snapshot(&mut self, symbol_hash: u64, now_ns: i64) -> bool {
+            let min_interval = self.policy.min_request_interval_ns;
+            if let Some(&last) = self.last_request_ns.get(&symbol_hash) {
+                if min_interval > 0 && now_ns - last < min_interval {
+                    self.requests_rate_limited += 1;
+                    return false;
+                }
+            }
+            self.last_request_ns.insert(symbol_hash, now_ns);
+            // The on-demand snapshot also restarts the periodic schedule
+            self.symbols.insert(symbol_hash, SnapshotState { deltas_since: 0, last_snapshot_ns: now_ns });
+            self.snapshots_on_demand += 1;
+            true
+        }
+
+        /// Serve a request from the live book - the full snapshot, or None if rate-limited
+        pub fn serve_request(&mut self, book: &L2Orderbook, now_ns: i64) -> Option<OrderbookSnapshot> {
+            self.request_snapshot(book.symbol_hash, now_ns).then(|| book.snapshot())
+        }
+    }
+
+    impl Default for SnapshotScheduler {
+        fn default() -> Self {
+            Self::new(SnapshotPolicy::default())
+        }
+    }
+
+    /// Decayed trade flow at one price level
+    #[derive(Clone, Copy, Debug, Default)]
+    struct LevelActivity {
+        volume: f64,
+        trades: f64,
+        updated_ns: i64,
+    }
+
+    /// Recent trade activity per price level with exponential decay
+    ///
+    /// Distinguishes levels with real flow from quote-only levels. Memory is
+    /// bounded by `max_levels`; the least active level is evicted first.
+    pub struct TradeActivity {
+        half_life_ns: i64,
+        max_levels: usize,
+        levels: HashMap<(bool, i64), LevelActivity>,   // (is_bid, price_key) -> activity
+        now_ns: i64,
+        tick_scale: i64,
+    }
+
+    impl TradeActivity {
+        pub fn new(half_life_ns: i64, max_levels: usize) -> Self {
+            Self {
+                half_life_ns: half_life_ns.max(1),
+                max_levels: max_levels.max(1),
+                levels: HashMap::new(),
+                now_ns: 0,
+                tick_scale: PRICE_SCALE as i64,
+            }
+        }
+
+        /// Activity keyed at `book`'s tick scale, so levels line up with it
+        pub fn for_book(book: &L2Orderbook, half_life_ns: i64, max_levels: usize) -> Self {
+            Self {
+                tick_scale: book.tick_scale,
+                ..Self::new(half_life_ns, max_levels)
+            }
+        }
+
+        #[inline(always)]
+        fn price_to_key(&self, price: f64) -> Option<i64> {
+            checked_price_to_key(price, self.tick_scale)
+        }
+
+        #[inline(always)]
+        fn decay_factor(&self, from_ns: i64, to_ns: i64) -> f64 {
+            let dt = (to_ns - from_ns).max(0) as f64;
+            0.5f64.powf(dt / self.half_life_ns as f64)
+        }
+
+        /// Record a trade that printed at `price` on the given side
+        /// Unrepresentable prices are ignored
+        pub fn record_trade(&mut self, Price(price): Price, Qty(qty): Qty, is_bid: bool, ts_ns: i64) {
+            self.now_ns = self.now_ns.max(ts_ns);
+            let now = self.now_ns;
+            let Some(price_key) = self.price_to_key(price) else {
+                return;
+            };
+            let key = (is_bid, price_key);
+
+            if !self.levels.contains_key(&key) && self.levels.len() >= self.max_levels {
+                self.evict_least_active();
+            }
+
+            let decay = match self.levels.get(&key) {
+                Some(level) => self.decay_factor(level.updated_ns, now),
+                None => 0.0,
+            };
+            let level = self.levels.entry(key).or_default();
+            level.volume = level.volume * decay + qty;
+            level.trades = level.trades * decay + 1.0;
+            level.updated_ns = now;
+        }
+
+        /// Decayed traded volume at a level as of the latest clock
+        pub fn trade_activity_at(&self, price: f64, is_bid: bool) -> f64 {
+            self.price_to_key(price)
+                .and_then(|key| self.levels.get(&(is_bid, key)))
+                .map_or(0.0, |l| l.volume * self.decay_factor(l.updated_ns, self.now_ns))
+        }
+
+        /// Decayed trade count at a level as of the latest clock
+        pub fn trade_count_at(&self, price: f64, is_bid: bool) -> f64 {
+            self.price_to_key(price)
+                .and_then(|key| self.levels.get(&(is_bid, key)))
+                .map_or(0.0, |l| l.trades * self.decay_factor(l.updated_ns, self.now_ns))
+        }
+
+        /// Advance the clock without a trade, dropping negligible levels
+        pub fn advance(&mut self, now_ns: i64) {
+            self.now_ns = self.now_ns.max(now_ns);
+            let (now, half_life) = (self.now_ns, self.half_life_ns as f64);
+            self.levels.retain(|_, l| {
+                let decay = 0.5f64.powf((now - l.updated_ns).max(0) as f64 / half_life);
+                l.volume * decay > 1e-9
+            });
+        }
+
+        fn evict_least_active(&mut self) {
+            let now = self.now_ns;
+            let victim = self
+                .levels
+                .iter()
+                .map(|(&k, l)| (k, l.volume * self.decay_factor(l.updated_ns, now)))
+                .min_by(|a, b| a.1.total_cmp(&b.1))
+                .map(|(k, _)| k);
+            if let Some(k) = victim {
+                self.levels.remove(&k);
+            }
+        }
+
+        pub fn len(&self) -> usize {
+            self.levels.len()
+        }
+
+        pub fn is_empty(&self) -> bool {
+            self.levels.is_empty()
+        }
+    }
+
+    /// De-noising thresholds - 0 disables a threshold
+    #[derive(Clone, Copy, Debug, Default, PartialEq)]
+    pub struct TickFilterConfig {
+        pub min_price_move: f64,    // vs last accepted price on the same side
+        pub min_size_change: f64,   // vs the book's current size at the level
+    }
+
+    /// Drops deltas that would not meaningfully change the book
+    ///
+    /// A delta passes if its price moved at least `min_price_move` from the
+    /// last accepted price on that side, or its size differs from the
+    /// level's current size by at least `min_size_change`. A 0 threshold
+    /// takes its check out of the decision; with both at 0 nothing is
+    /// dropped. Level deletes always pass so filtering can never leave
+    /// phantom liquidity.
+    /// Use `L2Orderbook::apply_filtered_delta` so dropped deltas still
+    /// advance the book's seq.
+    pub struct TickFilter {
+        config: TickFilterConfig,
+        last_bid_price: Option<f64>,
+        last_ask_price: Option<f64>,
+        pub filtered_ticks: AtomicU64,
+        pub passed_ticks: AtomicU64,
+    }
+
+    impl TickFilter {
+        pub fn new(config: TickFilterConfig) -> Self {
+            Self {
+                config,
+                last_bid_price: None,
+                last_ask_price: None,
+                filtered_ticks: AtomicU64::new(0),
+                passed_ticks: AtomicU64::new(0),
+            }
+        }
+
+        /// Decide whether a delta should be applied - O(log n)
+        #[inline(always)]
+        pub fn accept(&mut self, book: &L2Orderbook, Price(price): Price, Qty(qty): Qty, is_bid: bool) -> bool {
+            let side = if is_bid { &book.bids } else { &book.asks };
+            let current = book
+                .price_to_key(price)
+                .and_then(|key| side.get(&key))
+                .map_or(0.0, |&q| book.units_to_qty(q));
+            let last_price = if is_bid { self.last_bid_price } else { self.last_ask_price };
+
+            // A disabled (0) threshold never satisfies its check; with both
+            // disabled the filter passes everything
+            let price_enabled = self.config.min_price_move > 0.0;
+            let size_enabled = self.config.min_size_change > 0.0;
+            let is_delete = qty <= 0.0;
+            let price_moved = price_enabled
+                && match last_price {
+                    Some(last) => (price - last).abs() >= self.config.min_price_move,
+                    None => true,
+                };
+            let size_changed = size_enabled && (qty - current).abs() >= self.config.min_size_change;
+            let unfiltered = !price_enabled && !size_enabled;
+
+            if !(is_delete || unfiltered || price_moved || size_changed) {
+                self.filtered_ticks.fetch_add(1, Ordering::Relaxed);
+                return false;
+            }
+
+            if is_bid {
+                self.last_bid_price = Some(price);
+            } else {
+                self.last_ask_price = Some(price);
+            }
+            self.passed_ticks.fetch_add(1, Ordering::Relaxed);
+            true
+        }
+    }
+
+    impl Default for TickFilter {
+        fn default() -> Self {
+            Self::new(TickFilterConfig::default())
+        }
+    }
+
+    /// One changed level in a diff - `qty == 0.0` means removed
+    #[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+    pub struct LevelChange {
+        pub is_bid: bool,
+        pub price: f64,
+        pub qty: f64,
+    }
+
+    /// Compact snapshot: only levels added/changed/removed since the last one
+    #[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+    pub struct BookDiff {
+        pub symbol_hash: u64,
+        pub seq_id: u64,
+        pub changes: Vec<LevelChange>,
+    }
+
+    impl BookDiff {
+        pub fn is_empty(&self) -> bool {
+            self.changes.is_empty()
+        }
+
+        /// Apply to a consumer-side mirror book
+        pub fn apply_to(&self, mirror: &mut L2Orderbook) {
+            for change in &self.changes {
+                let Some(key) = mirror.price_to_key(change.price) else {
+                    continue;
+                };
+                let qty = mirror.qty_to_units(change.qty);
+                let side = if change.is_bid { &mut mirror.bids } else { &mut mirror.asks };
+                if qty <= 0 {
+                    side.remove(&key);
+                } else {
+                    side.insert(key, qty);
+                }
+            }
+            mirror.last_seq_id.store(self.seq_id, Ordering::Relaxed);
+        }
+    }
+
+    /// Last published top-N per side
+    #[derive(Default)]
+    struct PublishedDepth {
+        bids: BTreeMap<i64, i64>,
+        asks: BTreeMap<i64, i64>,
+    }
+
+    /// Remembers the last published top-N per symbol and diffs against it
+    pub struct DiffSnapshotPublisher {
+        depth: usize,
+        published: HashMap<u64, PublishedDepth>,    // symbol_hash -> last top-N
+    }
+
+    impl DiffSnapshotPublisher {
+        pub fn new(depth: usize) -> Self {
+            Self {
+                depth,
+                published: HashMap::new(),
+            }
+        }
+
+        /// Diff the book's current top-N against the last publish - O(depth)
+        /// The first call for a symbol yields every top-N level
+        pub fn diff(&mut self, book: &L2Orderbook) -> BookDiff {
+            let current = PublishedDepth {
+                bids: book.bids.iter().rev().take(self.depth).map(|(&k, &q)| (k, q)).collect(),
+                asks: book.asks.iter().take(self.depth).map(|(&k, &q)| (k, q)).collect(),
+            };
+            let previous = self.published.entry(book.symbol_hash).or_default();
+
+            let mut changes = Vec::new();
+            Self::diff_side(book, &previous.bids, &current.bids, true, &mut changes);
+            Self::diff_side(book, &previous.asks, &current.asks, false, &mut changes);
+            *previous = current;
+
+            BookDiff {
+                symbol_hash: book.symbol_hash,
+                seq_id: book.last_seq_id.load(Ordering::Relaxed),
+                changes,
+            }
+        }
+
+        fn diff_side(
+            book: &L2Orderbook,
+            previous: &BTreeMap<i64, i64>,
+            current: &BTreeMap<i64, i64>,
+            is_bid: bool,
+            changes: &mut Vec<LevelChange>,
+        ) {
+            for (&key, &qty) in current {
+                if previous.get(&key) != Some(&qty) {
+                    changes.push(LevelChange { is_bid, price: book.key_to_price(key), qty: book.units_to_qty(qty) });
+                }
+            }
+            for &key in previous.keys() {
+                if !current.contains_key(&key) {
+                    changes.push(LevelChange { is_bid, price: book.key_to_price(key), qty: 0.0 });
+                }
+            }
+        }
+
+        /// Forget a symbol so its next diff is a full top-N
+        pub fn reset(&mut self, symbol_hash: u64) {
+            self.published.remove(&symbol_hash);
+        }
+    }
+
+    /// Outcome of one reconciliation check
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub enum Reconciliation {
+        Match,
+        Skipped,                                // Reference is at a different seq
+        Resynced { diverged_levels: usize },
+    }
+
+    /// Periodic live book vs reference snapshot (fetched from the exchange)
+    /// check - catches silent corruption the sequence checks can't see
+    pub struct BookReconciler {
+        interval_ns: i64,
+        last_check_ns: HashMap<u64, i64>,   // symbol_hash -> last check
+        pub reconciliations: u64,
+        pub reconciliation_failures: u64,
+    }
+
+    impl BookReconciler {
+        pub fn new(interval_ns: i64) -> Self {
+            Self {
+                interval_ns,
+                last_check_ns: HashMap::new(),
+                reconciliations: 0,
+                reconciliation_failures: 0,
+            }
+        }
+
+        /// True when a symbol is due a check (fetch a reference snapshot)
+        pub fn due(&mut self, symbol_hash: u64, now_ns: i64) -> bool {
+            let last = self.last_check_ns.entry(symbol_hash).or_insert(i64::MIN);
+            if *last != i64::MIN && now_ns - *last < self.interval_ns {
+                return false;
+            }
+            *last = now_ns;
+            true
+        }
+
+        /// Compare the book with a reference at the same seq; resync on mismatch - O(n)
+        pub fn reconcile(&mut self, book: &mut L2Orderbook, reference: &OrderbookSnapshot) -> Reconciliation {
+            if reference.seq_id != book.last_seq_id.load(Ordering::Relaxed) {
+                return Reconciliation::Skipped;
+            }
+            self.reconciliations += 1;
+
+            let mut expected = L2Orderbook::with_qty_scale(book.symbol_hash, book.qty_scale);
+            expected.resync(reference);
+            if expected.state_hash() == book.state_hash() {
+                return Reconciliation::Match;
+            }
+
+            let diverged_levels = Self::diverged(&book.bids, &expected.bids)
+                + Self::diverged(&book.asks, &expected.asks);
+            self.reconciliation_failures += 1;
+            tracing::warn!(
+                symbol_hash = book.symbol_hash,
+                seq_id = reference.seq_id,
+                diverged_levels,
+                "book diverged from reference snapshot, resyncing"
+            );
+            book.resync(reference);
+            Reconciliation::Resynced { diverged_levels }
+        }
+
+        /// Levels present on only one side or with different quantities
+        fn diverged(live: &BTreeMap<i64, i64>, expected: &BTreeMap<i64, i64>) -> usize {
+            let missing = expected.iter().filter(|(k, q)| live.get(k) != Some(q)).count();
+            let extra = live.keys().filter(|k| !expected.contains_key(k)).count();
+            missing + extra
+        }
+    }
+
+    /// Best bid/ask only, fed by `@bookTicker`-style updates - no level maps
+    #[derive(Clone, Copy, Debug, Default, PartialEq)]
+    pub struct TopOfBook {
+        pub symbol_hash: u64,
+        pub bid: Option<(f64, f64)>,    // (price, qty), None when empty
+        pub ask: Option<(f64, f64)>,
+        pub last_update_id: u64,
+        pub stale_updates: u64,         // Older update ids dropped
+    }
+
+    impl TopOfBook {
+        pub fn new(symbol_hash: u64) -> Self {
+            Self { symbol_hash, ..Default::default() }
+        }
+
+        /// Replace both sides - O(1)
+        /// Update ids increase but aren't contiguous, so only older ones are
+        /// rejected (returns false); zero qty empties a side
+        #[inline(always)]
+        pub fn apply(&mut self, update_id: u64, bid: (Price, Qty), ask: (Price, Qty)) -> bool {
+            if self.last_update_id > 0 && update_id <= self.last_update_id {
+                self.stale_updates += 1;
+                return false;
+            }
+            let side = |(Price(price), Qty(qty)): (Price, Qty)| (qty > 0.0).then_some((price, qty));
+            self.bid = side(bid);
+            self.ask = side(ask);
+            self.last_update_id = update_id;
+            true
+        }
+
+        #[inline(always)]
+        pub fn best_bid(&self) -> Option<f64> {
+            self.bid.map(|(price, _)| price)
+        }
+
+        #[inline(always)]
+        pub fn best_ask(&self) -> Option<f64> {
+            self.ask.map(|(price, _)| price)
+        }
+
+        #[inline(always)]
+        pub fn mid_price(&self) -> Option<f64> {
+            Some((self.best_bid()? + self.best_ask()?) / 2.0)
+        }
+
+        /// Same weighting as `L2Orderbook::microprice`
+        pub fn microprice(&self) -> Option<f64> {
+            let ((bid, bid_qty), (ask, ask_qty)) = (self.bid?, self.ask?);
+            Some((bid * ask_qty + ask * bid_qty) / (bid_qty + ask_qty))
+        }
+
+        /// Same truncation as `L2Orderbook::spread_bps`
+        pub fn spread_bps(&self) -> Option<i64> {
+            match (self.best_bid(), self.best_ask()) {
+                (Some(bid), Some(ask)) if bid > 0.0 => Some(((ask - bid) / bid * 10_000.0) as i64),
+                _ => None,
+            }
+        }
+    }
+
+    /// Counters summed across every managed book
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+    pub struct ManagerStats {
+        pub books: usize,
+        pub bid_levels: usize,
+        pub ask_levels: usize,
+        pub total_updates: u64,
+        pub gaps_detected: u64,
+        pub crossed_updates: u64,
+        pub invalid_prices: u64,
+        pub top_of_book_symbols: usize,
+    }
+
+    /// Books for all active symbols - full depth and BBO-only side by side
+    #[derive(Default)]
+    pub struct OrderbookManager {
+        books: HashMap<String, L2Orderbook>,
+        tops: HashMap<String, TopOfBook>,
+    }
+
+    impl OrderbookManager {
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        /// Add (or replace) the book for a symbol
+        pub fn insert(&mut self, symbol: &str, book: L2Orderbook) {
+            self.books.insert(symbol.to_string(), book);
+        }
+
+        pub fn get(&self, symbol: &str) -> Option<&L2Orderbook> {
+            self.books.get(symbol)
+        }
+
+        pub fn get_mut(&mut self, symbol: &str) -> Option<&mut L2Orderbook> {
+            self.books.get_mut(symbol)
+        }
+
+        /// The symbol's book, created empty on first use - one connection
+        /// can carry any number of symbols without pre-registration
+        pub fn get_or_create(&mut self, symbol: &str) -> &mut L2Orderbook {
+            // Look up first so the hot path never allocates the key
+            if !self.books.contains_key(symbol) {
+                self.books.insert(symbol.to_string(), L2Orderbook::new(symbol_hash(symbol)));
+            }
+            self.books.get_mut(symbol).expect("inserted above")
+        }
+
+        /// Apply a delta to `symbol`'s book, creating it if needed - O(log n)
+        #[inline(always)]
+        pub fn apply_delta(&mut self, symbol: &str, price: Price, qty: Qty, is_bid: bool, seq_id: u64) -> DeltaOutcome {
+            self.get_or_create(symbol).apply_delta(price, qty, is_bid, seq_id)
+        }
+
+        pub fn top_of_book(&self, symbol: &str) -> Option<&TopOfBook> {
+            self.tops.get(symbol)
+        }
+
+        /// Apply a BBO update to a top-of-book-only symbol, tracking it on
+        /// first use - O(1)
+        pub fn apply_top_of_book(&mut self, symbol: &str, update_id: u64, bid: (Price, Qty), ask: (Price, Qty)) -> bool {
+            if !self.tops.contains_key(symbol) {
+                self.tops.insert(symbol.to_string(), TopOfBook::new(symbol_hash(symbol)));
+            }
+            self.tops.get_mut(symbol).expect("inserted above").apply(update_id, bid, ask)
+        }
+
+        /// Counters summed across all full-depth books - O(symbols)
+        pub fn stats(&self) -> ManagerStats {
+            let init = ManagerStats {
+                books: self.books.len(),
+                top_of_book_symbols: self.tops.len(),
+                ..Default::default()
+            };
+            self.books.values().fold(init, |acc, book| {
+                ManagerStats {
+                    bid_levels: acc.bid_levels + book.bids.len(),
+                    ask_levels: acc.ask_levels + book.asks.len(),
+                    total_updates: acc.total_updates + book.total_updates.load(Ordering::Relaxed),
+                    gaps_detected: acc.gaps_detected + book.gaps_detected.load(Ordering::Relaxed),
+                    crossed_updates: acc.crossed_updates + book.crossed_updates.load(Ordering::Relaxed),
+                    invalid_prices: acc.invalid_prices + book.invalid_prices.load(Ordering::Relaxed),
+                    ..acc
+                }
+            })
+        }
+
+        pub fn symbols(&self) -> impl Iterator<Item = &str> {
+            self.books.keys().map(|s| s.as_str())
+        }
+
+        pub fn len(&self) -> usize {
+            self.books.len()
+        }
+
+        pub fn is_empty(&self) -> bool {
+            self.books.is_empty()
+        }
+
+        /// Spread in bps averaged across symbols, weighted by each book's
+        /// top-level notional (bid px*qty + ask px*qty)
+        /// One-sided and crossed books are skipped; None if nothing qualifies
+        pub fn weighted_avg_spread_bps(&self) -> Option<f64> {
+            let mut weighted = 0.0;
+            let mut total_notional = 0.0;
+
+            for book in self.books.values() {
+                let (Some((&bid_key, &bid_qty)), Some((&ask_key, &ask_qty))) =
+                    (book.bids.iter().next_back(), book.asks.iter().next())
+                else {
+                    continue;
+                };
+                if bid_key <= 0 || bid_key >= ask_key {
+                    continue;
+                }
+
+                let (bid, ask) = (book.key_to_price(bid_key), book.key_to_price(ask_key));
+                let notional = bid * book.units_to_qty(bid_qty) + ask * book.units_to_qty(ask_qty);
+                let spread_bps = (ask - bid) / bid * 10_000.0;
+
+                weighted += spread_bps * notional;
+                total_notional += notional;
+            }
+
+            (total_notional > 0.0).then(|| weighted / total_notional)
+        }
+    }
+}
+
+pub use orderbook::*;
+
+#[cfg(test)]
+mod tests {
+    use super::orderbook::*;
+
+    fn point(ts_ns: i64) -> PricePoint {
+        PricePoint { ts_ns, mid: ts_ns as f64, microprice: ts_ns as f64 }
+    }
+
+    #[test]
+    fn test_price_history_keeps_last_n_in_order() {
+        let mut history = PriceHistory::new(4);
+        for ts in 0..10 {
+            history.push(point(ts));
+        }
+
+        assert_eq!(history.len(), 4);
+        let ts: Vec<i64> = history.recent(4).iter().map(|p| p.ts_ns).collect();
+        assert_eq!(ts, vec![6, 7, 8, 9]);
+        let ts: Vec<i64> = history.recent(2).iter().map(|p| p.ts_ns).collect();
+        assert_eq!(ts, vec![8, 9]);
+        assert_eq!(history.recent(100).len(), 4);
+    }
+
+    #[test]
+    fn test_price_history_records_from_book() {
+        let mut book = L2Orderbook::new(1);
+        let mut history = PriceHistory::new(8);
+        book.apply_delta(Price(100.0), Qty(3.0), true, 1);
+        assert!(!history.record(&book, 1));
+
+        book.apply_delta(Price(102.0), Qty(1.0), false, 2);
+        assert!(history.record(&book, 2));
+
+        let p = history.recent(1)[0];
+        assert_eq!(p.mid, 101.0);
+        // Heavier bid pulls microprice toward the ask
+        assert_eq!(p.microprice, 101.5);
+    }
+
+    #[test]
+    fn test_snapshot_due_every_k_deltas() {
+        let mut scheduler = SnapshotScheduler::new(SnapshotPolicy {
+            every_n_deltas: 1_000,
+            interval_ns: 0,
+            min_request_interval_ns: 0,
+        });
+
+        let due: Vec<u64> = (1..=2_500u64)
+            .filter(|&i| scheduler.record_delta(7, i as i64))
+            .collect();
+
+        assert_eq!(due, vec![1_000, 2_000]);
+        assert_eq!(scheduler.deltas_since(7), 500);
+        assert_eq!(scheduler.deltas_since(8), 0);
+    }
+
+    #[test]
+    fn test_snapshot_time_trigger_wins_for_quiet_symbol() {
+        let mut scheduler = SnapshotScheduler::new(SnapshotPolicy {
+            every_n_deltas: 1_000,
+            interval_ns: 1_000,
+            min_request_interval_ns: 0,
+        });
+
+        assert!(!scheduler.record_delta(7, 0));
+        assert!(!scheduler.record_delta(7, 999));
+        assert!(scheduler.record_delta(7, 1_000));
+        assert_eq!(scheduler.snapshots_by_time, 1);
+
+        assert!(scheduler.due_by_time(1_500).is_empty());
+        assert_eq!(scheduler.due_by_time(2_000), vec![7]);
+    }
+
+    #[test]
+    fn test_duplicate_snapshot_is_skipped() {
+        let mut book = L2Orderbook::new(1);
+        let snapshot = OrderbookSnapshot {
+            symbol_hash: 1,
+            seq_id: 100,
+            bids: vec![(99.0, 1.0), (98.0, 2.0)],
+            asks: vec![(101.0, 1.5)],
+        };
+
+        assert!(book.apply_snapshot(&snapshot));
+        assert!(!book.apply_snapshot(&snapshot));
+        assert_eq!(book.duplicate_snapshots.load(std::sync::atomic::Ordering::Relaxed), 1);
+        assert_eq!(book.stats(), (2, 1, 0, 0));
+        assert_eq!(book.best_bid(), Some(99.0));
+
+        // Book moves on - a newer snapshot still rebuilds
+        assert!(book.apply_delta(Price(99.5), Qty(1.0), true, 101).is_applied());
+        let newer = OrderbookSnapshot { seq_id: 200, ..snapshot };
+        assert!(book.apply_snapshot(&newer));
+        assert_eq!(book.best_bid(), Some(99.0));
+    }
+
+    #[test]
+    fn test_trade_activity_reflects_trades_then_decays() {
+        let mut activity = TradeActivity::new(1_000, 16);
+        activity.record_trade(Price(100.0), Qty(2.0), true, 0);
+        activity.record_trade(Price(100.0), Qty(1.0), true, 0);
+
+        assert_eq!(activity.trade_activity_at(100.0, true), 3.0);
+        assert_eq!(activity.trade_count_at(100.0, true), 2.0);
+        // Quote-only level and opposite side show nothing
+        assert_eq!(activity.trade_activity_at(100.5, true), 0.0);
+        assert_eq!(activity.trade_activity_at(100.0, false), 0.0);
+
+        activity.advance(1_000);
+        assert!((activity.trade_activity_at(100.0, true) - 1.5).abs() < 1e-12);
+
+        activity.advance(1_000_000);
+        assert_eq!(activity.trade_activity_at(100.0, true), 0.0);
+        assert!(activity.is_empty());
+    }
+
+    #[test]
+    fn test_trade_activity_memory_is_bounded() {
+        let mut activity = TradeActivity::new(1_000_000, 2);
+        activity.record_trade(Price(100.0), Qty(5.0), true, 0);
+        activity.record_trade(Price(101.0), Qty(1.0), false, 0);
+        activity.record_trade(Price(102.0), Qty(3.0), false, 0);
+
+        assert_eq!(activity.len(), 2);
+        assert_eq!(activity.trade_activity_at(101.0, false), 0.0);
+        assert_eq!(activity.trade_activity_at(100.0, true), 5.0);
+    }
+
+    #[test]
+    fn test_trade_activity_uses_book_tick_scale() {
+        // Whole-point ticks: 100.2 and 99.8 both print at the 100.0 level
+        let book = L2Orderbook::new(1).with_tick_scale(1);
+        let mut activity = TradeActivity::for_book(&book, 1_000, 16);
+        activity.record_trade(Price(100.2), Qty(1.0), true, 0);
+        activity.record_trade(Price(99.8), Qty(2.0), true, 0);
+        activity.record_trade(Price(f64::NAN), Qty(5.0), true, 0);
+
+        assert_eq!(activity.len(), 1);
+        assert_eq!(activity.trade_activity_at(100.0, true), 3.0);
+        assert_eq!(activity.trade_count_at(book.key_to_price(100), true), 2.0);
+        assert_eq!(activity.trade_activity_at(f64::NAN, true), 0.0);
+    }
+
+    #[test]
+    fn test_tick_filter_drops_sub_threshold_ticks() {
+        let mut book = L2Orderbook::new(1);
+        let mut filter = TickFilter::new(TickFilterConfig {
+            min_price_move: 0.5,
+            min_size_change: 0.1,
+        });
+
+        assert!(filter.accept(&book, Price(100.0), Qty(1.0), true));
+        book.apply_delta(Price(100.0), Qty(1.0), true, 1);
+
+        // Same level, size jitter below 0.1 -> filtered
+        assert!(!filter.accept(&book, Price(100.0), Qty(1.05), true));
+        // Nearby price, tiny size difference from that level's 0 -> passes as new liquidity
+        assert!(filter.accept(&book, Price(100.1), Qty(1.0), true));
+        // Size change at threshold -> passes
+        assert!(filter.accept(&book, Price(100.0), Qty(1.2), true));
+        // Price move above threshold -> passes even with equal size
+        book.apply_delta(Price(101.0), Qty(1.0), true, 2);
+        assert!(filter.accept(&book, Price(101.0), Qty(1.0), true));
+        // Deletes always pass
+        assert!(filter.accept(&book, Price(101.0), Qty(0.0), true));
+
+        assert_eq!(filter.filtered_ticks.load(std::sync::atomic::Ordering::Relaxed), 1);
+        assert_eq!(filter.passed_ticks.load(std::sync::atomic::Ordering::Relaxed), 5);
+    }
+
+    #[test]
+    fn test_tick_filter_zero_threshold_is_disabled() {
+        let mut book = L2Orderbook::new(1);
+        // Size-only filter: a fresh price must not pass on price alone
+        let mut filter = TickFilter::new(TickFilterConfig {
+            min_price_move: 0.0,
+            min_size_change: 0.5,
+        });
+        book.apply_delta(Price(100.0), Qty(1.0), true, 1);
+        assert!(!filter.accept(&book, Price(100.0), Qty(1.1), true));
+        assert!(filter.accept(&book, Price(100.0), Qty(2.0), true));
+
+        // Price-only filter: any size jitter at an unmoved price is dropped
+        let mut filter = TickFilter::new(TickFilterConfig {
+            min_price_move: 0.5,
+            min_size_change: 0.0,
+        });
+        assert!(filter.accept(&book, Price(100.0), Qty(5.0), true));
+        assert!(!filter.accept(&book, Price(100.0), Qty(9.0), true));
+        assert!(filter.accept(&book, Price(100.5), Qty(9.0), true));
+
+        // Both disabled: pass-through
+        let mut filter = TickFilter::default();
+        assert!(filter.accept(&book, Price(100.0), Qty(1.0), true));
+        assert!(filter.accept(&book, Price(100.0), Qty(1.0), true));
+    }
+
+    #[test]
+    fn test_filtered_delta_still_advances_seq() {
+        let mut book = L2Orderbook::new(1);
+        let mut filter = TickFilter::new(TickFilterConfig {
+            min_price_move: 0.5,
+            min_size_change: 0.1,
+        });
+
+        assert_eq!(book.apply_filtered_delta(&mut filter, Price(100.0), Qty(1.0), true, 1), DeltaOutcome::Applied);
+        assert_eq!(book.apply_filtered_delta(&mut filter, Price(100.0), Qty(1.05), true, 2), DeltaOutcome::Filtered);
+        assert_eq!(book.total_qty(true), 1.0);
+        assert_eq!(book.apply_filtered_delta(&mut filter, Price(100.0), Qty(2.0), true, 3), DeltaOutcome::Applied);
+        assert_eq!(book.total_qty(true), 2.0);
+        assert_eq!(book.gaps_detected.load(std::sync::atomic::Ordering::Relaxed), 0);
+
+        // A real gap is still a gap, filtered or not
+        assert_eq!(
+            book.apply_filtered_delta(&mut filter, Price(100.0), Qty(2.01), true, 5),
+            DeltaOutcome::Gap { expected: 4, got: 5 }
+        );
+    }
+
+    #[test]
+    fn test_diff_snapshot_contains_only_changed_level() {
+        let mut book = L2Orderbook::new(1);
+        let mut seq = 0;
+        for i in 0..5 {
+            seq += 1;
+            book.apply_delta(Price(100.0 - i as f64), Qty(1.0), true, seq);
+            seq += 1;
+            book.apply_delta(Price(101.0 + i as f64), Qty(1.0), false, seq);
+        }
+
+        let mut publisher = DiffSnapshotPublisher::new(3);
+        let mut mirror = L2Orderbook::new(1);
+        let full = publisher.diff(&book);
+        assert_eq!(full.changes.len(), 6);
+        full.apply_to(&mut mirror);
+
+        book.apply_delta(Price(99.0), Qty(2.5), true, seq + 1);
+        let diff = publisher.diff(&book);
+        assert_eq!(diff.changes, vec![LevelChange { is_bid: true, price: 99.0, qty: 2.5 }]);
+        assert_eq!(diff.seq_id, seq + 1);
+        diff.apply_to(&mut mirror);
+
+        // Removing the best ask pulls a new level into the top-3
+        book.apply_delta(Price(101.0), Qty(0.0), false, seq + 2);
+        let diff = publisher.diff(&book);
+        assert_eq!(diff.changes.len(), 2);
+        assert!(diff.changes.contains(&LevelChange { is_bid: false, price: 101.0, qty: 0.0 }));
+        assert!(diff.changes.contains(&LevelChange { is_bid: false, price: 104.0, qty: 1.0 }));
+        diff.apply_to(&mut mirror);
+
+        assert!(publisher.diff(&book).is_empty());
+        assert_eq!(mirror.top_levels(3), book.top_levels(3));
+    }
+
+    #[test]
+    fn test_weighted_avg_spread_by_notional() {
+        let mut manager = OrderbookManager::new();
+
+        // 10bps spread, ~200 notional
+        let mut btc = L2Orderbook::new(symbol_hash("BTCUSDT"));
+        btc.apply_delta(Price(100.0), Qty(1.0), true, 1);
+        btc.apply_delta(Price(100.1), Qty(1.0), false, 2);
+        manager.insert("BTCUSDT", btc);
+
+        // 100bps spread, ~20 notional
+        let mut alt = L2Orderbook::new(symbol_hash("ALTUSDT"));
+        alt.apply_delta(Price(10.0), Qty(1.0), true, 1);
+        alt.apply_delta(Price(10.1), Qty(1.0), false, 2);
+        manager.insert("ALTUSDT", alt);
+
+        // One-sided book is ignored
+        let mut thin = L2Orderbook::new(symbol_hash("THINUSDT"));
+        thin.apply_delta(Price(5.0), Qty(100.0), true, 1);
+        manager.insert("THINUSDT", thin);
+
+        let expected = (10.0 * 200.1 + 100.0 * 20.1) / (200.1 + 20.1);
+        let got = manager.weighted_avg_spread_bps().unwrap();
+        assert!((got - expected).abs() < 1e-6, "got {got}, expected {expected}");
+
+        assert_eq!(OrderbookManager::new().weighted_avg_spread_bps(), None);
+    }
+
+    #[test]
+    fn test_manager_routes_by_symbol_and_sums_stats() {
+        let mut manager = OrderbookManager::new();
+        for (i, symbol) in ["BTCUSDT", "ETHUSDT", "SOLUSDT"].iter().enumerate() {
+            let base = 100.0 * (i + 1) as f64;
+            assert!(manager.apply_delta(symbol, Price(base), Qty(1.0), true, 1).is_applied());
+            assert!(manager.apply_delta(symbol, Price(base + 1.0), Qty(1.0), false, 2).is_applied());
+        }
+        manager.apply_delta("ETHUSDT", Price(150.0), Qty(1.0), true, 9);   // Gap
+
+        assert_eq!(manager.get("BTCUSDT").unwrap().best_bid(), Some(100.0));
+        assert_eq!(manager.get("SOLUSDT").unwrap().best_ask(), Some(301.0));
+        assert_eq!(manager.get("ETHUSDT").unwrap().symbol_hash, symbol_hash("ETHUSDT"));
+        assert_eq!(
+            manager.stats(),
+            ManagerStats {
+                books: 3,
+                bid_levels: 3,
+                ask_levels: 3,
+                total_updates: 6,
+                gaps_detected: 1,
+                crossed_updates: 0,
+                invalid_prices: 0,
+                top_of_book_symbols: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn test_imbalance_sign_follows_heavier_side() {
+        let mut book = L2Orderbook::new(1);
+        assert_eq!(book.imbalance(5), None);
+        book.apply_delta(Price(100.0), Qty(3.0), true, 1);
+        assert_eq!(book.imbalance(5), None);   // One-sided
+        book.apply_delta(Price(99.0), Qty(5.0), true, 2);
+        book.apply_delta(Price(101.0), Qty(1.0), false, 3);
+        book.apply_delta(Price(110.0), Qty(7.0), false, 4);
+
+        // Top 1: 3 vs 1 -> bid-heavy; top 2: 8 vs 8 -> balanced
+        assert_eq!(book.imbalance(1), Some(0.5));
+        assert_eq!(book.imbalance(2), Some(0.0));
+        book.apply_delta(Price(102.0), Qty(12.0), false, 5);
+        assert_eq!(book.imbalance(2), Some(-5.0 / 21.0));   // 8 vs 1 + 12 -> ask-heavy
+        assert_eq!(book.imbalance(1), Some(0.5));
+
+        // Microprice leans toward the thin side's price
+        let micro = book.microprice().unwrap();
+        assert!((micro - (100.0 * 1.0 + 101.0 * 3.0) / 4.0).abs() < 1e-9);
+        assert!(micro > book.mid_price().unwrap());
+    }
+
+    #[test]
+    fn test_checksum_matches_exchange_layout() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+
+        // OKX documentation example: "3366.1:7:3366.8:9:3366:6:3368:8"
+        let mut book = L2Orderbook::new(1);
+        book.apply_snapshot(&OrderbookSnapshot {
+            symbol_hash: 1,
+            seq_id: 1,
+            bids: vec![(3366.1, 7.0), (3366.0, 6.0)],
+            asks: vec![(3366.8, 9.0), (3368.0, 8.0)],
+        });
+        assert_eq!(book.checksum() as i32, -1_881_014_294);
+        assert!(book.verify_checksum(-1_881_014_294i32 as u32));
+        assert!(!book.needs_resync());
+
+        // Longer bid side: remainder appended after the interleave
+        let mut uneven = L2Orderbook::new(2);
+        uneven.apply_delta(Price(100.5), Qty(1.25), true, 1);
+        uneven.apply_delta(Price(100.0), Qty(3.0), true, 2);
+        uneven.apply_delta(Price(101.0), Qty(2.0), false, 3);
+        assert_eq!(uneven.checksum(), crc32(b"100.5:1.25:101:2:100:3"));
+        assert_eq!(uneven.checksum(), 3_863_252_202);
+
+        // Drift from the exchange book: mismatch flags a resync
+        book.apply_delta(Price(3366.1), Qty(6.5), true, 2);
+        assert!(!book.verify_checksum(-1_881_014_294i32 as u32));
+        assert!(book.needs_resync());
+        assert_eq!(book.checksum_mismatches.load(std::sync::atomic::Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn test_manager_mixes_full_depth_and_top_of_book_symbols() {
+        let mut manager = OrderbookManager::new();
+        manager.apply_delta("BTCUSDT", Price(100.0), Qty(1.0), true, 1);
+        assert!(manager.apply_top_of_book("DOGEUSDT", 10, (Price(0.1), Qty(500.0)), (Price(0.1002), Qty(1_500.0))));
+        assert!(manager.apply_top_of_book("DOGEUSDT", 14, (Price(0.1001), Qty(200.0)), (Price(0.1002), Qty(600.0))));
+        assert!(!manager.apply_top_of_book("DOGEUSDT", 12, (Price(0.09), Qty(1.0)), (Price(0.2), Qty(1.0))));
+
+        let doge = manager.top_of_book("DOGEUSDT").unwrap();
+        assert_eq!(doge.bid, Some((0.1001, 200.0)));
+        assert_eq!(doge.best_ask(), Some(0.1002));
+        assert_eq!(doge.stale_updates, 1);
+        assert_eq!(doge.spread_bps(), Some(9));
+        assert!((doge.microprice().unwrap() - 0.100125).abs() < 1e-12);
+
+        // BBO symbols never get level maps
+        assert!(manager.get("DOGEUSDT").is_none());
+        assert!(manager.top_of_book("BTCUSDT").is_none());
+        let stats = manager.stats();
+        assert_eq!((stats.books, stats.top_of_book_symbols, stats.bid_levels), (1, 1, 1));
+
+        // Zero qty empties a side
+        manager.apply_top_of_book("DOGEUSDT", 15, (Price(0.1001), Qty(0.0)), (Price(0.1002), Qty(600.0)));
+        assert_eq!(manager.top_of_book("DOGEUSDT").unwrap().mid_price(), None);
+    }
+
+    #[test]
+    fn test_lot_units_keep_sums_exact() {
+        let mut lots = L2Orderbook::with_lot_size(1, 0.001).unwrap();
+        let mut float_sum = 0.0f64;
+        for i in 0..1_000u64 {
+            lots.apply_delta(Price(100.0 - i as f64 * 0.01), Qty(0.001), true, i + 1);
+            float_sum += 0.001;
+        }
+
+        assert_eq!(lots.bids.values().sum::<i64>(), 1_000);
+        assert_eq!(lots.total_qty(true), 1.0);
+        assert_ne!(float_sum, 1.0);
+
+        // 0.3 / 0.1 is 2.9999999999999996 in f64 - rounds to 3 lots, not 2
+        let mut tenth_lots = L2Orderbook::with_lot_size(2, 0.1).unwrap();
+        tenth_lots.apply_delta(Price(50.0), Qty(0.3), false, 1);
+        assert_eq!(tenth_lots.asks[&price_to_key(50.0)], 3);
+        assert_eq!(tenth_lots.top_levels(1).1, vec![(50.0, 0.3)]);
+
+        for bad in [0.0, -0.1, f64::NAN, f64::INFINITY, 1e-320] {
+            assert!(L2Orderbook::with_lot_size(3, bad).is_none(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn test_stale_levels_expire_fresh_remain() {
+        let mut book = L2Orderbook::new(1).with_level_expiry();
+        book.apply_delta_at(Price(100.0), Qty(1.0), true, 1, 1_000);
+        book.apply_delta_at(Price(99.0), Qty(1.0), true, 2, 1_000);
+        book.apply_delta_at(Price(101.0), Qty(1.0), false, 3, 1_000);
+        // Refresh one level on each side later
+        book.apply_delta_at(Price(100.0), Qty(2.0), true, 4, 5_000);
+        book.apply_delta_at(Price(101.0), Qty(3.0), false, 5, 5_000);
+
+        assert_eq!(book.expire_stale_levels(5_500, 1_000), 1);
+        assert_eq!(book.best_bid(), Some(100.0));
+        assert_eq!(book.bids.len(), 1);
+        assert_eq!(book.asks.len(), 1);
+
+        assert_eq!(book.expire_stale_levels(7_000, 1_000), 2);
+        assert!(book.bids.is_empty() && book.asks.is_empty());
+        assert_eq!(book.expired_levels.load(std::sync::atomic::Ordering::Relaxed), 3);
+
+        // Without expiry enabled nothing is tracked or removed
+        let mut plain = L2Orderbook::new(1);
+        plain.apply_delta_at(Price(100.0), Qty(1.0), true, 1, 0);
+        assert_eq!(plain.expire_stale_levels(i64::MAX, 0), 0);
+        assert_eq!(plain.bids.len(), 1);
+    }
+
+    #[test]
+    fn test_snapshot_request_served_immediately_and_rate_limited() {
+        let mut book = L2Orderbook::new(7);
+        book.apply_delta(Price(100.0), Qty(1.0), true, 1);
+        book.apply_delta(Price(101.0), Qty(2.0), false, 2);
+        let mut scheduler = SnapshotScheduler::new(SnapshotPolicy::default());
+        scheduler.record_delta(7, 0);
+
+        // Served right away, long before the periodic interval
+        let snap = scheduler.serve_request(&book, 1_000).unwrap();
+        assert_eq!(snap, OrderbookSnapshot {
+            symbol_hash: 7,
+            seq_id: 2,
+            bids: vec![(100.0, 1.0)],
+            asks: vec![(101.0, 2.0)],
+        });
+        assert_eq!(scheduler.deltas_since(7), 0);
+
+        // Spam within 100ms is dropped; after it another is served
+        assert!(scheduler.serve_request(&book, 50_000_000).is_none());
+        assert!(scheduler.serve_request(&book, 100_001_000).is_some());
+        assert_eq!(scheduler.snapshots_on_demand, 2);
+        assert_eq!(scheduler.requests_rate_limited, 1);
+    }
+
+    #[test]
+    fn test_snapshot_prices_sharing_a_key_are_merged() {
+        // Both round to the same 1e-8 key
+        assert_eq!(price_to_key(100.000000001), price_to_key(100.000000004));
+
+        let mut book = L2Orderbook::new(1);
+        book.apply_snapshot(&OrderbookSnapshot {
+            symbol_hash: 1,
+            seq_id: 1,
+            bids: vec![(100.000000001, 1.0), (100.000000004, 2.5), (99.0, 1.0)],
+            asks: vec![(101.0, 1.0)],
+        });
+
+        // One level holding the combined quantity - liquidity isn't understated
+        assert_eq!(book.bids.len(), 2);
+        assert_eq!(book.top_levels(1).0, vec![(100.0, 3.5)]);
+        assert_eq!(book.total_qty(true), 4.5);
+        assert_eq!(book.merged_levels.load(std::sync::atomic::Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn test_price_qty_serialize_transparently() {
+        assert_eq!(serde_json::to_string(&Price(101.5)).unwrap(), "101.5");
+        assert_eq!(serde_json::from_str::<Qty>("0.25").unwrap(), Qty(0.25));
+        assert_eq!(serde_json::to_string(&(Price(1.0), Qty(2.0))).unwrap(), "[1.0,2.0]");
+
+        assert_eq!(Price(101.0) - Price(100.0), Price(1.0));
+        assert_eq!(Qty(1.5) + Qty(0.5), Qty(2.0));
+        assert_eq!(Qty(3.0) / 2.0, Qty(1.5));
+        assert_eq!(Price(100.0) * Qty(2.5), 250.0);
+    }
+
+    #[test]
+    fn test_reconciliation_detects_corruption_and_resyncs() {
+        let reference = OrderbookSnapshot {
+            symbol_hash: 1,
+            seq_id: 10,
+            bids: vec![(100.0, 1.0), (99.0, 2.0)],
+            asks: vec![(101.0, 1.5)],
+        };
+        let mut book = L2Orderbook::new(1);
+        book.apply_snapshot(&reference);
+        let mut reconciler = BookReconciler::new(1_000);
+
+        assert!(reconciler.due(1, 0));
+        assert!(!reconciler.due(1, 500));
+        assert_eq!(reconciler.reconcile(&mut book, &reference), Reconciliation::Match);
+
+        // Silent corruption: a level changed and one appeared, seq untouched
+        *book.bids.get_mut(&price_to_key(99.0)).unwrap() += 1;
+        book.asks.insert(price_to_key(102.0), 5);
+        let healthy_hash = {
+            let mut b = L2Orderbook::new(1);
+            b.apply_snapshot(&reference);
+            b.state_hash()
+        };
+        assert_ne!(book.state_hash(), healthy_hash);
+
+        assert!(reconciler.due(1, 1_000));
+        assert_eq!(
+            reconciler.reconcile(&mut book, &reference),
+            Reconciliation::Resynced { diverged_levels: 2 }
+        );
+        assert_eq!(reconciler.reconciliation_failures, 1);
+        assert_eq!(book.state_hash(), healthy_hash);
+
+        // A reference from another seq can't be compared
+        let stale = OrderbookSnapshot { seq_id: 9, ..reference };
+        assert_eq!(reconciler.reconcile(&mut book, &stale), Reconciliation::Skipped);
+    }
+
+    #[test]
+    fn test_aggregated_depth_buckets_and_csv() {
+        let mut book = L2Orderbook::new(1);
+        book.apply_snapshot(&OrderbookSnapshot {
+            symbol_hash: 1,
+            seq_id: 1,
+            bids: vec![(99.9, 1.0), (99.5, 2.0), (99.0, 0.5), (98.7, 1.5)],
+            asks: vec![(100.1, 1.0), (100.5, 3.0), (101.0, 2.0), (101.2, 1.0)],
+        });
+
+        let (bids, asks) = book.aggregated_depth(1.0);
+        assert_eq!(bids, vec![(99.0, 3.5), (98.0, 1.5)]);
+        assert_eq!(asks, vec![(101.0, 6.0), (102.0, 1.0)]);
+        assert_eq!(book.aggregated_depth(0.0), book.top_levels(usize::MAX));
+
+        assert_eq!(
+            depth_csv(&bids, &asks),
+            "side,price,qty\nbid,99,3.5\nbid,98,1.5\nask,101,6\nask,102,1\n"
+        );
+    }
+
+    #[test]
+    fn test_apply_delta_with_prev_seq() {
+        let mut book = L2Orderbook::new(1);
+        assert!(book.apply_delta_with_prev(Price(100.0), Qty(1.0), true, Some(90), 100).is_applied());
+
+        // Seq steps of 7 per message are fine while `pu` chains
+        assert!(book.apply_delta_with_prev(Price(101.0), Qty(1.0), false, Some(100), 107).is_applied());
+        assert!(book.apply_delta_with_prev(Price(100.5), Qty(2.0), true, Some(107), 114).is_applied());
+        assert_eq!(book.gaps_detected.load(std::sync::atomic::Ordering::Relaxed), 0);
+
+        // A dropped message breaks the chain, book untouched
+        assert_eq!(
+            book.apply_delta_with_prev(Price(99.0), Qty(5.0), true, Some(121), 128),
+            DeltaOutcome::Gap { expected: 114, got: 121 }
+        );
+        assert_eq!(book.gaps_detected.load(std::sync::atomic::Ordering::Relaxed), 1);
+        assert_eq!(book.last_seq_id.load(std::sync::atomic::Ordering::Relaxed), 114);
+        assert_eq!(book.total_qty(true), 3.0);
+
+        // No prev_seq: falls back to last + 1
+        assert!(book.apply_delta_with_prev(Price(99.0), Qty(5.0), true, None, 115).is_applied());
+        assert_eq!(
+            book.apply_delta_with_prev(Price(99.0), Qty(5.0), true, None, 117),
+            DeltaOutcome::Gap { expected: 116, got: 117 }
+        );
+    }
+
+    #[test]
+    fn test_tick_scale_round_trips_extreme_prices() {
+        // 8-decimal token: rounding (not truncation) keeps the exact tick
+        let mut token = L2Orderbook::new(1);
+        for (seq, price) in [(1, 0.00000123), (2, 0.00004567), (3, 0.29)] {
+            assert!(token.apply_delta(Price(price), Qty(1_000.0), true, seq).is_applied());
+            assert_eq!(token.key_to_price(token.price_to_key(price).unwrap()), price);
+        }
+        assert_eq!(token.best_bid(), Some(0.29));
+        assert_eq!(token.bids.keys().next(), Some(&123));
+
+        // 7-digit index at 2 decimals
+        let mut index = L2Orderbook::new(2).with_tick_scale(100);
+        assert!(index.apply_delta(Price(9_123_456.78), Qty(1.0), false, 1).is_applied());
+        assert_eq!(index.best_ask(), Some(9_123_456.78));
+        assert_eq!(index.asks.keys().next(), Some(&912_345_678));
+
+        // Bad price: rejected, book and seq untouched, not saturated
+        assert_eq!(index.apply_delta(Price(1e17), Qty(1.0), false, 2), DeltaOutcome::InvalidPrice);
+        assert_eq!(token.apply_delta(Price(1e13), Qty(1.0), false, 4), DeltaOutcome::InvalidPrice);
+        assert_eq!(token.apply_delta(Price(f64::NAN), Qty(1.0), false, 4), DeltaOutcome::InvalidPrice);
+        assert_eq!(token.invalid_prices.load(std::sync::atomic::Ordering::Relaxed), 2);
+        assert_eq!(token.last_seq_id.load(std::sync::atomic::Ordering::Relaxed), 3);
+        assert!(token.asks.is_empty());
+        assert_eq!(index.asks.len(), 1);
+        assert!(token.apply_delta(Price(0.3), Qty(1.0), false, 4).is_applied());
+    }
+
+    #[test]
+    fn test_vwap_for_qty_pro_rates_last_level() {
+        let mut book = L2Orderbook::new(1);
+        book.apply_snapshot(&OrderbookSnapshot {
+            symbol_hash: 1,
+            seq_id: 1,
+            bids: vec![(99.0, 1.0), (98.0, 2.0)],
+            asks: vec![(101.0, 1.0), (102.0, 2.0), (104.0, 1.0)],
+        });
+
+        // 1 @ 101 + 0.5 of the 2 @ 102 -> (101 + 51) / 1.5
+        let vwap = book.vwap_for_qty(1.5, false).unwrap();
+        assert!((vwap - 152.0 / 1.5).abs() < 1e-9);
+        assert_eq!(book.vwap_for_qty(1.0, false), Some(101.0));
+        let sell = book.vwap_for_qty(2.0, true).unwrap();
+        assert!((sell - 98.5).abs() < 1e-9);
+
+        // Not enough depth: no VWAP, but the shortfall is visible
+        assert_eq!(book.vwap_for_qty(5.0, true), None);
+        assert_eq!(book.walk_depth(5.0, true), Some(DepthFill { avg_price: 295.0 / 3.0, filled_qty: 3.0 }));
+        assert_eq!(book.walk_depth(0.0, true), None);
+        assert_eq!(L2Orderbook::new(2).walk_depth(1.0, false), None);
+    }
+
+    #[test]
+    fn test_crossing_delta_is_reported_and_healed() {
+        let seed = |heal| {
+            let mut book = L2Orderbook::new(1).with_cross_heal(heal);
+            book.apply_snapshot(&OrderbookSnapshot {
+                symbol_hash: 1,
+                seq_id: 10,
+                bids: vec![(99.0, 1.0)],
+                asks: vec![(101.0, 1.0)],
+            });
+            book
+        };
+
+        // Drop: the bid through the ask never lands, the seq still advances
+        let mut book = seed(CrossHeal::DropLevel);
+        assert_eq!(book.apply_delta(Price(101.5), Qty(2.0), true, 11), DeltaOutcome::Crossed);
+        assert!(!book.is_crossed());
+        assert_eq!(book.best_bid(), Some(99.0));
+        assert_eq!(book.crossed_updates.load(std::sync::atomic::Ordering::Relaxed), 1);
+        assert!(book.apply_delta(Price(100.0), Qty(1.0), false, 12).is_applied());
+        assert!(!book.needs_resync());
+
+        // Snapshot: the cross is applied and the book flagged until resync
+        let mut book = seed(CrossHeal::RequestSnapshot);
+        assert_eq!(book.apply_delta(Price(99.0), Qty(1.0), false, 11), DeltaOutcome::Crossed);
+        assert!(book.is_crossed());
+        assert!(book.needs_resync());
+        assert_eq!(book.gaps_detected.load(std::sync::atomic::Ordering::Relaxed), 0);
+        let fresh = seed(CrossHeal::RequestSnapshot).snapshot();
+        book.resync(&OrderbookSnapshot { seq_id: 20, ..fresh });
+        assert!(!book.is_crossed());
+        assert!(!book.needs_resync());
+
+        // Off by default: crossing inserts apply silently
+        let mut book = L2Orderbook::new(1);
+        book.apply_delta(Price(100.0), Qty(1.0), false, 1);
+        assert!(book.apply_delta(Price(100.5), Qty(1.0), true, 2).is_applied());
+        assert!(book.is_crossed());
+    }
+
+    #[test]
+    fn test_snapshot_save_restore_round_trips_deep_book() {
+        let mut book = L2Orderbook::new(42);
+        let mut seq = 0;
+        for i in 0..400 {
+            seq += 1;
+            book.apply_delta(Price(67_500.0 - 0.01 * (i + 1) as f64), Qty(0.001 * (i % 37 + 1) as f64), true, seq);
+            seq += 1;
+            book.apply_delta(Price(67_500.0 + 0.01 * (i + 1) as f64), Qty(0.003 * (i % 11 + 1) as f64), false, seq);
+        }
+        let path = std::env::temp_dir().join(format!("book_snapshot_{}.json", std::process::id()));
+        book.save_snapshot(&path).unwrap();
+        assert_eq!(load_snapshot(&path).unwrap(), book.snapshot());
+
+        let mut restored = L2Orderbook::new(42);
+        assert!(restored.restore_snapshot(&path).unwrap());
+        assert_eq!(restored.stats().0, 400);
+        assert_eq!(restored.stats().1, 400);
+        assert_eq!(restored.state_hash(), book.state_hash());
+        assert_eq!(restored.last_seq_id.load(std::sync::atomic::Ordering::Relaxed), 800);
+
+        // Deltas continue from the saved seq; a stale one still gaps
+        assert_eq!(restored.apply_delta(Price(67_499.0), Qty(1.0), true, 801), DeltaOutcome::Applied);
+        assert!(matches!(restored.apply_delta(Price(67_499.0), Qty(2.0), true, 900), DeltaOutcome::Gap { .. }));
+
+        assert!(L2Orderbook::new(7).restore_snapshot(&path).is_err());
+        std::fs::remove_file(&path).unwrap();
+        assert!(load_snapshot(&path).is_err());
+    }
+
+    #[test]
+    fn test_bbo_events_only_on_touch_changes() {
+        let (tx, rx) = crossbeam_channel::unbounded();
+        let mut book = L2Orderbook::new(9).with_bbo_events(tx);
+        book.apply_snapshot(&OrderbookSnapshot {
+            symbol_hash: 9,
+            seq_id: 100,
+            bids: (1..=20).map(|i| (100.0 - i as f64, 1.0)).collect(),
+            asks: (1..=20).map(|i| (100.0 + i as f64, 1.0)).collect(),
+        });
+        let first = rx.try_recv().unwrap();
+        assert_eq!((first.bid, first.ask, first.seq_id), (Some(99.0), Some(101.0), 100));
+
+        // Deep inserts, updates and removals leave the touch alone
+        for (seq, (price, qty, is_bid)) in [(95.0, 3.0, true), (90.0, 0.0, true), (110.0, 7.0, false), (104.5, 2.0, false)]
+            .into_iter()
+            .enumerate()
+        {
+            assert!(book.apply_delta(Price(price), Qty(qty), is_bid, 101 + seq as u64).is_applied());
+        }
+        assert!(rx.try_recv().is_err());
+
+        // Size change at the best bid: exactly one event
+        book.apply_delta(Price(99.0), Qty(4.0), true, 105);
+        let update = rx.try_recv().unwrap();
+        assert_eq!(update, BboUpdate { ts_ns: update.ts_ns, symbol_hash: 9, bid: Some(99.0), bid_size: 4.0, ask: Some(101.0), ask_size: 1.0, seq_id: 105 });
+        assert!(update.ts_ns > 0);
+        assert!(rx.try_recv().is_err());
+
+        // Best ask removed: the next level becomes the touch
+        book.apply_delta(Price(101.0), Qty(0.0), false, 106);
+        assert_eq!(rx.try_iter().map(|u| u.ask).collect::<Vec<_>>(), vec![Some(102.0)]);
+
+        // A closed channel is counted, not fatal
+        drop(rx);
+        book.apply_delta(Price(99.5), Qty(1.0), true, 107);
+        assert_eq!(book.bbo_events_dropped.load(std::sync::atomic::Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn test_level_cap_keeps_levels_nearest_the_touch() {
+        const CAP: usize = 50;
+        let mut book = L2Orderbook::new(3).with_max_levels_per_side(CAP);
+        let mut seq = 0;
+        // Worst bid first, so every later insert improves the touch
+        for i in 0..CAP + 10 {
+            seq += 1;
+            book.apply_delta(Price(1_000.0 + i as f64), Qty(1.0), true, seq);
+        }
+        assert_eq!(book.bids.len(), CAP);
+        assert_eq!(book.best_bid(), Some(1_059.0));
+        assert_eq!(book.key_to_price(*book.bids.keys().next().unwrap()), 1_010.0);
+        assert_eq!(book.trimmed_levels.load(std::sync::atomic::Ordering::Relaxed), 10);
+
+        // A level further out than the whole capped side is dropped itself
+        seq += 1;
+        book.apply_delta(Price(900.0), Qty(1.0), true, seq);
+        assert_eq!(book.bids.len(), CAP);
+        assert_eq!(book.key_to_price(*book.bids.keys().next().unwrap()), 1_010.0);
+
+        // Updates of existing levels and removals don't trim
+        seq += 1;
+        book.apply_delta(Price(1_020.0), Qty(5.0), true, seq);
+        seq += 1;
+        book.apply_delta(Price(1_030.0), Qty(0.0), true, seq);
+        assert_eq!(book.bids.len(), CAP - 1);
+        assert_eq!(book.trimmed_levels.load(std::sync::atomic::Ordering::Relaxed), 11);
+
+        // Asks trim from the top; snapshots are capped too
+        book.apply_snapshot(&OrderbookSnapshot {
+            symbol_hash: 3,
+            seq_id: 1_000,
+            bids: vec![],
+            asks: (0..CAP + 5).map(|i| (2_000.0 + i as f64, 1.0)).collect(),
+        });
+        assert_eq!(book.asks.len(), CAP);
+        assert_eq!(book.best_ask(), Some(2_000.0));
+        assert_eq!(book.key_to_price(*book.asks.keys().next_back().unwrap()), 2_049.0);
+    }
+
+    #[test]
+    fn test_trimmed_level_keeps_no_expiry_stamp() {
+        let mut book = L2Orderbook::new(3).with_max_levels_per_side(2).with_level_expiry();
+        book.apply_delta_at(Price(100.0), Qty(1.0), true, 1, 0);
+        book.apply_delta_at(Price(101.0), Qty(1.0), true, 2, 0);
+        // Further out than the capped side: trimmed on arrival
+        book.apply_delta_at(Price(99.0), Qty(1.0), true, 3, 0);
+        assert_eq!(book.bids.len(), 2);
+        assert_eq!(book.level_times.as_ref().unwrap().bids.len(), 2);
+
+        // Re-added without a stamp, the level starts aging at the next sweep
+        book.apply_delta_at(Price(101.0), Qty(0.0), true, 4, 500);
+        book.apply_delta(Price(99.0), Qty(1.0), true, 5);
+        book.apply_delta_at(Price(100.0), Qty(2.0), true, 6, 500);
+        assert_eq!(book.expire_stale_levels(1_000, 800), 0);
+        assert_eq!(book.bids.len(), 2);
+    }
+
+    #[test]
+    fn test_weighted_mid_and_pressure_on_bid_skewed_book() {
+        let mut book = L2Orderbook::new(5);
+        book.apply_snapshot(&OrderbookSnapshot {
+            symbol_hash: 5,
+            seq_id: 1,
+            bids: vec![(99.0, 10.0), (98.0, 20.0), (97.0, 30.0)],
+            asks: vec![(101.0, 1.0), (102.0, 1.0), (103.0, 2.0)],
+        });
+        let mid = book.mid_price().unwrap();
+        assert_eq!(mid, 100.0);
+
+        // Top level only: (99*10 + 101*1) / 11
+        assert!((book.weighted_mid(1).unwrap() - 1_091.0 / 11.0).abs() < 1e-9);
+        let deep = book.weighted_mid(3).unwrap();
+        assert!(deep < mid && deep > 97.0, "{}", deep);
+        assert!((deep - (99.0 * 10.0 + 98.0 * 20.0 + 97.0 * 30.0 + 101.0 + 102.0 + 103.0 * 2.0) / 64.0).abs() < 1e-9);
+
+        assert_eq!(book.book_pressure(1), Some(10.0));
+        assert_eq!(book.book_pressure(3), Some(15.0));
+        assert_eq!(book.book_pressure(100), Some(15.0));
+
+        book.apply_delta(Price(101.0), Qty(0.0), false, 2);
+        book.apply_delta(Price(102.0), Qty(0.0), false, 3);
+        book.apply_delta(Price(103.0), Qty(0.0), false, 4);
+        assert_eq!(book.weighted_mid(3), None);
+        assert_eq!(book.book_pressure(3), None);
+        assert_eq!(L2Orderbook::new(6).weighted_mid(5), None);
+    }
+}
+
+
+#[cfg(test)]
+mod proptests;
@@ -0,0 +1,162 @@
+// Orderbook property tests — random snapshot/delta feeds
+//
+// Each case drives an L2Orderbook and a plain reference model with the same
+// ops; deltas may delete, skip ahead or replay old seqs. After every op:
+// - the book matches the model level for level (counts, no phantom levels)
+// - a book fed a consistent two-sided feed is never crossed
+// - last_seq_id never moves backwards across deltas
+// - rejected deltas leave the book untouched
+
+use super::orderbook::*;
+use proptest::prelude::*;
+use std::collections::BTreeMap;
+use std::sync::atomic::Ordering;
+
+const MID: f64 = 100.0;
+const TICK: f64 = 0.01;
+const LOT: f64 = 0.001;
+
+#[derive(Clone, Debug)]
+enum Op {
+    Delta {
+        is_bid: bool,
+        ticks: u32,     // Distance from MID, >= 1 keeps the feed uncrossed
+        lots: u32,      // 0 = delete
+        seq_skew: i64,  // 0 = in order, > 0 gap, < 0 replay
+    },
+    Snapshot {
+        bids: Vec<(u32, u32)>,
+        asks: Vec<(u32, u32)>,
+    },
+}
+
+fn level() -> impl Strategy<Value = (u32, u32)> {
+    (1u32..=40, 1u32..=5_000)
+}
+
+fn op() -> impl Strategy<Value = Op> {
+    let seq_skew = prop_oneof![8 => Just(0i64), 1 => 1i64..5, 1 => -5i64..0];
+    prop_oneof![
+        12 => (any::<bool>(), 1u32..=40, prop_oneof![1 => Just(0u32), 4 => 1u32..=5_000], seq_skew)
+            .prop_map(|(is_bid, ticks, lots, seq_skew)| Op::Delta { is_bid, ticks, lots, seq_skew }),
+        1 => (prop::collection::vec(level(), 0..20), prop::collection::vec(level(), 0..20))
+            .prop_map(|(bids, asks)| Op::Snapshot { bids, asks }),
+    ]
+}
+
+fn price(is_bid: bool, ticks: u32) -> f64 {
+    if is_bid {
+        MID - TICK * ticks as f64
+    } else {
+        MID + TICK * ticks as f64
+    }
+}
+
+/// What the book should hold: price key -> qty units per side
+#[derive(Default)]
+struct Model {
+    bids: BTreeMap<i64, i64>,
+    asks: BTreeMap<i64, i64>,
+    last_seq: u64,
+}
+
+impl Model {
+    fn side(&mut self, is_bid: bool) -> &mut BTreeMap<i64, i64> {
+        if is_bid {
+            &mut self.bids
+        } else {
+            &mut self.asks
+        }
+    }
+}
+
+/// Run `ops` through a fresh book and the model, checking after each op
+fn run(ops: &[Op], book: &mut L2Orderbook, expect_uncrossed: bool) -> Result<(), TestCaseError> {
+    let mut model = Model::default();
+    let mut next_snapshot_seq = 1_000_000;
+
+    for op in ops {
+        let seq_before = book.last_seq_id.load(Ordering::Relaxed);
+        match op {
+            &Op::Delta { is_bid, ticks, lots, seq_skew } => {
+                let seq = (model.last_seq as i64 + 1 + seq_skew).max(0) as u64;
+                let p = price(is_bid, ticks);
+                let outcome = book.apply_delta(Price(p), Qty(lots as f64 * LOT), is_bid, seq);
+
+                let in_order = model.last_seq == 0 || seq == model.last_seq + 1;
+                prop_assert_eq!(outcome.is_applied() || outcome == DeltaOutcome::Crossed, in_order);
+                if let DeltaOutcome::Gap { expected, got } = outcome {
+                    prop_assert_eq!((expected, got), (model.last_seq + 1, seq));
+                }
+                if in_order {
+                    let key = book.price_to_key(p).unwrap();
+                    let units = book.qty_to_units(lots as f64 * LOT);
+                    if units == 0 {
+                        model.side(is_bid).remove(&key);
+                    } else if outcome.is_applied() {
+                        model.side(is_bid).insert(key, units);
+                    }
+                    model.last_seq = seq;
+                }
+                prop_assert!(book.last_seq_id.load(Ordering::Relaxed) >= seq_before);
+            }
+            Op::Snapshot { bids, asks } => {
+                next_snapshot_seq += 1_000;
+                let levels = |is_bid: bool, levels: &[(u32, u32)]| -> Levels {
+                    levels.iter().map(|&(t, l)| (price(is_bid, t), l as f64 * LOT)).collect()
+                };
+                book.apply_snapshot(&OrderbookSnapshot {
+                    symbol_hash: book.symbol_hash,
+                    seq_id: next_snapshot_seq,
+                    bids: levels(true, bids),
+                    asks: levels(false, asks),
+                });
+
+                model = Model { last_seq: next_snapshot_seq, ..Model::default() };
+                for (is_bid, levels) in [(true, bids), (false, asks)] {
+                    for &(ticks, lots) in levels {
+                        let key = book.price_to_key(price(is_bid, ticks)).unwrap();
+                        *model.side(is_bid).entry(key).or_insert(0) += book.qty_to_units(lots as f64 * LOT);
+                    }
+                }
+            }
+        }
+
+        prop_assert_eq!(&book.bids, &model.bids);
+        prop_assert_eq!(&book.asks, &model.asks);
+        prop_assert_eq!(book.last_seq_id.load(Ordering::Relaxed), model.last_seq);
+        prop_assert!(book.bids.values().chain(book.asks.values()).all(|&units| units > 0));
+        if expect_uncrossed {
+            prop_assert!(!book.is_crossed());
+        }
+    }
+    Ok(())
+}
+
+proptest! {
+    #[test]
+    fn consistent_feed_matches_model_and_never_crosses(ops in prop::collection::vec(op(), 1..200)) {
+        run(&ops, &mut L2Orderbook::new(1), true)?;
+    }
+
+    #[test]
+    fn lot_sized_book_matches_model(ops in prop::collection::vec(op(), 1..200)) {
+        run(&ops, &mut L2Orderbook::with_lot_size(1, LOT).unwrap(), true)?;
+    }
+
+    /// Bids and asks drawn from one overlapping range: DropLevel keeps the
+    /// book uncrossed no matter what the feed does
+    #[test]
+    fn drop_level_heal_never_crosses(
+        deltas in prop::collection::vec((any::<bool>(), 1u32..=20, 0u32..=3), 1..200)
+    ) {
+        let mut book = L2Orderbook::new(1).with_cross_heal(CrossHeal::DropLevel);
+        for (i, &(is_bid, ticks, lots)) in deltas.iter().enumerate() {
+            let seq = i as u64 + 1;
+            let outcome = book.apply_delta(Price(MID + TICK * ticks as f64), Qty(lots as f64), is_bid, seq);
+            prop_assert!(outcome.is_applied() || outcome == DeltaOutcome::Crossed);
+            prop_assert!(!book.is_crossed());
+            prop_assert_eq!(book.last_seq_id.load(Ordering::Relaxed), seq);
+        }
+    }
+}
@@ -0,0 +1,760 @@
+// Processor module — Sharded Book Update Processing
+//
+// Features:
+// - N worker threads, each owning the books of its symbols
+// - Routing by symbol hash preserves per-symbol ordering
+// - Per-shard atomic counters merged on read (no shared hot counter)
+// - Bounded shard queues for backpressure
+// - Optional per-symbol tick filter drops sub-threshold deltas before they apply
+// - Per-symbol price history recorded on every applied delta
+// - Per-shard strategy hooks; their orders are routed to the execution engine
+// - Gap -> snapshot resync requests; deltas buffered by seq and replayed on top
+
+pub mod processor {
+    use crate::execution::{ExecError, ExecutionEngine, OrderAck, OrderRequest};
+    use crate::feed::SnapshotSource;
+    use crate::orderbook::{
+        DeltaOutcome, L2Orderbook, OrderbookSnapshot, Price, PriceHistory, PricePoint, Qty, TickFilter, TickFilterConfig,
+    };
+    use crossbeam_channel::{bounded, unbounded, Receiver, Sender};
+    use std::collections::{BTreeMap, HashMap};
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::{Arc, Mutex, PoisonError};
+    use std::thread::JoinHandle;
+
+    /// Single price level update for one symbol
+    #[derive(Clone, Copy, Debug, Default, PartialEq)]
+    pub struct BookUpdate {
+        pub symbol_hash: u64,
+        pub price: Price,
+        pub qty: Qty,           // 0 = delete level
+        pub is_bid: bool,
+        pub seq_id: u64,
+        pub ts_ns: i64,
+    }
+
+    /// Strategy logic run inside a shard after every applied delta
+    ///
+    /// Each shard owns its own hook instance, so implementations need no
+    /// locking; returned orders are queued for the execution engine.
+    pub trait StrategyHook {
+        fn on_book_update(&mut self, book: &L2Orderbook, update: &BookUpdate) -> Option<OrderRequest>;
+    }
+
+    /// Counters owned by one shard
+    #[derive(Debug, Default)]
+    pub struct ShardMetrics {
+        pub updates_applied: AtomicU64,
+        pub gaps_detected: AtomicU64,
+        pub crossed_updates: AtomicU64,
+        pub invalid_prices: AtomicU64,
+        pub symbols: AtomicU64,
+        pub orders_emitted: AtomicU64,
+        pub resyncs_requested: AtomicU64,
+        pub resyncs_applied: AtomicU64,
+        pub replayed_updates: AtomicU64,    // Buffered deltas applied after a snapshot
+        pub filtered_ticks: AtomicU64,      // Dropped by the tick filter
+    }
+
+    /// Metrics merged across shards
+    #[derive(Clone, Copy, Debug, Default, PartialEq)]
+    pub struct ProcessorStats {
+        pub updates_applied: u64,
+        pub gaps_detected: u64,
+        pub crossed_updates: u64,
+        pub invalid_prices: u64,
+        pub symbols: u64,
+        pub orders_emitted: u64,
+        pub resyncs_requested: u64,
+        pub resyncs_applied: u64,
+        pub replayed_updates: u64,
+        pub filtered_ticks: u64,
+    }
+
+    /// Pool sizing and per-shard filtering
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub struct ProcessorConfig {
+        pub shards: usize,
+        pub capacity: usize,                // Queued updates per shard
+        pub tick_filter: TickFilterConfig,  // Applied per symbol, 0 thresholds = unfiltered
+        pub history_capacity: usize,        // Price points kept per symbol, 0 = no history
+    }
+
+    impl Default for ProcessorConfig {
+        fn default() -> Self {
+            Self {
+                shards: 1,
+                capacity: 1024,
+                tick_filter: TickFilterConfig::default(),
+                history_capacity: 256,
+            }
+        }
+    }
+
+    type ShardBooks = HashMap<u64, L2Orderbook>;
+
+    /// One shard's price histories, shared with `ShardedProcessor::recent`
+    type ShardHistories = Arc<Mutex<HashMap<u64, PriceHistory>>>;
+
+    /// Work for one shard, in symbol order
+    enum ShardMsg {
+        Update(BookUpdate),
+        Snapshot(OrderbookSnapshot),
+    }
+
+    fn book_entry<'a>(books: &'a mut ShardBooks, m: &ShardMetrics, symbol_hash: u64) -> &'a mut L2Orderbook {
+        books.entry(symbol_hash).or_insert_with(|| {
+            m.symbols.fetch_add(1, Ordering::Relaxed);
+            L2Orderbook::new(symbol_hash)
+        })
+    }
+
+    /// One worker's books, filters, hook and resync buffers
+    struct Shard {
+        books: ShardBooks,
+        filters: HashMap<u64, TickFilter>,
+        tick_filter: TickFilterConfig,
+        histories: ShardHistories,
+        history_capacity: usize,
+        awaiting_snapshot: HashMap<u64, BTreeMap<u64, BookUpdate>>,  // Buffered by seq
+        max_buffered: usize,
+        hook: Option<Box<dyn StrategyHook + Send>>,
+        metrics: Arc<ShardMetrics>,
+        order_tx: Sender<OrderRequest>,
+        resync_tx: Sender<u64>,
+    }
+
+    impl Shard {
+        fn on_update(&mut self, update: BookUpdate) {
+            // Resync in flight: hold deltas until the snapshot lands
+            if let Some(buffer) = self.awaiting_snapshot.get_mut(&update.symbol_hash) {
+                buffer.insert(update.seq_id, update);
+                if buffer.len() > self.max_buffered {
+                    buffer.pop_first();
+                }
+                return;
+            }
+            if let DeltaOutcome::Gap { .. } = self.apply(update) {
+                self.request_resync(update);
+            }
+        }
+
+        fn apply(&mut self, update: BookUpdate) -> DeltaOutcome {
+            let m = &self.metrics;
+            let book = book_entry(&mut self.books, m, update.symbol_hash);
+            let tick_filter = self.tick_filter;
+            let filter = self.filters.entry(update.symbol_hash).or_insert_with(|| TickFilter::new(tick_filter));
+            let outcome = book.apply_filtered_delta(filter, update.price, update.qty, update.is_bid, update.seq_id);
+            match outcome {
+                DeltaOutcome::Applied => {
+                    if self.history_capacity > 0 {
+                        let mut histories = self.histories.lock().unwrap_or_else(PoisonError::into_inner);
+                        let capacity = self.history_capacity;
+                        histories
+                            .entry(update.symbol_hash)
+                            .or_insert_with(|| PriceHistory::new(capacity))
+                            .record(book, update.ts_ns);
+                    }
+                    m.updates_applied.fetch_add(1, Ordering::Relaxed);
+                    let order = self.hook.as_mut().and_then(|h| h.on_book_update(book, &update));
+                    // Engine side gone = shutting down; the order is moot
+                    if order.is_some_and(|o| self.order_tx.send(o).is_ok()) {
+                        m.orders_emitted.fetch_add(1, Ordering::Relaxed);
+                    }
+                }
+                DeltaOutcome::Gap { expected, got } => {
+                    m.gaps_detected.fetch_add(1, Ordering::Relaxed);
+                    tracing::warn!(symbol_hash = update.symbol_hash, expected, got, "sequence gap, resync required");
+                }
+                DeltaOutcome::Crossed => {
+                    m.crossed_updates.fetch_add(1, Ordering::Relaxed);
+                }
+                DeltaOutcome::InvalidPrice => {
+                    m.invalid_prices.fetch_add(1, Ordering::Relaxed);
+                    tracing::warn!(symbol_hash = update.symbol_hash, price = update.price.0, "unrepresentable price dropped");
+                }
+                DeltaOutcome::Filtered => {
+                    m.filtered_ticks.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+            outcome
+        }
+
+        /// Start buffering at `first` and ask the feed side for a snapshot
+        fn request_resync(&mut self, first: BookUpdate) {
+            self.awaiting_snapshot.insert(first.symbol_hash, BTreeMap::from([(first.seq_id, first)]));
+            self.metrics.resyncs_requested.fetch_add(1, Ordering::Relaxed);
+            // No resync consumer = shutting down
+            let _ = self.resync_tx.send(first.symbol_hash);
+        }
+
+        /// Rebuild from the snapshot, then replay buffered deltas newer than it
+        fn on_snapshot(&mut self, snapshot: OrderbookSnapshot) {
+            let m = &self.metrics;
+            book_entry(&mut self.books, m, snapshot.symbol_hash).resync(&snapshot);
+            m.resyncs_applied.fetch_add(1, Ordering::Relaxed);
+
+            let buffered = self.awaiting_snapshot.remove(&snapshot.symbol_hash).unwrap_or_default();
+            let mut pending = buffered.range(snapshot.seq_id + 1..).map(|(_, &u)| u);
+            while let Some(update) = pending.next() {
+                match self.apply(update) {
+                    // Still a hole past the snapshot: keep the rest, ask again
+                    DeltaOutcome::Gap { .. } => {
+                        self.request_resync(update);
+                        for rest in pending.by_ref() {
+                            self.on_update(rest);
+                        }
+                    }
+                    DeltaOutcome::Applied => {
+                        self.metrics.replayed_updates.fetch_add(1, Ordering::Relaxed);
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    /// Pool of processor shards keyed by symbol hash
+    pub struct ShardedProcessor {
+        senders: Vec<Sender<ShardMsg>>,
+        workers: Vec<JoinHandle<ShardBooks>>,
+        metrics: Vec<Arc<ShardMetrics>>,
+        histories: Vec<ShardHistories>,
+        orders: Receiver<OrderRequest>,
+        resync_tx: Sender<u64>,
+        resyncs: Receiver<u64>,
+    }
+
+    impl ShardedProcessor {
+        /// Spawn `shards` workers, each with a queue of `capacity` updates
+        pub fn new(shards: usize, capacity: usize) -> Self {
+            Self::with_config(ProcessorConfig { shards, capacity, ..Default::default() }, |_| None)
+        }
+
+        /// Like `new`, with a strategy hook per shard built by `hook(shard)`
+        pub fn with_strategy<F>(shards: usize, capacity: usize, hook: F) -> Self
+        where
+            F: Fn(usize) -> Box<dyn StrategyHook + Send>,
+        {
+            Self::with_config(ProcessorConfig { shards, capacity, ..Default::default() }, |shard| Some(hook(shard)))
+        }
+
+        /// Spawn workers per `config`; `hook(shard)` returns None for shards
+        /// without a strategy
+        pub fn with_config<F>(config: ProcessorConfig, hook: F) -> Self
+        where
+            F: Fn(usize) -> Option<Box<dyn StrategyHook + Send>>,
+        {
+            let ProcessorConfig { shards, capacity, tick_filter, history_capacity } = config;
+            let shards = shards.max(1);
+            let mut senders = Vec::with_capacity(shards);
+            let mut workers = Vec::with_capacity(shards);
+            let mut metrics = Vec::with_capacity(shards);
+            let mut histories = Vec::with_capacity(shards);
+            let (order_tx, orders) = unbounded::<OrderRequest>();
+            let (resync_tx, resyncs) = unbounded::<u64>();
+
+            for shard in 0..shards {
+                let (tx, rx) = bounded::<ShardMsg>(capacity);
+                let shard_metrics = Arc::new(ShardMetrics::default());
+                let shard_histories = ShardHistories::default();
+                let mut worker = Shard {
+                    books: ShardBooks::new(),
+                    filters: HashMap::new(),
+                    tick_filter,
+                    histories: shard_histories.clone(),
+                    history_capacity,
+                    awaiting_snapshot: HashMap::new(),
+                    max_buffered: capacity.max(1),
+                    hook: hook(shard),
+                    metrics: shard_metrics.clone(),
+                    order_tx: order_tx.clone(),
+                    resync_tx: resync_tx.clone(),
+                };
+
+                workers.push(std::thread::spawn(move || {
+                    // Runs until every sender is dropped
+                    for msg in rx.iter() {
+                        match msg {
+                            ShardMsg::Update(update) => worker.on_update(update),
+                            ShardMsg::Snapshot(snapshot) => worker.on_snapshot(snapshot),
+                        }
+                    }
+                    worker.books
+                }));
+                senders.push(tx);
+                metrics.push(shard_metrics);
+                histories.push(shard_histories);
+            }
+
+            Self {
+                senders,
+                workers,
+                metrics,
+                histories,
+                orders,
+                resync_tx,
+                resyncs,
+            }
+        }
+
+        pub fn shards(&self) -> usize {
+            self.senders.len()
+        }
+
+        /// Shard owning a symbol - stable for the pool's lifetime
+        #[inline(always)]
+        pub fn shard_for(&self, symbol_hash: u64) -> usize {
+            (symbol_hash % self.senders.len() as u64) as usize
+        }
+
+        /// Queue an update on its symbol's shard (blocks while the shard is full)
+        #[inline(always)]
+        pub fn route(&self, update: BookUpdate) -> Result<(), &'static str> {
+            self.senders[self.shard_for(update.symbol_hash)]
+                .send(ShardMsg::Update(update))
+                .map_err(|_| "SHARD_DISCONNECTED")
+        }
+
+        /// Queue a snapshot on its symbol's shard, behind any queued updates
+        /// Deltas buffered since the gap with seq > `snapshot.seq_id` are
+        /// replayed on top; older ones are discarded
+        pub fn apply_snapshot(&self, snapshot: OrderbookSnapshot) -> Result<(), &'static str> {
+            self.senders[self.shard_for(snapshot.symbol_hash)]
+                .send(ShardMsg::Snapshot(snapshot))
+                .map_err(|_| "SHARD_DISCONNECTED")
+        }
+
+        /// Symbols whose book gapped and needs a snapshot, one per gap
+        pub fn resync_requests(&self) -> &Receiver<u64> {
+            &self.resyncs
+        }
+
+        /// Fetch and apply a snapshot for every queued resync (non-blocking)
+        /// `symbols` maps hashes to exchange names; failed fetches are
+        /// re-queued for the next call. Returns the snapshots applied.
+        pub fn serve_resyncs(&self, source: &mut dyn SnapshotSource, symbols: &HashMap<u64, String>) -> usize {
+            let requests: Vec<u64> = self.resyncs.try_iter().collect();
+            let mut applied = 0;
+            for symbol_hash in requests {
+                let Some(symbol) = symbols.get(&symbol_hash) else {
+                    tracing::warn!(symbol_hash, "resync requested for unknown symbol");
+                    continue;
+                };
+                match source.fetch(symbol) {
+                    Ok(snapshot) => {
+                        // Shards gone = shutting down
+                        if self.apply_snapshot(snapshot).is_ok() {
+                            applied += 1;
+                        }
+                    }
+                    Err(e) => {
+                        tracing::warn!(symbol = %symbol, error = e, "snapshot fetch failed, will retry");
+                        let _ = self.resync_tx.send(symbol_hash);
+                    }
+                }
+            }
+            applied
+        }
+
+        /// Counters of a single shard
+        pub fn shard_metrics(&self, shard: usize) -> &ShardMetrics {
+            &self.metrics[shard]
+        }
+
+        /// Counters summed across all shards
+        pub fn stats(&self) -> ProcessorStats {
+            self.metrics.iter().fold(ProcessorStats::default(), |acc, m| ProcessorStats {
+                updates_applied: acc.updates_applied + m.updates_applied.load(Ordering::Relaxed),
+                gaps_detected: acc.gaps_detected + m.gaps_detected.load(Ordering::Relaxed),
+                crossed_updates: acc.crossed_updates + m.crossed_updates.load(Ordering::Relaxed),
+                invalid_prices: acc.invalid_prices + m.invalid_prices.load(Ordering::Relaxed),
+                symbols: acc.symbols + m.symbols.load(Ordering::Relaxed),
+                orders_emitted: acc.orders_emitted + m.orders_emitted.load(Ordering::Relaxed),
+                resyncs_requested: acc.resyncs_requested + m.resyncs_requested.load(Ordering::Relaxed),
+                resyncs_applied: acc.resyncs_applied + m.resyncs_applied.load(Ordering::Relaxed),
+                replayed_updates: acc.replayed_updates + m.replayed_updates.load(Ordering::Relaxed),
+                filtered_ticks: acc.filtered_ticks + m.filtered_ticks.load(Ordering::Relaxed),
+            })
+        }
+
+        /// Last `n` price points recorded for a symbol, oldest first
+        /// Empty for unknown symbols or with history disabled
+        pub fn recent(&self, symbol_hash: u64, n: usize) -> Vec<PricePoint> {
+            let histories = self.histories[self.shard_for(symbol_hash)].lock().unwrap_or_else(PoisonError::into_inner);
+            histories.get(&symbol_hash).map_or_else(Vec::new, |h| h.recent(n).to_vec())
+        }
+
+        /// Orders emitted by strategy hooks, in arrival order across shards
+        pub fn orders(&self) -> &Receiver<OrderRequest> {
+            &self.orders
+        }
+
+        /// Submit every queued strategy order to `engine` (non-blocking)
+        pub fn route_orders(&self, engine: &mut ExecutionEngine) -> Vec<Result<OrderAck, ExecError>> {
+            self.orders.try_iter().map(|order| engine.submit(&order)).collect()
+        }
+
+        /// Drain all queues, stop the workers and hand back every book
+        pub fn shutdown(self) -> HashMap<u64, L2Orderbook> {
+            drop(self.senders);
+            let mut books = HashMap::new();
+            for worker in self.workers {
+                match worker.join() {
+                    Ok(shard_books) => books.extend(shard_books),
+                    Err(_) => tracing::warn!("processor shard panicked; its books are lost"),
+                }
+            }
+            books
+        }
+    }
+}
+
+pub use processor::*;
+
+#[cfg(test)]
+mod tests {
+    use super::processor::*;
+    use crate::execution::{ExecutionEngine, OrderRequest, OrderType, Side, FIXED_SCALE};
+    use crate::feed::SnapshotSource;
+    use crate::orderbook::{symbol_hash, L2Orderbook, OrderbookSnapshot, Price, PriceHistory, Qty, TickFilterConfig};
+    use std::collections::HashMap;
+    use std::sync::atomic::Ordering;
+
+    #[test]
+    fn test_two_shards_preserve_per_symbol_order_and_merge_metrics() {
+        let pool = ShardedProcessor::new(2, 64);
+        let (btc, eth) = (10u64, 11u64);
+        assert_ne!(pool.shard_for(btc), pool.shard_for(eth));
+
+        // Interleave both symbols; any reordering would show up as a gap
+        for seq in 1..=1_000u64 {
+            for symbol_hash in [btc, eth] {
+                let update = BookUpdate {
+                    symbol_hash,
+                    price: Price(100.0 + (seq % 10) as f64),
+                    qty: Qty((seq % 3) as f64),
+                    is_bid: seq % 2 == 0,
+                    seq_id: seq,
+                    ts_ns: seq as i64,
+                };
+                pool.route(update).unwrap();
+            }
+        }
+
+        let (btc_shard, eth_shard) = (pool.shard_for(btc), pool.shard_for(eth));
+        let shard_updates = |pool: &ShardedProcessor, shard| {
+            pool.shard_metrics(shard).updates_applied.load(Ordering::Relaxed)
+        };
+        while pool.stats().updates_applied + pool.stats().gaps_detected < 2_000 {
+            std::thread::yield_now();
+        }
+        assert_eq!(shard_updates(&pool, btc_shard), 1_000);
+        assert_eq!(shard_updates(&pool, eth_shard), 1_000);
+        assert_eq!(
+            pool.stats(),
+            ProcessorStats {
+                updates_applied: 2_000,
+                gaps_detected: 0,
+                crossed_updates: 0,
+                invalid_prices: 0,
+                symbols: 2,
+                orders_emitted: 0,
+                resyncs_requested: 0,
+                resyncs_applied: 0,
+                replayed_updates: 0,
+                filtered_ticks: 0,
+            }
+        );
+
+        let books = pool.shutdown();
+        assert_eq!(books[&btc].last_seq_id.load(Ordering::Relaxed), 1_000);
+        assert_eq!(books[&eth].last_seq_id.load(Ordering::Relaxed), 1_000);
+    }
+
+    /// Joins the bid when the spread is wider than `max_spread_bps`
+    struct WideSpreadMaker {
+        max_spread_bps: i64,
+        next_key: u64,
+    }
+
+    impl StrategyHook for WideSpreadMaker {
+        fn on_book_update(&mut self, book: &L2Orderbook, update: &BookUpdate) -> Option<OrderRequest> {
+            if book.spread_bps()? <= self.max_spread_bps {
+                return None;
+            }
+            self.next_key += 1;
+            Some(OrderRequest {
+                symbol_hash: update.symbol_hash,
+                side: Side::Buy,
+                quantity: FIXED_SCALE,
+                price: (book.best_bid()? * FIXED_SCALE as f64) as i64,
+                order_type: OrderType::Limit,
+                idempotency_key: update.seq_id,
+                timestamp_ns: update.ts_ns,
+                ..Default::default()
+            })
+        }
+    }
+
+    #[test]
+    fn test_strategy_hook_orders_reach_execution_engine() {
+        let pool = ShardedProcessor::with_strategy(2, 64, |_| {
+            Box::new(WideSpreadMaker { max_spread_bps: 50, next_key: 0 })
+        });
+        let update = |seq_id, price, is_bid| BookUpdate {
+            symbol_hash: 10,
+            price: Price(price),
+            qty: Qty(1.0),
+            is_bid,
+            seq_id,
+            ts_ns: seq_id as i64,
+        };
+        pool.route(update(1, 100.0, true)).unwrap();    // One-sided: no spread
+        pool.route(update(2, 100.1, false)).unwrap();   // 10 bps: tight
+        pool.route(update(3, 101.0, false)).unwrap();   // Best ask still 100.1
+        pool.route(BookUpdate { qty: Qty(0.0), ..update(4, 100.1, false) }).unwrap(); // ~100 bps: wide
+
+        while pool.stats().updates_applied < 4 {
+            std::thread::yield_now();
+        }
+        let mut engine = ExecutionEngine::default();
+        let acks = pool.route_orders(&mut engine);
+
+        assert_eq!(pool.stats().orders_emitted, 1);
+        assert_eq!(acks.len(), 1);
+        let ack = acks[0].as_ref().ok().unwrap();
+        assert_eq!(engine.live_order(ack.exchange_hash).unwrap().price, 100 * FIXED_SCALE);
+        assert_eq!(engine.stats().0, 1);
+        pool.shutdown();
+    }
+
+    /// Buys once the microprice has risen over the last `window` updates
+    struct MicropriceMomentum {
+        window: usize,
+        history: HashMap<u64, PriceHistory>,
+    }
+
+    impl StrategyHook for MicropriceMomentum {
+        fn on_book_update(&mut self, book: &L2Orderbook, update: &BookUpdate) -> Option<OrderRequest> {
+            let history = self.history.entry(update.symbol_hash).or_insert_with(|| PriceHistory::new(self.window));
+            if !history.record(book, update.ts_ns) || history.len() < self.window {
+                return None;
+            }
+            let recent = history.recent(self.window);
+            if !recent.windows(2).all(|w| w[1].microprice > w[0].microprice) {
+                return None;
+            }
+            Some(OrderRequest {
+                symbol_hash: update.symbol_hash,
+                side: Side::Buy,
+                quantity: FIXED_SCALE,
+                order_type: OrderType::Market,
+                idempotency_key: update.seq_id,
+                ..Default::default()
+            })
+        }
+    }
+
+    #[test]
+    fn test_hook_keeps_price_history_per_symbol() {
+        let pool = ShardedProcessor::with_strategy(1, 64, |_| {
+            Box::new(MicropriceMomentum { window: 3, history: HashMap::new() })
+        });
+        let update = |symbol_hash, seq_id, price, qty, is_bid| BookUpdate {
+            symbol_hash,
+            price: Price(price),
+            qty: Qty(qty),
+            is_bid,
+            seq_id,
+            ts_ns: seq_id as i64,
+        };
+        for symbol_hash in [1, 2] {
+            pool.route(update(symbol_hash, 1, 100.0, 1.0, true)).unwrap();   // One-sided: not recorded
+            pool.route(update(symbol_hash, 2, 101.0, 1.0, false)).unwrap();
+        }
+        // Bid size grows on symbol 1 only, lifting its microprice
+        for (seq, qty) in [(3, 2.0), (4, 3.0)] {
+            pool.route(update(1, seq, 100.0, qty, true)).unwrap();
+            pool.route(update(2, seq, 100.0, 1.0, true)).unwrap();
+        }
+
+        // Counters move before the hook runs; wait for the order too
+        while pool.stats().updates_applied < 8 || pool.stats().orders_emitted < 1 {
+            std::thread::yield_now();
+        }
+        let orders: Vec<_> = pool.orders().try_iter().collect();
+        assert_eq!(orders.len(), 1);
+        assert_eq!((orders[0].symbol_hash, orders[0].idempotency_key), (1, 4));
+        pool.shutdown();
+    }
+
+    #[test]
+    fn test_processor_records_bounded_price_history_per_symbol() {
+        let config = ProcessorConfig { shards: 2, capacity: 64, history_capacity: 3, ..Default::default() };
+        let pool = ShardedProcessor::with_config(config, |_| None);
+        let update = |symbol_hash, seq_id, price, is_bid| BookUpdate {
+            symbol_hash,
+            price: Price(price),
+            qty: Qty(1.0),
+            is_bid,
+            seq_id,
+            ts_ns: seq_id as i64,
+        };
+        pool.route(update(10, 1, 100.0, true)).unwrap();    // One-sided: not recorded
+        pool.route(update(10, 2, 102.0, false)).unwrap();
+        for (seq, bid) in [(3, 100.5), (4, 101.0), (5, 101.5)] {
+            pool.route(update(10, seq, bid, true)).unwrap();
+        }
+        pool.route(update(11, 1, 50.0, true)).unwrap();
+        pool.route(update(11, 2, 51.0, false)).unwrap();
+
+        while pool.stats().updates_applied < 7 {
+            std::thread::yield_now();
+        }
+        let mids: Vec<f64> = pool.recent(10, 10).iter().map(|p| p.mid).collect();
+        assert_eq!(mids, vec![101.25, 101.5, 101.75]);     // Capacity 3: seq 2 evicted
+        assert_eq!(pool.recent(10, 1)[0].ts_ns, 5);
+        assert_eq!(pool.recent(11, 10).len(), 1);
+        assert!(pool.recent(12, 10).is_empty());
+        pool.shutdown();
+    }
+
+    #[test]
+    fn test_outcomes_counted_separately() {
+        let pool = ShardedProcessor::new(1, 16);
+        let update = |seq_id, price| BookUpdate {
+            symbol_hash: 3,
+            price: Price(price),
+            qty: Qty(1.0),
+            is_bid: true,
+            seq_id,
+            ts_ns: 0,
+        };
+        pool.route(update(1, 100.0)).unwrap();
+        pool.route(update(2, f64::NAN)).unwrap();   // Seq not consumed
+        pool.route(update(2, 100.5)).unwrap();
+        pool.route(update(5, 101.0)).unwrap();      // Gap: expected 3
+
+        let stats = loop {
+            let stats = pool.stats();
+            if stats.updates_applied + stats.invalid_prices + stats.gaps_detected == 4 {
+                break stats;
+            }
+            std::thread::yield_now();
+        };
+        assert_eq!(stats.updates_applied, 2);
+        assert_eq!(stats.invalid_prices, 1);
+        assert_eq!(stats.gaps_detected, 1);
+        assert_eq!(pool.shutdown()[&3].last_seq_id.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn test_tick_filter_drops_sub_threshold_updates() {
+        let config = ProcessorConfig {
+            shards: 2,
+            capacity: 16,
+            tick_filter: TickFilterConfig { min_price_move: 0.0, min_size_change: 0.5 },
+            ..Default::default()
+        };
+        let pool = ShardedProcessor::with_config(config, |_| None);
+        let update = |symbol_hash, seq_id, qty| BookUpdate {
+            symbol_hash,
+            price: Price(100.0),
+            qty: Qty(qty),
+            is_bid: true,
+            seq_id,
+            ts_ns: 0,
+        };
+        for symbol_hash in [10, 11] {
+            pool.route(update(symbol_hash, 1, 1.0)).unwrap();
+            pool.route(update(symbol_hash, 2, 1.2)).unwrap();   // Jitter: dropped
+            pool.route(update(symbol_hash, 3, 2.0)).unwrap();
+        }
+
+        let stats = loop {
+            let stats = pool.stats();
+            if stats.updates_applied + stats.filtered_ticks == 6 {
+                break stats;
+            }
+            std::thread::yield_now();
+        };
+        assert_eq!(stats.updates_applied, 4);
+        assert_eq!(stats.filtered_ticks, 2);
+        assert_eq!(stats.gaps_detected, 0);
+
+        // Filtered deltas never reached the book but kept its seq moving
+        let books = pool.shutdown();
+        assert_eq!(books[&10].total_qty(true), 2.0);
+        assert_eq!(books[&11].last_seq_id.load(Ordering::Relaxed), 3);
+    }
+
+    /// Serves a fixed snapshot; fails the first `failures` fetches
+    struct StaticSnapshots {
+        snapshot: OrderbookSnapshot,
+        failures: u32,
+    }
+
+    impl SnapshotSource for StaticSnapshots {
+        fn fetch(&mut self, _symbol: &str) -> Result<OrderbookSnapshot, &'static str> {
+            if self.failures > 0 {
+                self.failures -= 1;
+                return Err("SNAPSHOT_FETCH_FAILED");
+            }
+            Ok(self.snapshot.clone())
+        }
+    }
+
+    #[test]
+    fn test_gap_resync_replays_only_deltas_newer_than_snapshot() {
+        let btc = symbol_hash("BTCUSDT");
+        let pool = ShardedProcessor::new(1, 64);
+        let update = |seq_id: u64, price: f64| BookUpdate {
+            symbol_hash: btc,
+            price: Price(price),
+            qty: Qty(1.0),
+            is_bid: true,
+            seq_id,
+            ts_ns: 0,
+        };
+        pool.route(update(96, 100.0)).unwrap();
+        pool.route(update(97, 100.5)).unwrap();
+        // 98 arrives after 99: the gap opens the resync window
+        for seq in [99, 98, 101, 102] {
+            pool.route(update(seq, 90.0 + seq as f64 / 100.0)).unwrap();
+        }
+
+        while pool.resync_requests().is_empty() {
+            std::thread::yield_now();
+        }
+
+        let symbols = HashMap::from([(btc, "BTCUSDT".to_string())]);
+        let snapshot = OrderbookSnapshot {
+            symbol_hash: btc,
+            seq_id: 100,
+            bids: vec![(95.0, 2.0)],
+            asks: vec![(105.0, 2.0)],
+        };
+        let mut source = StaticSnapshots { snapshot, failures: 1 };
+        assert_eq!(pool.serve_resyncs(&mut source, &symbols), 0);  // Failed, re-queued
+        assert_eq!(pool.serve_resyncs(&mut source, &symbols), 1);
+
+        let stats = loop {
+            let stats = pool.stats();
+            if stats.resyncs_applied == 1 && stats.updates_applied == 4 {
+                break stats;
+            }
+            std::thread::yield_now();
+        };
+        assert_eq!(stats.resyncs_requested, 1);
+        assert_eq!(stats.gaps_detected, 1);
+        assert_eq!(stats.replayed_updates, 2);  // 101 and 102; 98, 99 predate the snapshot
+
+        let books = pool.shutdown();
+        let book = &books[&btc];
+        assert_eq!(book.last_seq_id.load(Ordering::Relaxed), 102);
+        assert_eq!(book.bids.len(), 3);
+        assert_eq!(book.best_bid(), Some(95.0));
+        assert!(!book.has_open_gap());
+    }
+}
@@ -0,0 +1,171 @@
+// Quality module — Post-Run Market Data Quality Report
+//
+// Features:
+// - Per-symbol gap, resync, stale-delete, crossed and dropped counts
+// - Run totals including feed-level malformed frame drops
+// - Logged at shutdown, optionally written as JSON for a QA gate
+
+pub mod quality {
+    use crate::orderbook::L2Orderbook;
+    use serde::Serialize;
+    use std::path::Path;
+    use std::sync::atomic::Ordering;
+
+    /// Data quality counters of one symbol's book
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+    pub struct SymbolQuality {
+        pub symbol_hash: u64,
+        pub gaps_detected: u64,
+        pub gaps_resynced: u64,
+        pub stale_deletes: u64,     // Levels expired as stale
+        pub crossed_events: u64,
+        pub invalid_prices: u64,
+        pub dropped_ticks: u64,     // Deltas never applied: gaps + invalid prices
+        pub open_gap: bool,         // Gapped and never resynced afterwards
+    }
+
+    impl SymbolQuality {
+        pub fn from_book(book: &L2Orderbook) -> Self {
+            let gaps_detected = book.gaps_detected.load(Ordering::Relaxed);
+            let invalid_prices = book.invalid_prices.load(Ordering::Relaxed);
+            Self {
+                symbol_hash: book.symbol_hash,
+                gaps_detected,
+                gaps_resynced: book.gaps_resynced.load(Ordering::Relaxed),
+                stale_deletes: book.expired_levels.load(Ordering::Relaxed),
+                crossed_events: book.crossed_updates.load(Ordering::Relaxed),
+                invalid_prices,
+                dropped_ticks: gaps_detected + invalid_prices,
+                open_gap: book.has_open_gap(),
+            }
+        }
+    }
+
+    /// Whole-run summary, per-symbol rows sorted by symbol_hash
+    #[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+    pub struct DataQualityReport {
+        pub total_ticks: u64,
+        pub gaps_detected: u64,
+        pub gaps_resynced: u64,
+        pub stale_deletes: u64,
+        pub malformed_drops: u64,   // Frames rejected before reaching a book
+        pub crossed_events: u64,
+        pub dropped_ticks: u64,
+        pub per_symbol: Vec<SymbolQuality>,
+    }
+
+    impl DataQualityReport {
+        /// Report over `books`; `total_ticks` and `malformed_drops` come from
+        /// the feed side, which sees frames no book ever does
+        pub fn from_books<'a>(
+            books: impl IntoIterator<Item = &'a L2Orderbook>,
+            total_ticks: u64,
+            malformed_drops: u64,
+        ) -> Self {
+            let mut report = Self { total_ticks, malformed_drops, ..Default::default() };
+            for book in books {
+                report.add_symbol(SymbolQuality::from_book(book));
+            }
+            report
+        }
+
+        /// Add one symbol's row to the totals
+        pub fn add_symbol(&mut self, row: SymbolQuality) {
+            self.gaps_detected += row.gaps_detected;
+            self.gaps_resynced += row.gaps_resynced;
+            self.stale_deletes += row.stale_deletes;
+            self.crossed_events += row.crossed_events;
+            self.dropped_ticks += row.dropped_ticks;
+            let at = self.per_symbol.partition_point(|r| r.symbol_hash < row.symbol_hash);
+            self.per_symbol.insert(at, row);
+        }
+
+        /// Symbols whose book ended the run inside a gap
+        pub fn open_gaps(&self) -> usize {
+            self.per_symbol.iter().filter(|r| r.open_gap).count()
+        }
+
+        /// Log the report, then write it as JSON when `path` is given
+        pub fn finish(&self, path: Option<&Path>) -> std::io::Result<()> {
+            tracing::info!(
+                total_ticks = self.total_ticks,
+                gaps_detected = self.gaps_detected,
+                gaps_resynced = self.gaps_resynced,
+                stale_deletes = self.stale_deletes,
+                malformed_drops = self.malformed_drops,
+                crossed_events = self.crossed_events,
+                dropped_ticks = self.dropped_ticks,
+                symbols = self.per_symbol.len(),
+                open_gaps = self.open_gaps(),
+                "data quality report"
+            );
+            for row in self.per_symbol.iter().filter(|r| r.dropped_ticks > 0 || r.crossed_events > 0) {
+                tracing::info!(
+                    symbol_hash = row.symbol_hash,
+                    dropped_ticks = row.dropped_ticks,
+                    crossed_events = row.crossed_events,
+                    "symbol data quality"
+                );
+            }
+            match path {
+                Some(path) => std::fs::write(path, serde_json::to_vec_pretty(self)?),
+                None => Ok(()),
+            }
+        }
+    }
+}
+
+pub use quality::*;
+
+#[cfg(test)]
+mod tests {
+    use super::quality::*;
+    use crate::orderbook::{L2Orderbook, OrderbookSnapshot, Price, Qty};
+    use std::sync::atomic::Ordering;
+
+    #[test]
+    fn test_report_aggregates_book_counters() {
+        // Gap, invalid price, then a resync closes the gap
+        let mut btc = L2Orderbook::new(2);
+        btc.apply_delta(Price(100.0), Qty(1.0), true, 1);
+        btc.apply_delta(Price(100.0), Qty(1.0), true, 5);
+        btc.apply_delta(Price(f64::NAN), Qty(1.0), true, 2);
+        btc.resync(&OrderbookSnapshot { symbol_hash: 2, seq_id: 10, ..Default::default() });
+        btc.apply_delta(Price(99.0), Qty(1.0), true, 12);     // Open gap at the end
+        btc.expired_levels.store(4, Ordering::Relaxed);
+
+        let eth = L2Orderbook::new(1);
+        eth.crossed_updates.store(3, Ordering::Relaxed);
+
+        let report = DataQualityReport::from_books([&btc, &eth], 1_000, 7);
+        assert_eq!(
+            report.per_symbol,
+            vec![
+                SymbolQuality { symbol_hash: 1, crossed_events: 3, ..Default::default() },
+                SymbolQuality {
+                    symbol_hash: 2,
+                    gaps_detected: 2,
+                    gaps_resynced: 1,
+                    stale_deletes: 4,
+                    crossed_events: 0,
+                    invalid_prices: 1,
+                    dropped_ticks: 3,
+                    open_gap: true,
+                },
+            ]
+        );
+        assert_eq!(
+            (report.total_ticks, report.gaps_detected, report.gaps_resynced, report.stale_deletes),
+            (1_000, 2, 1, 4)
+        );
+        assert_eq!((report.malformed_drops, report.crossed_events, report.dropped_ticks), (7, 3, 3));
+        assert_eq!(report.open_gaps(), 1);
+
+        let path = std::env::temp_dir().join(format!("dq-report-{}.json", std::process::id()));
+        report.finish(Some(&path)).unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(json["dropped_ticks"], 3);
+        assert_eq!(json["per_symbol"][1]["gaps_resynced"], 1);
+    }
+}
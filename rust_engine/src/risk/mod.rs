@@ -1,22 +1,168 @@
 // Risk module — Zero-Bottleneck Risk Calculations
 //
-// All functions are pure (no side effects) and O(1) complexity.
+// All functions are pure (no side effects) and O(1) complexity,
+// except portfolio aggregation (O(positions)), drawdown (O(points)), correlated
+// portfolio VaR (O(assets^2)) and historical simulation which sorts its
+// return series (O(n log n)).
 // Uses fixed-point arithmetic for determinism.
+// Inputs that would produce meaningless figures are rejected with RiskError.
 
 pub mod risk {
-    /// Parametric Value at Risk - O(1)
-    /// Uses pre-computed Z-scores for common confidence levels
+    use crate::execution::FIXED_SCALE;
+    use std::fmt;
+
+    /// Invalid risk model inputs
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub enum RiskError {
+        InvalidConfidence,      // Outside (0, 100) percent
+        NegativeHorizon,
+        NegativeVolatility,
+        DimensionMismatch,      // Weights, vols and correlation sizes disagree
+        InvalidCorrelation,     // Diagonal not 1 or entry outside [-1, 1]
+        InvalidLeverage,        // Zero leverage
+        LeverageAboveCap,
+    }
+
+    impl RiskError {
+        pub fn code(&self) -> &'static str {
+            match self {
+                RiskError::InvalidConfidence => "INVALID_CONFIDENCE",
+                RiskError::NegativeHorizon => "NEGATIVE_HORIZON",
+                RiskError::NegativeVolatility => "NEGATIVE_VOLATILITY",
+                RiskError::DimensionMismatch => "DIMENSION_MISMATCH",
+                RiskError::InvalidCorrelation => "INVALID_CORRELATION",
+                RiskError::InvalidLeverage => "INVALID_LEVERAGE",
+                RiskError::LeverageAboveCap => "LEVERAGE_ABOVE_CAP",
+            }
+        }
+    }
+
+    impl fmt::Display for RiskError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str(self.code())
+        }
+    }
+
+    impl std::error::Error for RiskError {}
+
+    /// Parametric Value at Risk over `holding_period_days` - O(1)
+    /// Uses pre-computed Z-scores for common confidence levels; other
+    /// levels in (0, 100) round up to the next tabulated one (never
+    /// understating VaR). Daily volatility scales by sqrt(days).
+    #[inline(always)]
+    pub fn parametric_var(
+        portfolio_value: i64,
+        volatility_bps: i64,    // Daily
+        confidence: u8,         // Percent
+        holding_period_days: i64,
+    ) -> Result<i64, RiskError> {
+        if volatility_bps < 0 {
+            return Err(RiskError::NegativeVolatility);
+        }
+        if holding_period_days < 0 {
+            return Err(RiskError::NegativeHorizon);
+        }
+        let z = z_score_milli(confidence)?;
+
+        // Result in basis points; i128 so large books don't overflow
+        let one_day = portfolio_value as i128 * volatility_bps as i128 * z / 100_000;
+        Ok((one_day as f64 * (holding_period_days as f64).sqrt()).round() as i64)
+    }
+
+    /// Z-score * 1000 for a percent confidence, rounded up to a tabulated level
     #[inline(always)]
-    pub fn parametric_var(portfolio_value: i64, volatility_bps: i64, confidence: u8) -> i64 {
+    fn z_score_milli(confidence: u8) -> Result<i128, RiskError> {
         // Pre-computed Z-scores: 90%=1.282, 95%=1.645, 99%=2.326
-        let z = match confidence {
-            99 => 2326,   // 2.326 * 1000
-            95 => 1645,   // 1.645 * 1000
-            _ => 1282,    // 1.282 * 1000 (90%)
+        match confidence {
+            1..=90 => Ok(1282),
+            91..=95 => Ok(1645),
+            96..=99 => Ok(2326),
+            _ => Err(RiskError::InvalidConfidence),
+        }
+    }
+
+    /// One-day parametric VaR of a correlated portfolio - O(assets^2)
+    /// Portfolio volatility is sqrt(w' * Sigma * w) with
+    /// `Sigma[i][j] = corr[i][j] * vol[i] * vol[j]`; the z-score is
+    /// `parametric_var`'s. `weights` are fractions of `portfolio_value`.
+    pub fn portfolio_var(
+        weights: &[f64],
+        vols_bps: &[i64],       // Daily, per asset
+        corr: &[Vec<f64>],
+        portfolio_value: i64,
+        confidence: u8,
+    ) -> Result<i64, RiskError> {
+        let n = weights.len();
+        if vols_bps.len() != n || corr.len() != n || corr.iter().any(|row| row.len() != n) {
+            return Err(RiskError::DimensionMismatch);
+        }
+        if vols_bps.iter().any(|&v| v < 0) {
+            return Err(RiskError::NegativeVolatility);
+        }
+        for (i, row) in corr.iter().enumerate() {
+            if row[i] != 1.0 || row.iter().any(|c| !(-1.0..=1.0).contains(c)) {
+                return Err(RiskError::InvalidCorrelation);
+            }
+        }
+        let z = z_score_milli(confidence)?;
+
+        let mut variance = 0.0;
+        for i in 0..n {
+            for j in 0..n {
+                variance += weights[i] * weights[j] * corr[i][j] * vols_bps[i] as f64 * vols_bps[j] as f64;
+            }
+        }
+        let vol_bps = variance.max(0.0).sqrt();
+        Ok((portfolio_value as f64 * vol_bps * z as f64 / 100_000.0).round() as i64)
+    }
+
+    /// Empirical `p`-quantile of `returns`, interpolating linearly between
+    /// neighbouring order statistics - O(n log n)
+    /// None for an empty series or `p` outside [0, 1]
+    pub fn empirical_quantile(returns: &[f64], p: f64) -> Option<f64> {
+        if returns.is_empty() || !(0.0..=1.0).contains(&p) {
+            return None;
+        }
+        let mut sorted = returns.to_vec();
+        sorted.sort_unstable_by(f64::total_cmp);
+
+        let rank = p * (sorted.len() - 1) as f64;
+        let lower = rank.floor() as usize;
+        let upper = rank.ceil() as usize;
+        Some(sorted[lower] + (sorted[upper] - sorted[lower]) * (rank - lower as f64))
+    }
+
+    /// Historical-simulation Value at Risk - O(n log n)
+    /// Loss at the empirical (100 - confidence)% quantile of `returns`
+    /// (fractions, e.g. -0.02), as a positive fixed-point amount; never
+    /// negative. Same confidence convention as `parametric_var`; an empty
+    /// series has no observed loss (0).
+    pub fn historical_var(returns: &[f64], portfolio_value: i64, confidence: u8) -> Result<i64, RiskError> {
+        if !(1..=99).contains(&confidence) {
+            return Err(RiskError::InvalidConfidence);
+        }
+        let Some(quantile) = empirical_quantile(returns, (100 - confidence) as f64 / 100.0) else {
+            return Ok(0);
         };
-        
-        // Result in basis points
-        (portfolio_value * volatility_bps * z) / 100_000
+        Ok((-quantile * portfolio_value as f64).round().max(0.0) as i64)
+    }
+
+    /// Expected shortfall (CVaR) as a return - O(n log n)
+    /// Mean of the returns at or below the historical VaR quantile, so it
+    /// is never better than that quantile; the tail always holds at least
+    /// the worst sample. An empty series gives 0.
+    pub fn expected_shortfall(returns: &[f64], confidence: u8) -> Result<f64, RiskError> {
+        if !(1..=99).contains(&confidence) {
+            return Err(RiskError::InvalidConfidence);
+        }
+        let Some(quantile) = empirical_quantile(returns, (100 - confidence) as f64 / 100.0) else {
+            return Ok(0.0);
+        };
+        let (sum, count) = returns
+            .iter()
+            .filter(|&&r| r <= quantile)
+            .fold((0.0, 0usize), |(sum, count), &r| (sum + r, count + 1));
+        Ok(sum / count as f64)
     }
 
     /// Maximum position size given risk parameters - O(1)
@@ -46,13 +192,116 @@ pub mod risk {
         notional / leverage as i64
     }
 
+    /// Margin requirement with a leverage cap - O(1)
+    /// Unlike margin_requirement, zero leverage and leverage above
+    /// `max_leverage` are rejected instead of quietly priced
+    #[inline(always)]
+    pub fn capped_margin_requirement(notional: i64, leverage: u8, max_leverage: u8) -> Result<i64, RiskError> {
+        if leverage == 0 {
+            return Err(RiskError::InvalidLeverage);
+        }
+        if leverage > max_leverage {
+            return Err(RiskError::LeverageAboveCap);
+        }
+        Ok(margin_requirement(notional, leverage))
+    }
+
     /// Portfolio exposure in basis points - O(1)
+    /// Saturates at the i64 bounds instead of overflowing
     #[inline(always)]
     pub fn exposure_bps(total_position_value: i64, equity: i64) -> i64 {
+        wide_exposure_bps(total_position_value as i128, equity)
+    }
+
+    // i128 throughout: value * 10_000 overflows i64 from ~$9.2M notional
+    #[inline(always)]
+    fn wide_exposure_bps(total_position_value: i128, equity: i64) -> i64 {
         if equity == 0 {
             return 0;
         }
-        (total_position_value * 10_000) / equity
+        let bps = total_position_value * 10_000 / equity as i128;
+        bps.clamp(i64::MIN as i128, i64::MAX as i128) as i64
+    }
+
+    /// Portfolio-wide exposure, all figures in basis points of equity
+    #[derive(Clone, Debug, Default, PartialEq, Eq)]
+    pub struct PortfolioExposure {
+        pub gross_bps: i64,                 // Sum of |notional|
+        pub net_bps: i64,                   // Longs minus shorts
+        pub per_symbol_bps: Vec<(u64, i64)>,   // Signed, sorted by symbol_hash
+        pub unmarked: usize,                // Positions skipped for lack of a mark
+    }
+
+    /// Aggregate signed positions (fixed-point qty) at mark prices - O(positions)
+    /// Feed it `ExecutionEngine::positions()`; flat positions are omitted
+    pub fn portfolio_exposure<'a>(
+        positions: impl IntoIterator<Item = (&'a u64, &'a i64)>,
+        mark_price: impl Fn(u64) -> Option<i64>,
+        equity: i64,
+    ) -> PortfolioExposure {
+        let mut exposure = PortfolioExposure::default();
+        let (mut gross, mut net) = (0i128, 0i128);
+
+        for (&symbol_hash, &qty) in positions {
+            if qty == 0 {
+                continue;
+            }
+            let Some(mark) = mark_price(symbol_hash) else {
+                exposure.unmarked += 1;
+                continue;
+            };
+            // i128 intermediate: qty * price overflows i64
+            let notional = qty as i128 * mark as i128 / FIXED_SCALE as i128;
+            gross += notional.abs();
+            net += notional;
+            exposure.per_symbol_bps.push((symbol_hash, wide_exposure_bps(notional, equity)));
+        }
+
+        exposure.per_symbol_bps.sort_unstable_by_key(|&(symbol_hash, _)| symbol_hash);
+        exposure.gross_bps = wide_exposure_bps(gross, equity);
+        exposure.net_bps = wide_exposure_bps(net, equity);
+        exposure
+    }
+
+    /// Largest peak-to-trough decline of an equity curve
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+    pub struct Drawdown {
+        pub depth_bps: i64,     // Decline as basis points of the peak
+        pub peak: usize,        // Index of the peak it fell from
+        pub trough: usize,      // Index of the low
+    }
+
+    /// Maximum drawdown of a fixed-point equity curve - O(n), single pass
+    /// A curve that never falls (or is empty) has depth 0 at index 0
+    pub fn max_drawdown(equity_curve: &[i64]) -> Drawdown {
+        let mut worst = Drawdown::default();
+        let mut peak = 0;
+        for (i, &equity) in equity_curve.iter().enumerate() {
+            if equity > equity_curve[peak] {
+                peak = i;
+                continue;
+            }
+            let high = equity_curve[peak];
+            if high <= 0 {
+                continue;
+            }
+            // i128: equity * 10_000 overflows i64 for large books
+            let depth_bps = ((high - equity) as i128 * 10_000 / high as i128) as i64;
+            if depth_bps > worst.depth_bps {
+                worst = Drawdown { depth_bps, peak, trough: i };
+            }
+        }
+        worst
+    }
+
+    /// Calmar ratio in hundredths (e.g., 150 = 1.5) - O(1)
+    /// None without a drawdown to divide by
+    #[inline(always)]
+    pub fn calmar_ratio(annual_return_bps: i64, max_drawdown_bps: i64) -> Option<i64> {
+        if max_drawdown_bps <= 0 {
+            return None;
+        }
+        Some(annual_return_bps * 100 / max_drawdown_bps)
     }
 
     /// Kelly Criterion optimal position fraction - O(1)
@@ -61,14 +310,18 @@ pub mod risk {
     pub fn kelly_fraction(win_rate_bps: i64, win_loss_ratio: i64) -> i64 {
         // Kelly = W - (1-W)/R where W = win rate, R = win/loss ratio
         // win_rate_bps is in basis points (e.g., 5500 = 55%)
+        // win_loss_ratio is in hundredths (e.g., 150 = 1.5)
+        if win_loss_ratio <= 0 {
+            return 0;
+        }
         let win_rate = win_rate_bps;
         let loss_rate = 10_000 - win_rate;
         
         // Result in basis points
-        let kelly = win_rate - (loss_rate * 10_000 / win_loss_ratio);
+        let kelly = win_rate - (loss_rate * 100 / win_loss_ratio);
         
         // Clamp to reasonable range (0% to 100%)
-        kelly.max(0).min(10_000)
+        kelly.clamp(0, 10_000)
     }
 
     /// Check if order passes risk limits - O(1)
@@ -105,11 +358,115 @@ pub mod risk {
 #[cfg(test)]
 mod tests {
     use super::risk::*;
+    use crate::execution::FIXED_SCALE;
 
     #[test]
     fn test_var() {
-        let var = parametric_var(100_000_00_000_000, 200, 95); // $100k, 2% vol, 95% conf
+        let var = parametric_var(10_000_000_000_000, 200, 95, 1).unwrap(); // $100k, 2% vol, 95% conf
         assert!(var > 0);
+        assert_eq!(parametric_var(10_000_000_000_000, 200, 95, 4), Ok(2 * var));
+        assert_eq!(parametric_var(10_000_000_000_000, 200, 93, 1), Ok(var));
+        assert_eq!(parametric_var(10_000_000_000_000, 200, 95, 0), Ok(0));
+    }
+
+    #[test]
+    fn test_var_rejects_invalid_inputs() {
+        assert_eq!(parametric_var(1_000, 200, 95, -1), Err(RiskError::NegativeHorizon));
+        assert_eq!(parametric_var(1_000, 200, 100, 1), Err(RiskError::InvalidConfidence));
+        assert_eq!(parametric_var(1_000, 200, 0, 1), Err(RiskError::InvalidConfidence));
+        assert_eq!(parametric_var(1_000, -5, 95, 1), Err(RiskError::NegativeVolatility));
+    }
+
+    #[test]
+    fn test_historical_var_interpolates_empirical_quantile() {
+        // -4.9% .. +5.0% in 0.1% steps, shuffled order
+        let mut returns: Vec<f64> = (1..=100).map(|i| (i as f64 - 50.0) / 1_000.0).collect();
+        returns.reverse();
+        let value = 1_000_000 * FIXED_SCALE;
+
+        // 95%: rank 4.95 between -4.5% and -4.4%
+        assert_eq!(historical_var(&returns, value, 95), Ok(44_050 * FIXED_SCALE));
+        // 99%: rank 0.99 between -4.9% and -4.8%
+        assert_eq!(historical_var(&returns, value, 99), Ok(48_010 * FIXED_SCALE));
+
+        assert_eq!(empirical_quantile(&[-0.10, 0.10], 0.25), Some(-0.05));
+        assert_eq!(historical_var(&[], value, 95), Ok(0));
+        assert_eq!(historical_var(&[0.01, 0.02], value, 95), Ok(0));    // No losses observed
+        assert_eq!(historical_var(&returns, value, 100), Err(RiskError::InvalidConfidence));
+    }
+
+    #[test]
+    fn test_expected_shortfall_exceeds_var_on_fat_tail() {
+        // 95 quiet days within +/-1%, 5 crashes
+        let mut returns: Vec<f64> = (0..95).map(|i| ((i % 21) as f64 - 10.0) / 1_000.0).collect();
+        returns.extend([-0.05, -0.08, -0.10, -0.15, -0.30]);
+
+        let quantile = empirical_quantile(&returns, 0.05).unwrap();
+        let es = expected_shortfall(&returns, 95).unwrap();
+        assert!((es - -0.136).abs() < 1e-12, "{}", es);
+        assert!(es < quantile, "{} vs {}", es, quantile);
+        let value = 1_000_000 * FIXED_SCALE;
+        assert!((-es * value as f64) as i64 > historical_var(&returns, value, 95).unwrap());
+
+        // Only the worst sample sits at or below the quantile
+        assert_eq!(expected_shortfall(&[0.03, -0.02, 0.01], 95), Ok(-0.02));
+        assert_eq!(expected_shortfall(&[], 95), Ok(0.0));
+        assert_eq!(expected_shortfall(&returns, 0), Err(RiskError::InvalidConfidence));
+    }
+
+    #[test]
+    fn test_portfolio_var_with_correlation() {
+        let value = 1_000_000 * FIXED_SCALE;
+        let weights = [0.6, 0.4];
+        let vols = [300, 500];
+        let standalone: i64 = [(0.6, 300), (0.4, 500)]
+            .iter()
+            .map(|&(w, vol)| parametric_var((value as f64 * w) as i64, vol, 95, 1).unwrap())
+            .sum();
+
+        // Perfect correlation: no diversification
+        let perfect = vec![vec![1.0, 1.0], vec![1.0, 1.0]];
+        let var = portfolio_var(&weights, &vols, &perfect, value, 95).unwrap();
+        assert!((var - standalone).abs() <= 1, "{} vs {}", var, standalone);
+
+        // Uncorrelated: sqrt(180^2 + 200^2) = 269bps vs 380bps combined
+        let independent = vec![vec![1.0, 0.0], vec![0.0, 1.0]];
+        let diversified = portfolio_var(&weights, &vols, &independent, value, 95).unwrap();
+        assert!(diversified < var);
+        assert_eq!(diversified, (value as f64 * 72_400f64.sqrt() * 1_645.0 / 100_000.0).round() as i64);
+
+        let bad_diagonal = vec![vec![0.9, 0.5], vec![0.5, 1.0]];
+        assert_eq!(portfolio_var(&weights, &vols, &bad_diagonal, value, 95), Err(RiskError::InvalidCorrelation));
+        assert_eq!(portfolio_var(&weights, &vols, &[vec![1.0]], value, 95), Err(RiskError::DimensionMismatch));
+        assert_eq!(portfolio_var(&weights, &[300], &perfect, value, 95), Err(RiskError::DimensionMismatch));
+        let ragged = vec![vec![1.0, 0.5], vec![1.0]];
+        assert_eq!(portfolio_var(&weights, &vols, &ragged, value, 95), Err(RiskError::DimensionMismatch));
+    }
+
+    #[test]
+    fn test_max_drawdown_finds_known_twenty_percent_decline() {
+        let curve: Vec<i64> = [100, 110, 120, 108, 96, 115, 130, 117, 125]
+            .iter()
+            .map(|&e| e * FIXED_SCALE)
+            .collect();
+        // 120 -> 96 beats the later 130 -> 117 (10%)
+        assert_eq!(max_drawdown(&curve), Drawdown { depth_bps: 2_000, peak: 2, trough: 4 });
+        assert_eq!(calmar_ratio(3_000, 2_000), Some(150));
+        assert_eq!(calmar_ratio(3_000, 0), None);
+
+        let rising: Vec<i64> = (1..=10).map(|e| e * FIXED_SCALE).collect();
+        assert_eq!(max_drawdown(&rising), Drawdown::default());
+        assert_eq!(max_drawdown(&[]), Drawdown::default());
+    }
+
+    #[test]
+    fn test_capped_margin_rejects_zero_and_excess_leverage() {
+        let notional = 50_000 * FIXED_SCALE;
+        assert_eq!(capped_margin_requirement(notional, 10, 20), Ok(5_000 * FIXED_SCALE));
+        assert_eq!(capped_margin_requirement(notional, 20, 20), Ok(2_500 * FIXED_SCALE));
+        assert_eq!(capped_margin_requirement(notional, 0, 20), Err(RiskError::InvalidLeverage));
+        assert_eq!(capped_margin_requirement(notional, 125, 20), Err(RiskError::LeverageAboveCap));
+        assert_eq!(RiskError::LeverageAboveCap.to_string(), "LEVERAGE_ABOVE_CAP");
     }
 
     #[test]
@@ -117,4 +474,60 @@ mod tests {
         let kelly = kelly_fraction(5500, 150); // 55% win rate, 1.5 win/loss ratio
         assert!(kelly > 0 && kelly < 10_000);
     }
+
+    #[test]
+    fn test_portfolio_gross_vs_net_exposure() {
+        use crate::execution::{ExecutionEngine, OrderRequest, OrderType, Side};
+
+        let mut engine = ExecutionEngine::default();
+        let mut fill = |key: u64, symbol_hash: u64, side: Side, quantity: i64| {
+            let req = OrderRequest {
+                symbol_hash,
+                side,
+                quantity,
+                price: 100 * FIXED_SCALE,
+                order_type: OrderType::Market,
+                idempotency_key: key,
+                ..Default::default()
+            };
+            let ack = engine.submit(&req).unwrap();
+            engine.process_fill(&ack, &req).unwrap();
+        };
+        fill(1, 1, Side::Buy, 2 * FIXED_SCALE);     // Long 2 BTC
+        fill(2, 2, Side::Sell, 10 * FIXED_SCALE);   // Short 10 ETH
+        fill(3, 3, Side::Buy, FIXED_SCALE);         // No mark
+        fill(4, 3, Side::Sell, FIXED_SCALE);        // ...and flat anyway
+        fill(5, 4, Side::Buy, FIXED_SCALE);         // No mark
+
+        let marks = |symbol_hash| match symbol_hash {
+            1 => Some(30_000 * FIXED_SCALE),
+            2 => Some(2_000 * FIXED_SCALE),
+            _ => None,
+        };
+        let equity = 100_000 * FIXED_SCALE;
+        let exposure = portfolio_exposure(engine.positions(), marks, equity);
+
+        // 60k long + 20k short on 100k equity
+        assert_eq!(exposure.gross_bps, 8_000);
+        assert_eq!(exposure.net_bps, 4_000);
+        assert_eq!(exposure.per_symbol_bps, vec![(1, 6_000), (2, -2_000)]);
+        assert_eq!(exposure.unmarked, 1);
+    }
+
+    #[test]
+    fn test_large_notional_exposure_does_not_overflow() {
+        // 200 BTC @ 50,000 = $10M long, 100,000 ETH @ 2,000 = $200M short
+        let positions = std::collections::HashMap::from([(1u64, 200 * FIXED_SCALE), (2, -100_000 * FIXED_SCALE)]);
+        let marks = |symbol_hash| match symbol_hash {
+            1 => Some(50_000 * FIXED_SCALE),
+            _ => Some(2_000 * FIXED_SCALE),
+        };
+        let exposure = portfolio_exposure(&positions, marks, 1_000_000 * FIXED_SCALE);
+
+        assert_eq!(exposure.gross_bps, 2_100_000);
+        assert_eq!(exposure.net_bps, -1_900_000);
+        assert_eq!(exposure.per_symbol_bps, vec![(1, 100_000), (2, -2_000_000)]);
+        assert_eq!(exposure_bps(10_000_000 * FIXED_SCALE, 1_000_000 * FIXED_SCALE), 100_000);
+        assert_eq!(exposure_bps(i64::MAX, 1), i64::MAX);
+    }
 }
@@ -0,0 +1,272 @@
+// Shutdown module — Coordinated Pipeline Shutdown
+//
+// Features:
+// - Shared shutdown flag handed to every pipeline task
+// - Per-task completion flags (set on return or panic)
+// - Configurable drain timeout; stragglers are reported, then abandoned
+// - Unexpected channel disconnects counted, critical ones trigger shutdown
+
+pub mod shutdown {
+    use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+    use std::sync::Arc;
+    use std::thread::JoinHandle;
+    use std::time::{Duration, Instant};
+
+    /// Shutdown behaviour
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub struct ShutdownConfig {
+        pub drain_timeout: Duration,
+    }
+
+    impl Default for ShutdownConfig {
+        fn default() -> Self {
+            Self {
+                drain_timeout: Duration::from_secs(5),
+            }
+        }
+    }
+
+    /// Outcome of a shutdown
+    #[derive(Clone, Debug, Default, PartialEq)]
+    pub struct ShutdownReport {
+        pub completed: Vec<&'static str>,
+        pub abandoned: Vec<&'static str>,   // Still running at the drain timeout
+        pub elapsed: Duration,
+    }
+
+    /// Marks the task done when dropped - covers normal return and panic
+    struct CompletionGuard(Arc<AtomicBool>);
+
+    impl Drop for CompletionGuard {
+        fn drop(&mut self) {
+            self.0.store(true, Ordering::Release);
+        }
+    }
+
+    struct TrackedTask {
+        name: &'static str,
+        done: Arc<AtomicBool>,
+        handle: Option<JoinHandle<()>>,
+    }
+
+    /// Tells expected channel closure (during shutdown) from wiring bugs
+    #[derive(Debug)]
+    pub struct ChannelMonitor {
+        shutdown: Arc<AtomicBool>,
+        pub channel_disconnects: AtomicU64,     // Unexpected only
+    }
+
+    impl ChannelMonitor {
+        pub fn new(shutdown: Arc<AtomicBool>) -> Self {
+            Self {
+                shutdown,
+                channel_disconnects: AtomicU64::new(0),
+            }
+        }
+
+        /// Report a `Disconnected` on `channel`; the caller stops its loop
+        /// either way. Outside shutdown it is counted and logged, and a
+        /// `critical` channel raises the shared shutdown flag so the rest
+        /// of the pipeline doesn't run on half-wired.
+        /// Returns true if the disconnect was unexpected.
+        pub fn on_disconnect(&self, channel: &'static str, critical: bool) -> bool {
+            if self.shutdown.load(Ordering::Acquire) {
+                return false;
+            }
+            self.channel_disconnects.fetch_add(1, Ordering::Relaxed);
+            if critical {
+                tracing::error!(channel, "critical channel disconnected unexpectedly, shutting down");
+                self.shutdown.store(true, Ordering::Release);
+            } else {
+                tracing::warn!(channel, "channel disconnected unexpectedly");
+            }
+            true
+        }
+
+        pub fn disconnects(&self) -> u64 {
+            self.channel_disconnects.load(Ordering::Relaxed)
+        }
+    }
+
+    /// Owns the pipeline's tasks and their shutdown
+    pub struct TaskSupervisor {
+        config: ShutdownConfig,
+        shutdown: Arc<AtomicBool>,
+        tasks: Vec<TrackedTask>,
+        channels: Arc<ChannelMonitor>,
+    }
+
+    impl TaskSupervisor {
+        pub fn new(config: ShutdownConfig) -> Self {
+            let shutdown = Arc::new(AtomicBool::new(false));
+            Self {
+                config,
+                channels: Arc::new(ChannelMonitor::new(shutdown.clone())),
+                shutdown,
+                tasks: Vec::new(),
+            }
+        }
+
+        /// Shared flag tasks poll to learn shutdown was requested
+        pub fn shutdown_flag(&self) -> Arc<AtomicBool> {
+            self.shutdown.clone()
+        }
+
+        /// Disconnect reporting wired to this supervisor's shutdown flag
+        pub fn channel_monitor(&self) -> Arc<ChannelMonitor> {
+            self.channels.clone()
+        }
+
+        /// Spawn a named task; it receives the shutdown flag
+        pub fn spawn<F>(&mut self, name: &'static str, task: F)
+        where
+            F: FnOnce(Arc<AtomicBool>) + Send + 'static,
+        {
+            let done = Arc::new(AtomicBool::new(false));
+            let guard = CompletionGuard(done.clone());
+            let shutdown = self.shutdown.clone();
+
+            let handle = std::thread::Builder::new()
+                .name(name.to_string())
+                .spawn(move || {
+                    let _guard = guard;
+                    task(shutdown);
+                })
+                .expect("failed to spawn pipeline task");
+
+            self.tasks.push(TrackedTask { name, done, handle: Some(handle) });
+        }
+
+        /// Names of tasks that have not finished
+        pub fn running(&self) -> Vec<&'static str> {
+            self.tasks
+                .iter()
+                .filter(|t| !t.done.load(Ordering::Acquire))
+                .map(|t| t.name)
+                .collect()
+        }
+
+        /// Request shutdown and wait up to `drain_timeout` for every task
+        ///
+        /// Tasks still running at the deadline are logged by name and
+        /// abandoned (detached) so shutdown never hangs.
+        pub fn shutdown(&mut self) -> ShutdownReport {
+            let start = Instant::now();
+            self.shutdown.store(true, Ordering::Release);
+
+            while !self.running().is_empty() && start.elapsed() < self.config.drain_timeout {
+                std::thread::sleep(Duration::from_millis(1));
+            }
+
+            let mut report = ShutdownReport::default();
+            for task in self.tasks.iter_mut() {
+                let handle = task.handle.take();
+                if task.done.load(Ordering::Acquire) {
+                    if let Some(handle) = handle {
+                        let _ = handle.join();
+                    }
+                    report.completed.push(task.name);
+                } else {
+                    tracing::warn!(
+                        task = task.name,
+                        timeout_ms = self.config.drain_timeout.as_millis() as u64,
+                        "task still running after drain timeout, aborting"
+                    );
+                    report.abandoned.push(task.name);
+                }
+            }
+            self.tasks.clear();
+
+            report.elapsed = start.elapsed();
+            report
+        }
+    }
+
+    impl Default for TaskSupervisor {
+        fn default() -> Self {
+            Self::new(ShutdownConfig::default())
+        }
+    }
+}
+
+pub use shutdown::*;
+
+#[cfg(test)]
+mod tests {
+    use super::shutdown::*;
+    use crossbeam_channel::{bounded, RecvTimeoutError};
+    use std::sync::atomic::Ordering;
+    use std::time::Duration;
+
+    #[test]
+    fn test_drain_timeout_reports_still_running_task() {
+        let mut supervisor = TaskSupervisor::new(ShutdownConfig {
+            drain_timeout: Duration::from_millis(50),
+        });
+
+        supervisor.spawn("processor", |shutdown| {
+            while !shutdown.load(Ordering::Acquire) {
+                std::thread::sleep(Duration::from_millis(1));
+            }
+        });
+        supervisor.spawn("hung_publisher", |_| {
+            std::thread::sleep(Duration::from_millis(500));
+        });
+
+        let report = supervisor.shutdown();
+
+        assert_eq!(report.completed, vec!["processor"]);
+        assert_eq!(report.abandoned, vec!["hung_publisher"]);
+        assert!(report.elapsed >= Duration::from_millis(50));
+        assert!(report.elapsed < Duration::from_millis(400));
+    }
+
+    #[test]
+    fn test_panicked_task_counts_as_completed() {
+        let mut supervisor = TaskSupervisor::default();
+        supervisor.spawn("faulty", |_| panic!("boom"));
+
+        let report = supervisor.shutdown();
+        assert_eq!(report.completed, vec!["faulty"]);
+        assert!(report.abandoned.is_empty());
+    }
+
+    #[test]
+    fn test_dropped_tick_sender_triggers_coordinated_shutdown() {
+        let mut supervisor = TaskSupervisor::default();
+        let monitor = supervisor.channel_monitor();
+        let shutdown = supervisor.shutdown_flag();
+        let (tick_tx, tick_rx) = bounded::<u64>(16);
+
+        supervisor.spawn("processor", move |shutdown| {
+            while !shutdown.load(Ordering::Acquire) {
+                match tick_rx.recv_timeout(Duration::from_millis(1)) {
+                    Ok(_) | Err(RecvTimeoutError::Timeout) => {}
+                    Err(RecvTimeoutError::Disconnected) => {
+                        monitor.on_disconnect("ticks", true);
+                        break;
+                    }
+                }
+            }
+        });
+        supervisor.spawn("publisher", |shutdown| {
+            while !shutdown.load(Ordering::Acquire) {
+                std::thread::sleep(Duration::from_millis(1));
+            }
+        });
+
+        tick_tx.send(1).unwrap();
+        drop(tick_tx); // Wiring bug: the feed went away without shutdown
+        while !shutdown.load(Ordering::Acquire) {
+            std::thread::sleep(Duration::from_millis(1));
+        }
+
+        let report = supervisor.shutdown();
+        assert_eq!(report.completed, vec!["processor", "publisher"]);
+        assert_eq!(supervisor.channel_monitor().disconnects(), 1);
+
+        // Once shutting down, closing channels is expected
+        assert!(!supervisor.channel_monitor().on_disconnect("ticks", true));
+        assert_eq!(supervisor.channel_monitor().disconnects(), 1);
+    }
+}
@@ -0,0 +1,610 @@
+// Simulation module — Fill Simulation Against the L2 Orderbook
+//
+// Features:
+// - Market fills walk the book level by level (fixed-point VWAP)
+// - Optional liquidity consumption: filled quantity leaves the book
+// - Interval-based regeneration of consumed liquidity for backtests
+// - Iceberg orders: only the display slice rests in the book
+// - Probabilistic fills for resting limit orders (PipelineRng sub-stream)
+
+pub mod simulation {
+    use crate::execution::{OrderRequest, FIXED_SCALE};
+    use crate::orderbook::L2Orderbook;
+    use crate::rng::{PipelineRng, StreamRng};
+    use std::collections::HashMap;
+
+    /// Resting liquidity behaviour
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub struct LiquidityConfig {
+        pub consume_liquidity: bool,    // Decrement filled qty from book levels
+        pub regen_interval_ns: i64,     // Consumed qty is restored after this long
+    }
+
+    impl Default for LiquidityConfig {
+        fn default() -> Self {
+            Self {
+                consume_liquidity: false,
+                regen_interval_ns: 1_000_000_000, // 1s
+            }
+        }
+    }
+
+    /// Maps how far the mid traded through a resting price (in bps,
+    /// positive = through) to a fill probability per mid update
+    pub type FillProbabilityFn = fn(through_bps: f64) -> f64;
+
+    /// Default: no fill until traded through, then linear to certain at 10bps
+    pub fn linear_fill_probability(through_bps: f64) -> f64 {
+        (through_bps / 10.0).clamp(0.0, 1.0)
+    }
+
+    /// Resting limit order fill model
+    #[derive(Clone, Copy, Debug)]
+    pub struct LimitFillModel {
+        pub probability: FillProbabilityFn,
+        pub partial_fills: bool,    // Fill `remaining * p` instead of all
+    }
+
+    impl Default for LimitFillModel {
+        fn default() -> Self {
+            Self {
+                probability: linear_fill_probability,
+                partial_fills: false,
+            }
+        }
+    }
+
+    /// Limit order resting in the simulator
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub struct RestingLimit {
+        pub client_hash: u64,
+        pub is_bid: bool,
+        pub price: i64,         // Fixed-point
+        pub remaining: i64,     // Fixed-point
+    }
+
+    /// Fill of a resting limit order
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub struct LimitFill {
+        pub client_hash: u64,
+        pub filled_qty: i64,
+        pub price: i64,
+        pub remaining: i64,
+        pub ts_ns: i64,
+    }
+
+    /// Result of a simulated market order
+    #[derive(Clone, Copy, Debug, Default, PartialEq)]
+    pub struct SimulatedFill {
+        pub filled_qty: i64,        // Fixed-point
+        pub avg_price: i64,         // Fixed-point, 0 when nothing filled
+        pub levels_touched: u32,
+    }
+
+    /// Liquidity taken from one level, pending regeneration
+    #[derive(Clone, Copy, Debug)]
+    struct ConsumedLevel {
+        qty: i64,
+        consumed_at_ns: i64,
+    }
+
+    /// Resting iceberg order - only `visible` is shown in the book
+    /// Quantities are in the book's units
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub struct IcebergOrder {
+        pub client_hash: u64,
+        pub total_qty: i64,
+        pub display_qty: i64,
+        pub visible_qty: i64,   // Currently displayed slice
+        pub hidden_qty: i64,    // Remainder not yet displayed
+        pub filled_qty: i64,
+    }
+
+    impl IcebergOrder {
+        #[inline(always)]
+        pub fn is_exhausted(&self) -> bool {
+            self.visible_qty == 0 && self.hidden_qty == 0
+        }
+    }
+
+    /// Fill simulator over a live L2 book
+    pub struct FillSimulator {
+        config: LiquidityConfig,
+        consumed: HashMap<(bool, i64), ConsumedLevel>,  // (is_bid, price_key) -> taken
+        icebergs: HashMap<(bool, i64), IcebergOrder>,   // (is_bid, price_key) -> order
+        resting: Vec<RestingLimit>,
+        limit_model: LimitFillModel,
+        rng: StreamRng,
+        total_fills: u64,
+        total_regenerated: u64,
+    }
+
+    impl FillSimulator {
+        pub fn new(config: LiquidityConfig) -> Self {
+            Self::with_limit_model(config, LimitFillModel::default(), &PipelineRng::default())
+        }
+
+        /// Simulator drawing from the run's `fill_simulator` sub-stream
+        pub fn with_limit_model(config: LiquidityConfig, limit_model: LimitFillModel, rng: &PipelineRng) -> Self {
+            Self {
+                config,
+                consumed: HashMap::new(),
+                icebergs: HashMap::new(),
+                resting: Vec::new(),
+                limit_model,
+                rng: rng.stream("fill_simulator"),
+                total_fills: 0,
+                total_regenerated: 0,
+            }
+        }
+
+        /// Fill a market order against the opposite side - O(levels touched)
+        pub fn simulate_market_fill(
+            &mut self,
+            book: &mut L2Orderbook,
+            is_buy: bool,
+            qty: i64,
+            now_ns: i64,
+        ) -> SimulatedFill {
+            self.regenerate(book, now_ns);
+
+            let is_bid = !is_buy;
+            let levels: Vec<(i64, i64)> = if is_buy {
+                book.asks.iter().map(|(&k, &q)| (k, q)).collect()
+            } else {
+                book.bids.iter().rev().map(|(&k, &q)| (k, q)).collect()
+            };
+
+            let mut remaining = qty;
+            let mut notional: i128 = 0;
+            let mut fill = SimulatedFill::default();
+
+            for (key, level_qty) in levels {
+                if remaining <= 0 {
+                    break;
+                }
+                let take = level_qty.min(remaining);
+                remaining -= take;
+                notional += take as i128 * key as i128;
+                fill.filled_qty += take;
+                fill.levels_touched += 1;
+
+                let iceberg_take = self.fill_iceberg(book, is_bid, key, take);
+                if self.config.consume_liquidity && take > iceberg_take {
+                    self.consume(book, is_bid, key, take - iceberg_take, now_ns);
+                }
+            }
+
+            if fill.filled_qty > 0 {
+                fill.avg_price = (notional / fill.filled_qty as i128) as i64;
+                self.total_fills += 1;
+            }
+            fill
+        }
+
+        /// Rest an order in the book; with `display_qty` below `quantity`
+        /// it becomes an iceberg showing one slice at a time
+        /// The request's fixed-point price and quantities are converted to
+        /// `book`'s keys and units; the iceberg's quantities are in units.
+        /// Returns false if the price is unrepresentable, the quantity rounds
+        /// to no units, or an iceberg already rests at that level
+        pub fn place_iceberg(&mut self, book: &mut L2Orderbook, req: &OrderRequest) -> bool {
+            let is_bid = req.side.is_buy();
+            let to_units = |qty: i64| book.qty_to_units(qty as f64 / FIXED_SCALE as f64);
+            let total = to_units(req.quantity);
+            let Some(key) = book.price_to_key(req.price as f64 / FIXED_SCALE as f64) else {
+                return false;
+            };
+            if total <= 0 || self.icebergs.contains_key(&(is_bid, key)) {
+                return false;
+            }
+
+            // A slice below one lot still shows a single unit
+            let display = if req.display_qty > 0 {
+                to_units(req.display_qty).clamp(1, total)
+            } else {
+                total
+            };
+
+            self.icebergs.insert((is_bid, key), IcebergOrder {
+                client_hash: req.client_hash,
+                total_qty: total,
+                display_qty: display,
+                visible_qty: display,
+                hidden_qty: total - display,
+                filled_qty: 0,
+            });
+
+            let side = if is_bid { &mut book.bids } else { &mut book.asks };
+            *side.entry(key).or_insert(0) += display;
+            true
+        }
+
+        /// Iceberg resting at a level, if any
+        pub fn iceberg(&self, is_bid: bool, price_key: i64) -> Option<&IcebergOrder> {
+            self.icebergs.get(&(is_bid, price_key))
+        }
+
+        /// Fill the iceberg's visible slice first; refresh the display from
+        /// the hidden remainder once it is used up
+        /// Returns the quantity taken from the iceberg
+        fn fill_iceberg(&mut self, book: &mut L2Orderbook, is_bid: bool, key: i64, take: i64) -> i64 {
+            let Some(iceberg) = self.icebergs.get_mut(&(is_bid, key)) else {
+                return 0;
+            };
+
+            let filled = take.min(iceberg.visible_qty);
+            iceberg.visible_qty -= filled;
+            iceberg.filled_qty += filled;
+
+            let mut refresh = 0;
+            if iceberg.visible_qty == 0 {
+                refresh = iceberg.display_qty.min(iceberg.hidden_qty);
+                iceberg.hidden_qty -= refresh;
+                iceberg.visible_qty = refresh;
+            }
+            let exhausted = iceberg.is_exhausted();
+
+            let side = if is_bid { &mut book.bids } else { &mut book.asks };
+            if let Some(level) = side.get_mut(&key) {
+                *level += refresh - filled;
+                if *level <= 0 {
+                    side.remove(&key);
+                }
+            }
+
+            if exhausted {
+                self.icebergs.remove(&(is_bid, key));
+            }
+            filled
+        }
+
+        fn consume(&mut self, book: &mut L2Orderbook, is_bid: bool, key: i64, take: i64, now_ns: i64) {
+            let side = if is_bid { &mut book.bids } else { &mut book.asks };
+            if let Some(level) = side.get_mut(&key) {
+                *level -= take;
+                if *level <= 0 {
+                    side.remove(&key);
+                }
+            }
+
+            let entry = self.consumed.entry((is_bid, key)).or_insert(ConsumedLevel {
+                qty: 0,
+                consumed_at_ns: now_ns,
+            });
+            entry.qty += take;
+            entry.consumed_at_ns = now_ns;
+        }
+
+        /// Restore liquidity consumed at least `regen_interval_ns` ago
+        /// Returns the number of levels refilled
+        pub fn regenerate(&mut self, book: &mut L2Orderbook, now_ns: i64) -> usize {
+            let interval = self.config.regen_interval_ns;
+            let mut refilled = 0;
+
+            self.consumed.retain(|&(is_bid, key), level| {
+                if now_ns - level.consumed_at_ns < interval {
+                    return true;
+                }
+                let side = if is_bid { &mut book.bids } else { &mut book.asks };
+                *side.entry(key).or_insert(0) += level.qty;
+                refilled += 1;
+                false
+            });
+
+            self.total_regenerated += refilled as u64;
+            refilled
+        }
+
+        /// Rest a limit order; it fills only via `on_mid`
+        pub fn rest_limit(&mut self, req: &OrderRequest) {
+            if req.quantity > 0 {
+                self.resting.push(RestingLimit {
+                    client_hash: req.client_hash,
+                    is_bid: req.side.is_buy(),
+                    price: req.price,
+                    remaining: req.quantity,
+                });
+            }
+        }
+
+        /// Resting limit orders still open
+        pub fn resting(&self) -> &[RestingLimit] {
+            &self.resting
+        }
+
+        /// Draw fills for resting limits against a new mid
+        /// A bid is traded through when the mid falls below its price.
+        /// Resting prices are keys of `book`; an unrepresentable mid fills nothing.
+        pub fn on_mid(&mut self, book: &L2Orderbook, mid: f64, ts_ns: i64) -> Vec<LimitFill> {
+            let Some(mid_key) = book.price_to_key(mid) else {
+                return Vec::new();
+            };
+            let model = self.limit_model;
+            let mut fills = Vec::new();
+
+            for order in self.resting.iter_mut() {
+                let through = if order.is_bid { order.price - mid_key } else { mid_key - order.price };
+                let through_bps = through as f64 / order.price as f64 * 10_000.0;
+                let p = (model.probability)(through_bps).clamp(0.0, 1.0);
+                if p <= 0.0 || self.rng.next_f64() >= p {
+                    continue;
+                }
+
+                let qty = if model.partial_fills {
+                    ((order.remaining as f64 * p) as i64).clamp(1, order.remaining)
+                } else {
+                    order.remaining
+                };
+                order.remaining -= qty;
+                fills.push(LimitFill {
+                    client_hash: order.client_hash,
+                    filled_qty: qty,
+                    price: order.price,
+                    remaining: order.remaining,
+                    ts_ns,
+                });
+            }
+
+            self.resting.retain(|o| o.remaining > 0);
+            fills
+        }
+
+        /// Quantity consumed and not yet regenerated - fixed-point
+        pub fn pending_regeneration(&self) -> i64 {
+            self.consumed.values().map(|l| l.qty).sum()
+        }
+
+        /// Get statistics: (fills, levels regenerated)
+        pub fn stats(&self) -> (u64, u64) {
+            (self.total_fills, self.total_regenerated)
+        }
+    }
+
+    impl Default for FillSimulator {
+        fn default() -> Self {
+            Self::new(LiquidityConfig::default())
+        }
+    }
+}
+
+pub use simulation::*;
+
+#[cfg(test)]
+mod tests {
+    use super::simulation::*;
+    use crate::execution::{OrderRequest, OrderType, Side, FIXED_SCALE};
+    use crate::orderbook::{price_to_key, L2Orderbook, Price, Qty};
+    use crate::rng::PipelineRng;
+
+    /// Quantity in units of a default-scale book
+    fn units(qty: f64) -> i64 {
+        L2Orderbook::new(0).qty_to_units(qty)
+    }
+
+    fn seeded_book() -> L2Orderbook {
+        let mut book = L2Orderbook::new(1);
+        book.apply_delta(Price(99.0), Qty(1.0), true, 1);
+        book.apply_delta(Price(100.0), Qty(1.0), false, 2);
+        book.apply_delta(Price(101.0), Qty(2.0), false, 3);
+        book
+    }
+
+    #[test]
+    fn test_static_book_is_not_drained() {
+        let mut book = seeded_book();
+        let mut sim = FillSimulator::default();
+
+        let first = sim.simulate_market_fill(&mut book, true, units(1.0), 0);
+        let second = sim.simulate_market_fill(&mut book, true, units(1.0), 1);
+
+        assert_eq!(first, second);
+        assert_eq!(first.avg_price, price_to_key(100.0));
+    }
+
+    #[test]
+    fn test_consumed_level_regenerates_after_interval() {
+        let mut book = seeded_book();
+        let mut sim = FillSimulator::new(LiquidityConfig {
+            consume_liquidity: true,
+            regen_interval_ns: 1_000,
+        });
+
+        let fill = sim.simulate_market_fill(&mut book, true, units(1.0), 0);
+        assert_eq!(fill.filled_qty, units(1.0));
+        assert_eq!(book.best_ask(), Some(101.0));
+
+        // Next market buy walks the now-best level
+        let fill = sim.simulate_market_fill(&mut book, true, units(0.5), 500);
+        assert_eq!(fill.avg_price, price_to_key(101.0));
+
+        // 100.0 consumed at t=0 regenerates at t=1000; 101.0 still pending
+        assert_eq!(sim.regenerate(&mut book, 1_000), 1);
+        assert_eq!(book.best_ask(), Some(100.0));
+        assert_eq!(book.asks[&price_to_key(100.0)], units(1.0));
+        assert_eq!(book.asks[&price_to_key(101.0)], units(1.5));
+
+        assert_eq!(sim.regenerate(&mut book, 1_500), 1);
+        assert_eq!(book.asks[&price_to_key(101.0)], units(2.0));
+        assert_eq!(sim.pending_regeneration(), 0);
+    }
+
+    #[test]
+    fn test_iceberg_shows_display_slices_until_exhausted() {
+        let mut book = L2Orderbook::new(1);
+        book.apply_delta(Price(99.0), Qty(1.0), true, 1);
+        let mut sim = FillSimulator::default();
+        let key = price_to_key(100.0);
+
+        let req = OrderRequest {
+            client_hash: 9,
+            side: Side::Sell,
+            quantity: units(2.5),
+            price: key,
+            order_type: OrderType::Limit,
+            display_qty: units(1.0),
+            ..Default::default()
+        };
+        assert!(sim.place_iceberg(&mut book, &req));
+        assert_eq!(book.asks[&key], units(1.0));
+
+        // Display filled -> next slice appears
+        let fill = sim.simulate_market_fill(&mut book, true, units(1.0), 0);
+        assert_eq!(fill.filled_qty, units(1.0));
+        assert_eq!(book.asks[&key], units(1.0));
+        assert_eq!(sim.iceberg(false, key).unwrap().hidden_qty, units(0.5));
+
+        sim.simulate_market_fill(&mut book, true, units(1.0), 1);
+        assert_eq!(book.asks[&key], units(0.5));
+
+        // Final partial slice -> order gone from book and simulator
+        let fill = sim.simulate_market_fill(&mut book, true, units(1.0), 2);
+        assert_eq!(fill.filled_qty, units(0.5));
+        assert!(book.asks.is_empty());
+        assert!(sim.iceberg(false, key).is_none());
+    }
+
+    #[test]
+    fn test_iceberg_converts_to_book_ticks_and_lots() {
+        // 0.5 tick (2 keys per 1.0) and 0.1 lot: 10 units per 1.0 qty
+        let mut book = L2Orderbook::with_lot_size(1, 0.1).unwrap().with_tick_scale(2);
+        let mut sim = FillSimulator::default();
+
+        let req = OrderRequest {
+            client_hash: 9,
+            side: Side::Buy,
+            quantity: 3 * FIXED_SCALE,
+            price: 99 * FIXED_SCALE + FIXED_SCALE / 2,
+            order_type: OrderType::Limit,
+            display_qty: FIXED_SCALE,
+            ..Default::default()
+        };
+        assert!(sim.place_iceberg(&mut book, &req));
+
+        let key = book.price_to_key(99.5).unwrap();
+        assert_eq!(key, 199);
+        assert_eq!(book.best_bid(), Some(99.5));
+        assert_eq!(book.bids[&key], 10);
+        let iceberg = sim.iceberg(true, key).unwrap();
+        assert_eq!((iceberg.total_qty, iceberg.hidden_qty), (30, 20));
+
+        // Only the 1.0 slice is visible to a sell of 1.5; the next one replaces it
+        let fill = sim.simulate_market_fill(&mut book, false, 15, 0);
+        assert_eq!(fill.filled_qty, 10);
+        assert_eq!(book.total_qty(true), 1.0);
+        let fill = sim.simulate_market_fill(&mut book, false, 5, 1);
+        assert_eq!(fill.filled_qty, 5);
+        assert_eq!(sim.iceberg(true, key).unwrap().filled_qty, 15);
+    }
+
+    fn resting_bid(sim: &mut FillSimulator, book: &L2Orderbook, price: f64) {
+        sim.rest_limit(&OrderRequest {
+            client_hash: 5,
+            side: Side::Buy,
+            quantity: book.qty_to_units(1.0),
+            price: book.price_to_key(price).unwrap(),
+            order_type: OrderType::Limit,
+            ..Default::default()
+        });
+    }
+
+    #[test]
+    fn test_limit_fills_when_mid_trades_through() {
+        let book = L2Orderbook::new(1);
+        let mut sim = FillSimulator::default();
+        resting_bid(&mut sim, &book, 100.0);
+
+        // Mid stays above the bid -> never fills
+        for ts in 0..1_000 {
+            assert!(sim.on_mid(&book, 100.5, ts).is_empty());
+        }
+        assert_eq!(sim.resting().len(), 1);
+
+        // Mid 1% through the bid -> certain under the default model
+        let fills = sim.on_mid(&book, 99.0, 1_000);
+        assert_eq!(fills.len(), 1);
+        assert_eq!(fills[0].filled_qty, book.qty_to_units(1.0));
+        assert_eq!(fills[0].price, price_to_key(100.0));
+        assert!(sim.resting().is_empty());
+    }
+
+    #[test]
+    fn test_limit_fills_use_book_tick_scale() {
+        // Cent ticks and whole-unit lots: keys and units are not 1e8-scaled
+        let book = L2Orderbook::with_lot_size(1, 1.0).unwrap().with_tick_scale(100);
+        let mut sim = FillSimulator::default();
+        resting_bid(&mut sim, &book, 100.0);
+        assert_eq!(sim.resting()[0].price, 10_000);
+        assert_eq!(sim.resting()[0].remaining, 1);
+
+        assert!(sim.on_mid(&book, 100.5, 0).is_empty());
+        assert!(sim.on_mid(&book, f64::NAN, 1).is_empty());
+        let fills = sim.on_mid(&book, 99.0, 2);
+        assert_eq!(fills.len(), 1);
+        assert_eq!(book.key_to_price(fills[0].price), 100.0);
+        assert_eq!(book.units_to_qty(fills[0].filled_qty), 1.0);
+    }
+
+    #[test]
+    fn test_limit_partial_fills_are_reproducible_by_seed() {
+        fn half(through_bps: f64) -> f64 {
+            if through_bps > 0.0 { 0.5 } else { 0.0 }
+        }
+        let model = LimitFillModel { probability: half, partial_fills: true };
+        let book = L2Orderbook::new(1);
+        let run = |seed| {
+            let mut sim = FillSimulator::with_limit_model(LiquidityConfig::default(), model, &PipelineRng::new(seed));
+            resting_bid(&mut sim, &book, 100.0);
+            (0..20).flat_map(|ts| sim.on_mid(&book, 99.9, ts)).collect::<Vec<_>>()
+        };
+
+        let fills = run(7);
+        assert_eq!(fills, run(7));
+        assert!(fills.iter().all(|f| f.filled_qty <= book.qty_to_units(0.5)));
+    }
+
+    /// Seeded mid random walk driving resting limits; returns the
+    /// published fill bytes
+    fn run_pipeline(seed: u64) -> Vec<u8> {
+        let rng = PipelineRng::new(seed);
+        let mut walk = rng.stream("mid_walk");
+        let model = LimitFillModel { partial_fills: true, ..LimitFillModel::default() };
+        let mut sim = FillSimulator::with_limit_model(LiquidityConfig::default(), model, &rng);
+        let book = L2Orderbook::new(1);
+
+        let mut published = Vec::new();
+        let mut mid = 100.0;
+        for ts in 0..2_000 {
+            if ts % 50 == 0 {
+                resting_bid(&mut sim, &book, mid - 0.05);
+            }
+            mid += walk.next_signed() * 0.05;
+            for fill in sim.on_mid(&book, mid, ts) {
+                published.extend_from_slice(&fill.client_hash.to_le_bytes());
+                published.extend_from_slice(&fill.filled_qty.to_le_bytes());
+                published.extend_from_slice(&fill.price.to_le_bytes());
+                published.extend_from_slice(&fill.ts_ns.to_le_bytes());
+            }
+        }
+        published
+    }
+
+    #[test]
+    fn test_same_seed_gives_byte_identical_output() {
+        let first = run_pipeline(2024);
+        assert!(!first.is_empty());
+        assert_eq!(first, run_pipeline(2024));
+        assert_ne!(first, run_pipeline(2025));
+    }
+
+    #[test]
+    fn test_market_fill_walks_levels_pro_rata() {
+        let mut book = seeded_book();
+        let mut sim = FillSimulator::default();
+
+        // 1.0 @ 100 + 1.0 @ 101 -> avg 100.5
+        let fill = sim.simulate_market_fill(&mut book, true, units(2.0), 0);
+        assert_eq!(fill.levels_touched, 2);
+        assert_eq!(fill.avg_price, price_to_key(100.5));
+    }
+}